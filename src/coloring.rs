@@ -0,0 +1,208 @@
+//! Strategies for deriving the value that a palette colors for each point.
+//!
+//! Every mode produces an `Escape`, so any palette can color any mode. Interior points are `None`
+//! regardless of the mode.
+
+use crate::ematrix::{self, EMatrix};
+use crate::Escape;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum ColorMode {
+    /// Color by the smoothed escape iteration count.
+    #[default]
+    Escape,
+
+    /// Color by the estimated distance to the set boundary, measured in pixels.
+    ///
+    /// This picks out thin filaments that the escape count blurs together.
+    Distance,
+
+    /// Color by the magnitude of the orbit derivative at escape, i.e. the escape velocity.
+    ///
+    /// Velocities span many orders of magnitude, so the log of the velocity is normalized across
+    /// each escape matrix.
+    Velocity,
+}
+
+impl ColorMode {
+    /// Scale applied to the log of the distance estimate so it spans a range similar to escape
+    /// iteration counts.
+    pub const DISTANCE_SCALE: f64 = 10.;
+
+    /// Map a distance estimate to a value suitable for coloring.
+    ///
+    /// - `distance`: the estimated distance to the set boundary.
+    /// - `scalar`: the size of a pixel in the complex plane.
+    pub fn distance_value(distance: f64, scalar: f64) -> f64 {
+        Self::DISTANCE_SCALE * (1. + distance / scalar).ln()
+    }
+
+    /// Map an escape velocity to a value suitable for coloring, prior to normalization.
+    pub fn velocity_value(velocity: f64) -> f64 {
+        velocity.max(1.).ln()
+    }
+
+    /// Apply any normalization this mode needs across a complete frame.
+    ///
+    /// Velocity values are rescaled to span half of the color period, sweeping from one end of the
+    /// palette to the other across the frame.
+    pub fn normalize(self, ematrix: EMatrix, color_period: f64) -> EMatrix {
+        let mut ematrix = ematrix;
+        self.normalize_escapes(ematrix.as_mut_slice(), color_period);
+        ematrix
+    }
+
+    /// Apply the normalization of `normalize` in place to the escapes of a complete frame.
+    pub fn normalize_escapes(self, escapes: &mut [Escape], color_period: f64) {
+        match self {
+            ColorMode::Velocity => ematrix::normalize_escapes(escapes, color_period / 2.),
+            ColorMode::Escape | ColorMode::Distance => {}
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ColorModeParseError(String);
+
+impl std::fmt::Display for ColorModeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to color mode", self.0)
+    }
+}
+
+impl std::error::Error for ColorModeParseError {}
+
+impl std::str::FromStr for ColorMode {
+    type Err = ColorModeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "escape" => Ok(ColorMode::Escape),
+            "distance" => Ok(ColorMode::Distance),
+            "velocity" => Ok(ColorMode::Velocity),
+            _ => Err(ColorModeParseError(s.to_string())),
+        }
+    }
+}
+
+/// An operator that compresses escape values with a high dynamic range before they're colored.
+///
+/// Values are first exposed relative to the mean escape of the frame, so the curve operators adapt
+/// to the range of each frame rather than being dominated by its extremes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMap {
+    /// Leave escape values unchanged.
+    None,
+
+    /// The Reinhard operator `x / (1 + x)`, which compresses highlights smoothly.
+    Reinhard,
+
+    /// Narkowicz's fit of the ACES filmic curve, which adds contrast in the midtones.
+    Filmic,
+
+    /// Histogram equalization, which maps each escape to its cumulative rank in the frame so
+    /// colors are evenly distributed across the image.
+    Equalize,
+}
+
+impl ToneMap {
+    /// Map an exposed value into `[0, 1]`.
+    fn map(self, x: f64) -> f64 {
+        match self {
+            ToneMap::None | ToneMap::Equalize => x,
+            ToneMap::Reinhard => x / (1. + x),
+            ToneMap::Filmic => {
+                let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+                mapped.clamp(0., 1.)
+            }
+        }
+    }
+
+    /// Tone map the escapes of a frame into `[0, max]`, preserving their order.
+    pub fn apply(self, ematrix: &EMatrix, max: f64) -> EMatrix {
+        match self {
+            ToneMap::None => return ematrix.clone(),
+            ToneMap::Equalize => return ematrix.equalize(max),
+            ToneMap::Reinhard | ToneMap::Filmic => {}
+        }
+
+        let (sum, count) = ematrix
+            .iter()
+            .flatten()
+            .fold((0., 0usize), |(sum, count), v| (sum + v.max(0.), count + 1));
+        let mean = if count > 0 { sum / count as f64 } else { 0. };
+        if mean <= 0. {
+            return ematrix.clone();
+        }
+
+        ematrix.map_escapes(|escape| escape.map(|v| self.map(v.max(0.) / mean) * max))
+    }
+}
+
+#[derive(Debug)]
+pub struct ToneMapParseError(String);
+
+impl std::fmt::Display for ToneMapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to tone map", self.0)
+    }
+}
+
+impl std::error::Error for ToneMapParseError {}
+
+impl std::str::FromStr for ToneMap {
+    type Err = ToneMapParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(ToneMap::None),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            "filmic" => Ok(ToneMap::Filmic),
+            "equalize" => Ok(ToneMap::Equalize),
+            _ => Err(ToneMapParseError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tone_maps_compress_high_dynamic_range_escapes_in_order() {
+        // Escapes spanning ten orders of magnitude, followed by an interior cell.
+        let mut escapes: Vec<Escape> = (-4..=6).map(|e| Some(10f64.powi(e))).collect();
+        escapes.push(None);
+        let ematrix = EMatrix::from_vec(1, escapes.len(), escapes).unwrap();
+
+        for tone_map in [ToneMap::Reinhard, ToneMap::Filmic] {
+            let mapped = tone_map.apply(&ematrix, 255.);
+            let values: Vec<f64> = mapped.iter().flatten().copied().collect();
+
+            assert_eq!(values.len(), 11, "{:?}", tone_map);
+            assert_eq!(mapped.iter().last(), Some(&None), "{:?}", tone_map);
+            assert!(
+                values.iter().all(|v| (0. ..=255.).contains(v)),
+                "{:?} mapped to {:?}",
+                tone_map,
+                values
+            );
+            assert!(
+                values.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{:?} mapped to {:?}",
+                tone_map,
+                values
+            );
+        }
+
+        // Reinhard never saturates, so distinct escapes stay distinct.
+        let reinhard: Vec<f64> = ToneMap::Reinhard
+            .apply(&ematrix, 255.)
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        assert!(reinhard.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
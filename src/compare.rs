@@ -0,0 +1,153 @@
+//! Pixel-wise comparison of rendered images.
+//!
+//! This is primarily a validation aid for confirming that a rendering change didn't alter output.
+
+use image::{GrayImage, RgbImage};
+
+/// Summary statistics for the per-channel difference between two images.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageDiff {
+    /// The largest absolute difference of any channel in any pixel.
+    pub max: u8,
+
+    /// The mean absolute difference over every channel of every pixel.
+    pub mean: f64,
+
+    /// The peak signal to noise ratio in decibels. Identical images have an infinite PSNR.
+    pub psnr: f64,
+}
+
+impl ImageDiff {
+    /// Compare two images of identical dimensions.
+    ///
+    /// Empty images have no differing pixels, so they compare as identical.
+    pub fn between(a: &RgbImage, b: &RgbImage) -> Result<Self, crate::Error> {
+        check_dimensions(a, b)?;
+
+        let mut max: u8 = 0;
+        let mut abs_sum: f64 = 0.;
+        let mut sq_sum: f64 = 0.;
+
+        for (pa, pb) in a.pixels().zip(b.pixels()) {
+            for (ca, cb) in pa.0.iter().zip(pb.0.iter()) {
+                let delta = ca.abs_diff(*cb);
+                max = max.max(delta);
+                abs_sum += f64::from(delta);
+                sq_sum += f64::from(delta) * f64::from(delta);
+            }
+        }
+
+        let samples = (a.width() as f64) * (a.height() as f64) * 3.;
+        if samples == 0. {
+            return Ok(Self {
+                max,
+                mean: 0.,
+                psnr: f64::INFINITY,
+            });
+        }
+        let mse = sq_sum / samples;
+
+        Ok(Self {
+            max,
+            mean: abs_sum / samples,
+            psnr: 10. * (255. * 255. / mse).log10(),
+        })
+    }
+}
+
+/// Generate a grayscale heatmap of the per-pixel difference between two images.
+///
+/// Each pixel takes the largest channel difference at that position, scaled so that the largest
+/// difference in the image is white. Identical images produce a black heatmap.
+pub fn heatmap(a: &RgbImage, b: &RgbImage) -> Result<GrayImage, crate::Error> {
+    check_dimensions(a, b)?;
+
+    let deltas: Vec<u8> = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|(pa, pb)| {
+            pa.0.iter()
+                .zip(pb.0.iter())
+                .map(|(ca, cb)| ca.abs_diff(*cb))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let peak = deltas.iter().cloned().max().unwrap_or(0);
+    let scaled = deltas
+        .into_iter()
+        .map(|d| match peak {
+            0 => 0,
+            p => (u32::from(d) * 255 / u32::from(p)) as u8,
+        })
+        .collect();
+
+    Ok(GrayImage::from_raw(a.width(), a.height(), scaled).unwrap())
+}
+
+fn check_dimensions(a: &RgbImage, b: &RgbImage) -> Result<(), crate::Error> {
+    if a.dimensions() == b.dimensions() {
+        Ok(())
+    } else {
+        Err(crate::Error::from(format!(
+            "Cannot compare images with dimensions {:?} and {:?}",
+            a.dimensions(),
+            b.dimensions()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images_have_no_difference() {
+        let img = RgbImage::from_fn(8, 6, |x, y| image::Rgb([x as u8 * 30, y as u8 * 40, 7]));
+        let diff = ImageDiff::between(&img, &img.clone()).unwrap();
+
+        assert_eq!(diff.max, 0);
+        assert_eq!(diff.mean, 0.);
+        assert_eq!(diff.psnr, f64::INFINITY);
+        assert!(heatmap(&img, &img).unwrap().pixels().all(|p| p.0 == [0]));
+    }
+
+    #[test]
+    fn a_changed_pixel_reports_its_difference() {
+        let a = RgbImage::from_pixel(4, 4, image::Rgb([100, 100, 100]));
+        let mut b = a.clone();
+        b.put_pixel(2, 1, image::Rgb([100, 140, 90]));
+        let diff = ImageDiff::between(&a, &b).unwrap();
+
+        assert_eq!(diff.max, 40);
+        assert!((diff.mean - 50. / 48.).abs() < 1e-12);
+        assert!(diff.psnr.is_finite());
+
+        let heatmap = heatmap(&a, &b).unwrap();
+        assert_eq!(heatmap.get_pixel(2, 1).0, [255]);
+        assert_eq!(heatmap.get_pixel(0, 0).0, [0]);
+    }
+
+    #[test]
+    fn images_of_different_dimensions_are_an_error() {
+        let a = RgbImage::new(4, 4);
+        let b = RgbImage::new(4, 5);
+
+        assert!(ImageDiff::between(&a, &b).is_err());
+        assert!(heatmap(&a, &b).is_err());
+    }
+
+    #[test]
+    fn empty_images_are_identical() {
+        for (width, height) in [(0, 0), (0, 4), (4, 0)] {
+            let img = RgbImage::new(width, height);
+            let diff = ImageDiff::between(&img, &img).unwrap();
+
+            assert_eq!(diff.max, 0);
+            assert_eq!(diff.mean, 0.);
+            assert_eq!(diff.psnr, f64::INFINITY);
+            assert_eq!(heatmap(&img, &img).unwrap().dimensions(), (width, height));
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Contact sheets tile renders of the same location at a range of exponents into one image.
+
+use crate::palette::Palette;
+use crate::polycomplex::ComplexFn;
+use crate::rctx::Rctx;
+use crate::Bounds;
+use image::{Rgb, RgbImage};
+
+/// An inclusive range of exponents, parsed from `start,end,step`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExpRange {
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl ExpRange {
+    /// Generate each exponent in the range.
+    ///
+    /// The end is included when it falls on a step, allowing for accumulated rounding error.
+    pub fn values(&self) -> Vec<f64> {
+        if self.step <= 0. || self.end < self.start {
+            return vec![self.start];
+        }
+
+        let count = ((self.end - self.start) / self.step + 1e-9).floor() as usize + 1;
+        (0..count)
+            .map(|i| self.start + self.step * i as f64)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct ExpRangeParseError(String);
+
+impl std::fmt::Display for ExpRangeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot parse {} to exponent range start,end,step",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ExpRangeParseError {}
+
+impl std::str::FromStr for ExpRange {
+    type Err = ExpRangeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<f64> = s
+            .split(',')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| ExpRangeParseError(s.to_string()))?;
+
+        match parts[..] {
+            [start, end, step] if step > 0. => Ok(ExpRange { start, end, step }),
+            _ => Err(ExpRangeParseError(s.to_string())),
+        }
+    }
+}
+
+/// Generate the palette phase offsets for a series of `count` renders, advancing by `delta` for
+/// each render so the series has a cohesive color progression.
+pub fn phase_offsets(delta: f64, count: usize) -> Vec<f64> {
+    (0..count).map(|i| delta * i as f64).collect()
+}
+
+/// Render a grid of cells showing the rendering context at each exponent, labeled with the
+/// exponent.
+///
+/// - `image`: the bounds the rendering context's location was authored for.
+/// - `cell`: the bounds of each cell in the grid.
+/// - `palettes`: the palettes cells are colored with, in order, cycling when there are fewer
+///   palettes than exponents.
+pub fn contact_sheet(
+    rctx: &Rctx,
+    image: &Bounds,
+    exps: &[f64],
+    cols: u32,
+    cell: Bounds,
+    palettes: &[Box<dyn Palette>],
+) -> RgbImage {
+    let cols = cols.max(1);
+    let rows = (exps.len() as u32).div_ceil(cols);
+    let (cell_width, cell_height) = (u32::from(cell.width), u32::from(cell.height));
+    let mut sheet = RgbImage::new(cols * cell_width, rows * cell_height);
+
+    let mut cell_rctx = rctx.rescaled(image, &cell);
+    for (i, exp) in exps.iter().enumerate() {
+        *cell_rctx.complexfn.exp_mut() = *exp;
+        let palette = &palettes[i % palettes.len()];
+        let mut img = cell_rctx.bind(cell).to_ematrix().to_img(palette.as_ref());
+        label(&mut img, &format!("{}", exp));
+
+        let (col, row) = (i as u32 % cols, i as u32 / cols);
+        image::imageops::replace(
+            &mut sheet,
+            &img,
+            i64::from(col * cell_width),
+            i64::from(row * cell_height),
+        );
+    }
+
+    sheet
+}
+
+/// Glyphs of a 3x5 pixel font, with each row packed into the low 3 bits.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    match c {
+        '0' => Some([0b111, 0b101, 0b101, 0b101, 0b111]),
+        '1' => Some([0b010, 0b110, 0b010, 0b010, 0b111]),
+        '2' => Some([0b111, 0b001, 0b111, 0b100, 0b111]),
+        '3' => Some([0b111, 0b001, 0b111, 0b001, 0b111]),
+        '4' => Some([0b101, 0b101, 0b111, 0b001, 0b001]),
+        '5' => Some([0b111, 0b100, 0b111, 0b001, 0b111]),
+        '6' => Some([0b111, 0b100, 0b111, 0b101, 0b111]),
+        '7' => Some([0b111, 0b001, 0b001, 0b001, 0b001]),
+        '8' => Some([0b111, 0b101, 0b111, 0b101, 0b111]),
+        '9' => Some([0b111, 0b101, 0b111, 0b001, 0b111]),
+        '.' => Some([0b000, 0b000, 0b000, 0b000, 0b010]),
+        '-' => Some([0b000, 0b000, 0b111, 0b000, 0b000]),
+        _ => None,
+    }
+}
+
+/// Draw a numeric label in white on a black box in the top left corner of an image.
+///
+/// Characters without a glyph are skipped.
+fn label(img: &mut RgbImage, text: &str) {
+    const SCALE: u32 = 2;
+    const ADVANCE: u32 = 4 * SCALE;
+    const MARGIN: u32 = SCALE;
+
+    let glyphs: Vec<[u8; 5]> = text.chars().filter_map(glyph).collect();
+    let width = MARGIN * 2 + glyphs.len() as u32 * ADVANCE;
+    let height = MARGIN * 2 + 5 * SCALE;
+
+    for y in 0..height.min(img.height()) {
+        for x in 0..width.min(img.width()) {
+            img.put_pixel(x, y, Rgb([0, 0, 0]));
+        }
+    }
+
+    for (i, rows) in glyphs.iter().enumerate() {
+        for (gy, bits) in rows.iter().enumerate() {
+            for gx in 0..3 {
+                if bits & (0b100 >> gx) == 0 {
+                    continue;
+                }
+                for dy in 0..SCALE {
+                    for dx in 0..SCALE {
+                        let x = MARGIN + i as u32 * ADVANCE + gx * SCALE + dx;
+                        let y = MARGIN + gy as u32 * SCALE + dy;
+                        if x < img.width() && y < img.height() {
+                            img.put_pixel(x, y, Rgb([255, 255, 255]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contact_sheets_tile_a_distinct_cell_per_exponent() {
+        let exps = "2,6,1".parse::<ExpRange>().unwrap().values();
+        assert_eq!(exps, vec![2., 3., 4., 5., 6.]);
+
+        let rctx = Rctx::default();
+        let cell = Bounds {
+            width: 40,
+            height: 30,
+        };
+        let sheet = contact_sheet(&rctx, &cell, &exps, 2, cell, &[rctx.palette()]);
+
+        // Five cells in two columns take three rows.
+        assert_eq!(sheet.dimensions(), (2 * 40, 3 * 30));
+
+        let cells: Vec<RgbImage> = (0..exps.len() as u32)
+            .map(|i| image::imageops::crop_imm(&sheet, i % 2 * 40, i / 2 * 30, 40, 30).to_image())
+            .collect();
+        for (i, a) in cells.iter().enumerate() {
+            for b in &cells[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn consecutive_renders_advance_the_phase_offset() {
+        assert_eq!(phase_offsets(0.25, 3), vec![0., 0.25, 0.5]);
+        assert_eq!(phase_offsets(-0.5, 3), vec![0., -0.5, -1.]);
+        assert!(phase_offsets(0.25, 0).is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+//! Double-double arithmetic represents a value as the unevaluated sum of two `f64`s, giving
+//! roughly 106 bits of mantissa.
+//!
+//! This extends the zoom depth that orbits can resolve to around `1e-28`, well past the `1e-13`
+//! where `f64` starts rendering blocks, at a fraction of the cost of arbitrary precision.
+
+use num::complex::Complex64;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The Veltkamp splitter for `f64`, `2^27 + 1`.
+const SPLITTER: f64 = 134_217_729.;
+
+/// Add two floats, returning the rounded sum and its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// Add two floats where `|a| >= |b|`, returning the rounded sum and its rounding error.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// Split a float into high and low halves with non-overlapping 26 bit mantissas.
+fn split(a: f64) -> (f64, f64) {
+    let t = SPLITTER * a;
+    let hi = t - (t - a);
+    (hi, a - hi)
+}
+
+/// Multiply two floats, returning the rounded product and its rounding error.
+///
+/// Dekker's product is used rather than `mul_add`, which falls back to a slow software fused
+/// multiply-add on targets without hardware support.
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    let (ah, al) = split(a);
+    let (bh, bl) = split(b);
+    (p, ((ah * bh - p) + ah * bl + al * bh) + al * bl)
+}
+
+/// A value held as the unevaluated sum `hi + lo`, where `lo` is below half an ulp of `hi`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    /// The relative precision of double-double arithmetic, `2^-104`.
+    pub const EPSILON: f64 = 4.930_380_657_631_324e-32;
+
+    /// Round the value to the nearest `f64`.
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    /// The absolute value.
+    pub fn abs(self) -> Self {
+        if self.hi < 0. {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// Square the value, which is cheaper than a general multiplication.
+    pub fn square(self) -> Self {
+        let (p, e) = two_prod(self.hi, self.hi);
+        let (hi, lo) = quick_two_sum(p, e + 2. * self.hi * self.lo);
+        Self { hi, lo }
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(hi: f64) -> Self {
+        Self { hi, lo: 0. }
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (s, e) = two_sum(self.hi, rhs.hi);
+        let (t, f) = two_sum(self.lo, rhs.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        let (hi, lo) = quick_two_sum(s, e + f);
+        Self { hi, lo }
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let (p, e) = two_prod(self.hi, rhs.hi);
+        let (hi, lo) = quick_two_sum(p, e + (self.hi * rhs.lo + self.lo * rhs.hi));
+        Self { hi, lo }
+    }
+}
+
+/// A complex number with double-double components.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDoubleComplex {
+    pub re: DoubleDouble,
+    pub im: DoubleDouble,
+}
+
+impl DoubleDoubleComplex {
+    pub fn new(re: DoubleDouble, im: DoubleDouble) -> Self {
+        Self { re, im }
+    }
+
+    /// Round the components to the nearest `f64`s.
+    pub fn to_complex64(self) -> Complex64 {
+        Complex64::new(self.re.to_f64(), self.im.to_f64())
+    }
+
+    /// The squared magnitude, rounded to `f64`.
+    ///
+    /// This is only used to compare against escape thresholds, where `f64` is plenty.
+    pub fn norm_sqr(self) -> f64 {
+        let (re, im) = (self.re.hi, self.im.hi);
+        re * re + im * im
+    }
+
+    /// The complex conjugate.
+    pub fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    /// Square the value.
+    pub fn square(self) -> Self {
+        Self {
+            re: self.re.square() - self.im.square(),
+            im: {
+                let product = self.re * self.im;
+                product + product
+            },
+        }
+    }
+
+    /// Raise the value to a positive integer power by repeated multiplication.
+    pub fn powu(self, exp: u32) -> Self {
+        match exp {
+            0 => Self::from(Complex64::new(1., 0.)),
+            2 => self.square(),
+            _ => (1..exp).fold(self, |acc, _| acc * self),
+        }
+    }
+}
+
+impl From<Complex64> for DoubleDoubleComplex {
+    fn from(c: Complex64) -> Self {
+        Self {
+            re: DoubleDouble::from(c.re),
+            im: DoubleDouble::from(c.im),
+        }
+    }
+}
+
+impl Add for DoubleDoubleComplex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Mul for DoubleDoubleComplex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+/// The precision orbits are iterated with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum Precision {
+    /// Iterate in `f64`, switching to double-double once pixels are smaller than
+    /// `DOUBLE_DOUBLE_SCALAR`.
+    #[default]
+    Auto,
+
+    /// Always iterate in `f64`.
+    Double,
+
+    /// Always iterate in double-double.
+    DoubleDouble,
+}
+
+impl Precision {
+    /// The pixel size below which `f64` orbits can no longer tell neighboring pixels apart
+    /// reliably, so `Auto` switches to double-double.
+    pub const DOUBLE_DOUBLE_SCALAR: f64 = 1e-13;
+
+    /// Determine if orbits are iterated in double-double at the given pixel size.
+    pub fn uses_double_double(self, scalar: f64) -> bool {
+        match self {
+            Precision::Auto => scalar < Self::DOUBLE_DOUBLE_SCALAR,
+            Precision::Double => false,
+            Precision::DoubleDouble => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PrecisionParseError(String);
+
+impl std::fmt::Display for PrecisionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to precision", self.0)
+    }
+}
+
+impl std::error::Error for PrecisionParseError {}
+
+impl std::str::FromStr for Precision {
+    type Err = PrecisionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Precision::Auto),
+            "double" => Ok(Precision::Double),
+            "double-double" => Ok(Precision::DoubleDouble),
+            _ => Err(PrecisionParseError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_keep_the_bits_f64_rounds_away() {
+        let (a, b, c) = (
+            DoubleDouble::from(0.1),
+            DoubleDouble::from(0.2),
+            DoubleDouble::from(0.3),
+        );
+        // The exact sum of 0.1 and 0.2 exceeds 0.3 by 2^-55, which f64 rounds to 2^-54.
+        assert_eq!(((a + b) - c).to_f64(), 2f64.powi(-55));
+        assert_eq!(0.1 + 0.2 - 0.3, 2f64.powi(-54));
+
+        let big = DoubleDouble::from(1e10);
+        let tiny = DoubleDouble::from(1e-10);
+        assert_eq!(
+            big + tiny,
+            DoubleDouble {
+                hi: 1e10,
+                lo: 1e-10
+            }
+        );
+        assert_eq!(((big + tiny) - big).to_f64(), 1e-10);
+    }
+
+    #[test]
+    fn products_match_an_exact_reference() {
+        // (1 + 2^-30)^2 = 1 + 2^-29 + 2^-60, which needs more than 53 bits.
+        let x = DoubleDouble::from(1. + 2f64.powi(-30));
+        let expected = DoubleDouble {
+            hi: 1. + 2f64.powi(-29),
+            lo: 2f64.powi(-60),
+        };
+        assert_eq!(x * x, expected);
+        assert_eq!(x.square(), expected);
+
+        // The rounding error of a product is exactly what a fused multiply-add recovers.
+        for (a, b) in [(0.1, 0.1), (1. / 3., 3.), (-123.456, 7.89e-5)] {
+            let product = DoubleDouble::from(a) * DoubleDouble::from(b);
+            assert_eq!(product.hi, a * b);
+            assert_eq!(product.lo, a.mul_add(b, -(a * b)), "{} * {}", a, b);
+        }
+
+        let z = DoubleDoubleComplex::from(Complex64::new(0.3, -0.7));
+        let cubed = z.powu(3).to_complex64();
+        let reference = Complex64::new(0.3, -0.7).powu(3);
+        assert!((cubed - reference).norm() < 1e-15);
+    }
+}
@@ -1,15 +1,86 @@
 //! An escape matrix contains an evaluated section of one of the complex polynomial functions.
 
-use crate::Escape;
+use crate::palette::{GradientPalette, Palette};
+use crate::{Escape, Pos};
+use rayon::prelude::*;
+use std::io::Write;
+
+/// The encoding of a PPM image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PpmFormat {
+    /// P3, with each channel written as decimal text.
+    Ascii,
+
+    /// P6, with each channel written as a raw byte.
+    Binary,
+}
+
+/// The bytes a full frame render holds per cell at its peak: the position evaluated, its escape
+/// and the normalized copy of the escape, and the colored pixel.
+pub const FRAME_BYTES_PER_CELL: usize =
+    std::mem::size_of::<Pos>() + 2 * std::mem::size_of::<Escape>() + 3;
+
+/// Linearly rescale escapes in place to span `[0, max]`, preserving interior points.
+///
+/// Escapes that are all equal are mapped to 0.
+pub fn normalize_escapes(escapes: &mut [Escape], max: f64) {
+    let (lo, hi) = escapes
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(*v), hi.max(*v))
+        });
+    let span = hi - lo;
+    escapes.par_iter_mut().flatten().for_each(|v| {
+        *v = if span > 0. {
+            (*v - lo) / span * max
+        } else {
+            0.
+        };
+    });
+}
 
 /// An EMatrix maps the cells in a frame to corresponding evaluated escapes.
 #[derive(Debug, Clone)]
 pub struct EMatrix(nalgebra::DMatrix<Escape>);
 
 impl EMatrix {
-    pub fn from_vec(ncols: usize, nrows: usize, v: Vec<Escape>) -> EMatrix {
-        let mat = nalgebra::DMatrix::from_vec(ncols, nrows, v);
-        Self(mat)
+    /// Build an escape matrix from escapes in column major order.
+    ///
+    /// Escapes that don't exactly fill the dimensions are an error rather than a panic.
+    pub fn from_vec(nrows: usize, ncols: usize, v: Vec<Escape>) -> Result<EMatrix, crate::Error> {
+        if nrows.checked_mul(ncols) != Some(v.len()) {
+            return Err(crate::Error::from(format!(
+                "Cannot build a {}x{} escape matrix from {} escapes",
+                ncols,
+                nrows,
+                v.len()
+            )));
+        }
+        Ok(Self(nalgebra::DMatrix::from_vec(nrows, ncols, v)))
+    }
+
+    /// Check that a frame with the given dimensions can be rendered and colored in memory.
+    ///
+    /// Failing to allocate aborts the process rather than panicking, so huge frames need to be
+    /// checked before they're evaluated to report an error instead. The probe reserves the peak
+    /// of a full render at once, `FRAME_BYTES_PER_CELL` for every cell. With memory overcommit
+    /// the reservation only claims address space, so it catches frames larger than the system
+    /// will ever hand out, but a frame that passes can still run out of memory part way.
+    pub fn check_capacity(nrows: usize, ncols: usize) -> Result<(), crate::Error> {
+        let too_large = || {
+            crate::Error::from(format!(
+                "Image too large: cannot allocate {}x{} escapes",
+                ncols, nrows
+            ))
+        };
+        let bytes = nrows
+            .checked_mul(ncols)
+            .and_then(|cells| cells.checked_mul(FRAME_BYTES_PER_CELL))
+            .ok_or_else(too_large)?;
+        Vec::<u8>::new()
+            .try_reserve_exact(bytes)
+            .map_err(|_| too_large())
     }
 
     pub fn from_dmatrix(mat: nalgebra::DMatrix<Escape>) -> Self {
@@ -28,6 +99,10 @@ impl EMatrix {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn nrows(&self) -> usize {
         self.0.nrows()
     }
@@ -46,13 +121,20 @@ impl EMatrix {
         &mut self.0
     }
 
-    pub fn iter(&self) -> EMatrixRefIterator {
+    /// Fetch the escapes as a mutable slice in column major order.
+    pub fn as_mut_slice(&mut self) -> &mut [Escape] {
+        self.0.as_mut_slice()
+    }
+
+    pub fn iter(&self) -> EMatrixRefIterator<'_> {
         EMatrixRefIterator {
             mat: self,
             index: 0,
         }
     }
 
+    // Predates the `IntoIterator` impl and is kept so existing callers don't break.
+    #[allow(clippy::should_implement_trait)]
     pub fn into_iter(self) -> EMatrixIterator {
         EMatrixIterator {
             mat: self,
@@ -60,16 +142,274 @@ impl EMatrix {
         }
     }
 
-    pub fn to_img(&self, colorer: &crate::SineRGB) -> image::RgbImage {
+    /// Iterate over each escape along with its position.
+    ///
+    /// Rows of the matrix correspond to `y` and columns to `x`. Escapes are yielded in the column
+    /// major storage order: every row of column `x = 0` from top to bottom, then column `x = 1`,
+    /// and so on.
+    pub fn enumerate_positions(&self) -> EMatrixPosIterator<'_> {
+        EMatrixPosIterator {
+            mat: self,
+            index: 0,
+        }
+    }
+
+    /// Create a new ematrix by applying a function to each escape.
+    pub fn map_escapes<F: Fn(Escape) -> Escape>(&self, f: F) -> Self {
+        Self(self.0.map(f))
+    }
+
+    /// Create a new ematrix by applying a function to each escape in parallel.
+    pub fn par_map_escapes<F: Fn(Escape) -> Escape + Sync>(&self, f: F) -> Self {
+        let escapes: Vec<Escape> = self.0.as_slice().par_iter().map(|e| f(*e)).collect();
+        Self::from_vec(self.0.nrows(), self.0.ncols(), escapes)
+            .expect("one escape is mapped per cell")
+    }
+
+    /// Linearly rescale the escapes to span `[0, max]`, preserving interior points.
+    ///
+    /// A matrix whose escapes are all equal is mapped to 0.
+    pub fn normalize(&self, max: f64) -> Self {
+        let mut normalized = self.clone();
+        normalize_escapes(normalized.as_mut_slice(), max);
+        normalized
+    }
+
+    /// Histogram equalize the escapes into `[0, max]`, preserving interior points.
+    ///
+    /// Each escape is replaced by its cumulative rank among the escapes of the matrix, so colors
+    /// are spread evenly across the image instead of bunching up where escapes cluster near the
+    /// set boundary. Equal escapes share a rank.
+    pub fn equalize(&self, max: f64) -> Self {
+        let mut sorted: Vec<f64> = self.iter().flatten().copied().collect();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let count = sorted.len() as f64;
+        self.par_map_escapes(|escape| {
+            escape.map(|v| sorted.partition_point(|s| *s <= v) as f64 / count * max)
+        })
+    }
+
+    /// Render the values as a heatmap, sampling the gradient linearly from 0 to the largest value.
+    ///
+    /// Cells without a value are colored as 0.
+    pub fn to_heatmap(&self, gradient: &GradientPalette) -> image::RgbImage {
+        let max = self.iter().flatten().fold(0., |max: f64, v| max.max(*v));
+        let mat = &self.0;
+
+        image::RgbImage::from_fn(mat.ncols() as u32, mat.nrows() as u32, move |x, y| {
+            let value = mat.index((y as usize, x as usize)).unwrap_or(0.);
+            let t = if max > 0. { value / max } else { 0. };
+            let (r, g, b) = gradient.sample(t);
+            image::Rgb([r, g, b])
+        })
+    }
+
+    pub fn to_img<P: Palette + ?Sized>(&self, colorer: &P) -> image::RgbImage {
         let mat = &self.0;
 
         image::RgbImage::from_fn(mat.ncols() as u32, mat.nrows() as u32, move |x, y| {
             let escape = mat.index((y as usize, x as usize));
-            let term_rgb = colorer.rgb(*escape);
+            let pos = Pos {
+                x: x as u16,
+                y: y as u16,
+            };
+            let term_rgb = colorer.rgb_at(pos, *escape);
             image::Rgb([term_rgb.0, term_rgb.1, term_rgb.2])
         })
     }
 
+    /// Color the escapes with transparent interior points, for compositing over other images.
+    ///
+    /// Escaped points are opaque and interior points have an alpha of 0, keeping the color the
+    /// palette gives them.
+    pub fn to_rgba_img<P: Palette + ?Sized>(&self, colorer: &P) -> image::RgbaImage {
+        let mat = &self.0;
+
+        image::RgbaImage::from_fn(mat.ncols() as u32, mat.nrows() as u32, move |x, y| {
+            let escape = mat.index((y as usize, x as usize));
+            let pos = Pos {
+                x: x as u16,
+                y: y as u16,
+            };
+            let (r, g, b) = colorer.rgb_at(pos, *escape);
+            let alpha = if escape.is_some() { 255 } else { 0 };
+            image::Rgba([r, g, b, alpha])
+        })
+    }
+
+    /// Color the escapes after histogram equalizing them into `[0, max]`.
+    ///
+    /// Interior points are excluded from the histogram and stay interior.
+    pub fn to_img_equalized<P: Palette + ?Sized>(&self, colorer: &P, max: f64) -> image::RgbImage {
+        self.equalize(max).to_img(colorer)
+    }
+
+    /// Color the escapes and write them as a PPM image, without going through the `image` crate.
+    ///
+    /// The pixels are identical to those of `to_img` with the same palette.
+    pub fn write_ppm<P: Palette + ?Sized, W: Write>(
+        &self,
+        colorer: &P,
+        format: PpmFormat,
+        mut writer: W,
+    ) -> Result<(), crate::Error> {
+        let (nrows, ncols) = (self.nrows(), self.ncols());
+        let magic = match format {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary => "P6",
+        };
+        write!(writer, "{}\n{} {}\n255\n", magic, ncols, nrows)?;
+
+        for row in 0..nrows {
+            let pixels = (0..ncols).map(|col| {
+                let pos = Pos {
+                    x: col as u16,
+                    y: row as u16,
+                };
+                colorer.rgb_at(pos, self.0[(row, col)])
+            });
+            match format {
+                PpmFormat::Ascii => {
+                    let line: Vec<String> = pixels
+                        .map(|(r, g, b)| format!("{} {} {}", r, g, b))
+                        .collect();
+                    writeln!(writer, "{}", line.join(" "))?;
+                }
+                PpmFormat::Binary => {
+                    let bytes: Vec<u8> = pixels.flat_map(|(r, g, b)| [r, g, b]).collect();
+                    writer.write_all(&bytes)?;
+                }
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Find the cell with the largest finite escape.
+    ///
+    /// The cell is returned as a `(row, column)` index, or `None` if no cell escaped. Interior
+    /// cells are ignored, and ties go to the first cell in storage order.
+    pub fn max_escape_position(&self) -> Option<(usize, usize)> {
+        let nrows = self.0.nrows();
+        let mut best: Option<(usize, f64)> = None;
+        for (index, escape) in self.0.iter().enumerate() {
+            if let Some(v) = escape.filter(|v| v.is_finite()) {
+                if best.is_none_or(|(_, max)| v > max) {
+                    best = Some((index, v));
+                }
+            }
+        }
+
+        best.map(|(index, _)| (index % nrows, index / nrows))
+    }
+
+    /// Find the centroid of the largest 4-connected region of interior cells.
+    ///
+    /// The centroid is returned as a `(row, column)` index, or `None` if there are no interior
+    /// cells. The centroid of a non-convex region may lie outside of the region itself.
+    pub fn largest_interior_centroid(&self) -> Option<(usize, usize)> {
+        let (nrows, ncols) = self.0.shape();
+        let mut visited = vec![false; nrows * ncols];
+        let mut best: Option<(usize, usize, usize)> = None;
+        let mut stack = Vec::new();
+
+        for col in 0..ncols {
+            for row in 0..nrows {
+                let index = col * nrows + row;
+                if visited[index] || self.0[(row, col)].is_some() {
+                    continue;
+                }
+
+                // Flood fill the region, accumulating the cell count and index sums.
+                let (mut count, mut row_sum, mut col_sum) = (0, 0, 0);
+                visited[index] = true;
+                stack.push((row, col));
+                while let Some((r, c)) = stack.pop() {
+                    count += 1;
+                    row_sum += r;
+                    col_sum += c;
+
+                    let neighbors = [
+                        (r.wrapping_sub(1), c),
+                        (r + 1, c),
+                        (r, c.wrapping_sub(1)),
+                        (r, c + 1),
+                    ];
+                    for &(nr, nc) in neighbors.iter() {
+                        if nr < nrows && nc < ncols {
+                            let ni = nc * nrows + nr;
+                            if !visited[ni] && self.0[(nr, nc)].is_none() {
+                                visited[ni] = true;
+                                stack.push((nr, nc));
+                            }
+                        }
+                    }
+                }
+
+                if best.is_none_or(|(best_count, _, _)| count > best_count) {
+                    best = Some((count, row_sum, col_sum));
+                }
+            }
+        }
+
+        best.map(|(count, row_sum, col_sum)| (row_sum / count, col_sum / count))
+    }
+
+    /// Measure how far each cell is from the nearest escaped cell.
+    ///
+    /// Each cell maps to its distance in cells from the nearest escaped cell along with the
+    /// escape of that cell, in column major order. Escaped cells are at a distance of 0 from
+    /// themselves. Distances are a chamfer approximation of the euclidean distance, stepping 1
+    /// along rows and columns and √2 along diagonals. Every cell of a frame without escaped cells
+    /// maps to `None`.
+    pub fn boundary_distances(&self) -> Vec<Option<(f64, f64)>> {
+        let (nrows, ncols) = self.0.shape();
+        let mut nearest: Vec<Option<(f64, f64)>> = self
+            .0
+            .iter()
+            .map(|escape| escape.map(|e| (0., e)))
+            .collect();
+
+        let diagonal = std::f64::consts::SQRT_2;
+        let mut relax = |row: usize, col: usize, offsets: &[(isize, isize, f64)]| {
+            for &(dr, dc, step) in offsets {
+                let (r, c) = (row.wrapping_add(dr as usize), col.wrapping_add(dc as usize));
+                if r >= nrows || c >= ncols {
+                    continue;
+                }
+                if let Some((distance, escape)) = nearest[c * nrows + r] {
+                    let cell = &mut nearest[col * nrows + row];
+                    if cell.is_none_or(|(best, _)| distance + step < best) {
+                        *cell = Some((distance + step, escape));
+                    }
+                }
+            }
+        };
+
+        // The forward pass carries distances from the cells visited before each cell in storage
+        // order, and the backward pass from the cells visited after it.
+        let before = [
+            (-1, 0, 1.),
+            (-1, -1, diagonal),
+            (0, -1, 1.),
+            (1, -1, diagonal),
+        ];
+        let after = before.map(|(dr, dc, step)| (-dr, -dc, step));
+        for col in 0..ncols {
+            for row in 0..nrows {
+                relax(row, col, &before);
+            }
+        }
+        for col in (0..ncols).rev() {
+            for row in (0..nrows).rev() {
+                relax(row, col, &after);
+            }
+        }
+
+        nearest
+    }
+
     /// Create a new ematrix with a gaussian blur
     pub fn gaussian_blur(&self) -> Self {
         let kernel = nalgebra::base::Matrix3::new(1., 2., 1., 2., 16., 2., 1., 2., 1.);
@@ -81,12 +421,12 @@ impl EMatrix {
             |yi, xi| {
                 if yi == 0 || xi == 0 || yi == self.0.nrows() - 1 || xi == self.0.ncols() - 1 {
                     // Gaussian blurring along image edges isn't implemented.
-                    self.0.index((yi, xi)).clone()
+                    *self.0.index((yi, xi))
                 } else {
                     match self.0.index((yi, xi)) {
                         None => None,
                         Some(_) => {
-                            let area = self.0.slice((yi - 1, xi - 1), (3, 3));
+                            let area = self.0.view((yi - 1, xi - 1), (3, 3));
 
                             // Accumulate the region described by the kernel.
                             let acc = area.zip_fold(&kernel, None, |acc, escape, k| {
@@ -136,7 +476,7 @@ impl std::iter::Iterator for EMatrixIterator {
         if self.index >= self.mat.len() {
             None
         } else {
-            let esc: Escape = self.mat.0.index(self.index).clone(); // XXX bad memory allocation
+            let esc: Escape = *self.mat.0.index(self.index); // XXX bad memory allocation
             self.index += 1;
             Some(esc)
         }
@@ -187,3 +527,219 @@ impl<'a> IntoIterator for &'a EMatrix {
         }
     }
 }
+
+/// An iterator over the positions and escapes of an `&EMatrix` in column major order.
+pub struct EMatrixPosIterator<'a> {
+    mat: &'a EMatrix,
+    index: usize,
+}
+
+impl<'a> std::iter::Iterator for EMatrixPosIterator<'a> {
+    type Item = (Pos, &'a Escape);
+
+    fn next(&mut self) -> Option<(Pos, &'a Escape)> {
+        if self.index >= self.mat.len() {
+            None
+        } else {
+            let nrows = self.mat.nrows();
+            let pos = Pos {
+                x: (self.index / nrows) as u16,
+                y: (self.index % nrows) as u16,
+            };
+            let esc: &Escape = self.mat.0.index(self.index);
+            self.index += 1;
+            Some((pos, esc))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_vec_rejects_mismatched_lengths() {
+        assert!(EMatrix::from_vec(2, 3, vec![None; 5]).is_err());
+        assert!(EMatrix::from_vec(2, 3, vec![None; 7]).is_err());
+        assert!(EMatrix::from_vec(usize::MAX, 2, vec![]).is_err());
+
+        let ematrix = EMatrix::from_vec(2, 3, vec![Some(1.); 6]).unwrap();
+        assert_eq!((ematrix.nrows(), ematrix.ncols()), (2, 3));
+    }
+
+    #[test]
+    fn check_capacity_rejects_frames_that_cannot_be_addressed() {
+        assert!(EMatrix::check_capacity(usize::MAX, 2).is_err());
+        assert!(EMatrix::check_capacity(1 << 40, 1 << 20).is_err());
+        assert!(EMatrix::check_capacity(400, 400).is_ok());
+    }
+
+    #[test]
+    fn the_largest_interior_blob_has_its_centroid_returned() {
+        // A 2x2 blob in the top left corner and a 3x3 blob centered on (5, 6).
+        let interior = |row: usize, col: usize| {
+            (row < 2 && col < 2) || ((4..7).contains(&row) && (5..8).contains(&col))
+        };
+        let ematrix = EMatrix::from_dmatrix(nalgebra::DMatrix::from_fn(8, 10, |row, col| {
+            if interior(row, col) {
+                None
+            } else {
+                Some(1.)
+            }
+        }));
+        assert_eq!(ematrix.largest_interior_centroid(), Some((5, 6)));
+
+        let escaped = EMatrix::from_vec(8, 10, vec![Some(1.); 80]).unwrap();
+        assert_eq!(escaped.largest_interior_centroid(), None);
+    }
+
+    #[test]
+    fn the_largest_finite_escape_has_its_position_returned() {
+        let ematrix = EMatrix::from_dmatrix(nalgebra::DMatrix::from_fn(4, 5, |row, col| {
+            match (row, col) {
+                (0, 0) | (3, 4) => None,
+                (1, 3) => Some(f64::INFINITY),
+                (2, 1) => Some(42.5),
+                _ => Some((row + col) as f64),
+            }
+        }));
+        assert_eq!(ematrix.max_escape_position(), Some((2, 1)));
+
+        let interior = EMatrix::from_vec(3, 3, vec![None; 9]).unwrap();
+        assert_eq!(interior.max_escape_position(), None);
+    }
+
+    #[test]
+    fn mapped_escapes_double_finite_values_and_keep_the_interior() {
+        let escapes = vec![Some(1.), None, Some(2.5), Some(0.), None, Some(7.)];
+        let ematrix = EMatrix::from_vec(2, 3, escapes).unwrap();
+        let doubled = [Some(2.), None, Some(5.), Some(0.), None, Some(14.)];
+
+        let mapped = ematrix.map_escapes(|e| e.map(|v| v * 2.));
+        assert_eq!(mapped.inner().as_slice(), &doubled[..]);
+        assert_eq!((mapped.nrows(), mapped.ncols()), (2, 3));
+
+        let par_mapped = ematrix.par_map_escapes(|e| e.map(|v| v * 2.));
+        assert_eq!(par_mapped.inner(), mapped.inner());
+    }
+
+    #[test]
+    fn enumerated_positions_cover_the_grid_in_column_major_order() {
+        // Each escape records the position it was built for.
+        let ematrix = EMatrix::from_dmatrix(nalgebra::DMatrix::from_fn(3, 4, |row, col| {
+            Some((col * 10 + row) as f64)
+        }));
+
+        let visited: Vec<(u16, u16)> = ematrix
+            .enumerate_positions()
+            .map(|(pos, escape)| {
+                assert_eq!(*escape, Some(f64::from(pos.x * 10 + pos.y)));
+                (pos.x, pos.y)
+            })
+            .collect();
+        let expected: Vec<(u16, u16)> = (0..4).flat_map(|x| (0..3).map(move |y| (x, y))).collect();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn ppms_hold_the_pixels_of_to_img() {
+        let rctx = crate::rctx::Rctx::default();
+        let ematrix = rctx
+            .bind(crate::Bounds {
+                width: 23,
+                height: 17,
+            })
+            .to_ematrix();
+        let palette = rctx.palette();
+        let img = ematrix.to_img(palette.as_ref());
+
+        let mut binary = Vec::new();
+        ematrix
+            .write_ppm(palette.as_ref(), PpmFormat::Binary, &mut binary)
+            .unwrap();
+        let header = b"P6\n23 17\n255\n";
+        assert_eq!(&binary[..header.len()], header);
+        assert_eq!(&binary[header.len()..], img.as_raw().as_slice());
+
+        let mut ascii = Vec::new();
+        ematrix
+            .write_ppm(palette.as_ref(), PpmFormat::Ascii, &mut ascii)
+            .unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        let mut tokens = ascii.split_whitespace();
+        assert_eq!(tokens.next(), Some("P3"));
+        let values: Vec<u8> = tokens.map(|token| token.parse().unwrap()).collect();
+        assert_eq!(values[..3], [23, 17, 255]);
+        assert_eq!(&values[3..], img.as_raw().as_slice());
+    }
+
+    #[test]
+    fn equalized_images_spread_clustered_escapes_across_the_palette() {
+        // Escapes bunched together near 1, one outlier, and one interior cell.
+        let mut escapes: Vec<Escape> = (0..30).map(|i| Some(1. + 0.01 * f64::from(i))).collect();
+        escapes.push(Some(64.));
+        escapes.push(None);
+        let ematrix = EMatrix::from_vec(4, 8, escapes).unwrap();
+        let grayscale = crate::palette::GrayscalePalette::default();
+
+        let levels = |img: &image::RgbImage| {
+            let mut levels: Vec<u8> = img.pixels().map(|pixel| pixel[0]).collect();
+            levels.sort_unstable();
+            levels.dedup();
+            levels.len()
+        };
+        let linear = ematrix.to_img(&grayscale);
+        let equalized = ematrix.to_img_equalized(&grayscale, grayscale.period);
+
+        assert!(levels(&linear) <= 4, "{} linear levels", levels(&linear));
+        assert!(
+            levels(&equalized) > 20,
+            "{} equalized levels",
+            levels(&equalized)
+        );
+        // The interior cell is the last, in the bottom right corner.
+        assert_eq!(linear.get_pixel(7, 3).0, [0, 0, 0]);
+        assert_eq!(equalized.get_pixel(7, 3).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn rgba_images_have_transparent_interiors() {
+        let ematrix = EMatrix::from_vec(2, 2, vec![Some(3.5), None, Some(20.), None]).unwrap();
+        let colorer = crate::SineRGB::default();
+        let (rgb, rgba) = (ematrix.to_img(&colorer), ematrix.to_rgba_img(&colorer));
+
+        for (x, y, pixel) in rgba.enumerate_pixels() {
+            let alpha = if y == 0 { 255 } else { 0 };
+            assert_eq!(pixel[3], alpha, "{:?}", (x, y));
+            assert_eq!(pixel.0[..3], rgb.get_pixel(x, y).0, "{:?}", (x, y));
+        }
+    }
+
+    #[test]
+    fn boundary_distances_count_cells_to_the_nearest_escape() {
+        // A single escaped cell in the top left corner of a 3x4 frame.
+        let mut escapes = vec![None; 12];
+        escapes[0] = Some(5.);
+        let ematrix = EMatrix::from_vec(3, 4, escapes).unwrap();
+        let distances = ematrix.boundary_distances();
+
+        let at = |row: usize, col: usize| distances[col * 3 + row].unwrap();
+        assert_eq!(at(0, 0), (0., 5.));
+        assert_eq!(at(0, 3), (3., 5.));
+        assert_eq!(at(2, 0), (2., 5.));
+        assert_eq!(at(1, 1), (std::f64::consts::SQRT_2, 5.));
+
+        // The backward pass reaches cells before the escape in storage order.
+        let mut escapes = vec![None; 12];
+        escapes[11] = Some(7.);
+        let distances = EMatrix::from_vec(3, 4, escapes)
+            .unwrap()
+            .boundary_distances();
+        let (distance, escape) = distances[0].unwrap();
+        assert!((distance - (2. * std::f64::consts::SQRT_2 + 1.)).abs() < 1e-12);
+        assert_eq!(escape, 7.);
+
+        let interior = EMatrix::from_vec(3, 4, vec![None; 12]).unwrap();
+        assert!(interior.boundary_distances().iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,47 @@
+// Quadratic escape evaluation, mirroring `Mandelbrot::escape` and `Julia::escape` in f32.
+
+struct Params {
+    max_iter: u32,
+    julia: u32,
+    escape_value: f32,
+    _pad: f32,
+    c_offset: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> points: array<vec2<f32>>;
+@group(0) @binding(2) var<storage, read_write> escapes: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    // Large frames are dispatched as rows of workgroups to stay within dispatch limits.
+    let index = id.y * groups.x * 64u + id.x;
+    if (index >= arrayLength(&points)) {
+        return;
+    }
+
+    var z = vec2<f32>(0.0, 0.0);
+    var c = points[index];
+    if (params.julia != 0u) {
+        z = c;
+        c = params.c_offset;
+    }
+
+    // Smoothed escapes can be slightly negative, so interior points are marked with the lowest
+    // finite f32.
+    var escape = -3.40282347e38;
+    for (var i = 0u; i < params.max_iter; i = i + 1u) {
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        let norm_sqr = dot(z, z);
+        if (norm_sqr > params.escape_value) {
+            let fract = log(log(norm_sqr) / log(params.escape_value)) / log(2.0);
+            escape = f32(i) - fract;
+            break;
+        }
+    }
+
+    escapes[index] = escape;
+}
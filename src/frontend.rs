@@ -2,18 +2,22 @@
 //!
 //!
 
-use crate::polycomplex::ComplexFn;
-use crate::rctx::{Rctx, RctxTransform};
-use crate::Bounds;
+use crate::ematrix::EMatrix;
+use crate::loc::Loc;
+use crate::polycomplex::{ComplexFn, PolyComplexFn};
+use crate::prompt::PromptCmd;
+use crate::rctx::{PartialEMatrix, Rctx, RctxTransform};
+use crate::{Bounds, Pos};
 use std::fs::File;
 use std::io::{self, Write};
-use std::time::{Instant, SystemTime};
-use termion::event::Key;
+use std::time::{Duration, Instant, SystemTime};
+use termion::event::{Event, Key, MouseButton, MouseEvent};
 use termion::input::{MouseTerminal, TermRead};
 use termion::raw::IntoRawMode;
 use tui::backend::TermionBackend;
 use tui::layout::{Constraint, Direction, Layout};
-use tui::widgets::{Block, Borders, Widget};
+use tui::style::{Color, Modifier, Style};
+use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
 use tui::Terminal;
 
 #[derive(Debug, Clone, Copy)]
@@ -24,6 +28,36 @@ pub enum AppCmd {
     /// Generate a screenshot based on the current rendering context.
     Save,
 
+    /// Open the command prompt.
+    Prompt,
+
+    /// Center on the largest visible interior region.
+    Center,
+
+    /// Center and zoom on the slowest escaping point in view.
+    CenterOnMaxEscape,
+
+    /// Redraw a reduced quality frame at full quality.
+    Refine,
+
+    /// Bookmark the current location as home.
+    SetHome,
+
+    /// Return to the home location.
+    Home,
+
+    /// Apply the iteration limit suggested for the current frame.
+    AutoIterations,
+
+    /// Switch between full cell and half block drawing.
+    ToggleHalfBlock,
+
+    /// Return to the view before the last navigation.
+    Undo,
+
+    /// Reapply the last undone navigation.
+    Redo,
+
     /// Gracefully shut down the app.
     Quit,
 
@@ -37,14 +71,83 @@ pub enum AppCmd {
 #[derive(Debug)]
 pub struct RunOptions {
     pub img_dir: std::path::PathBuf,
+
+    /// Render screenshots at this multiple of their resolution and downsample them, anti-aliasing
+    /// the saved image without slowing interactive frames.
+    pub screenshot_supersample: u32,
+
+    /// The minimum time between frames. Input arriving within this interval is coalesced into a
+    /// single frame.
+    pub min_frame_interval: Duration,
 }
 
 impl RunOptions {
+    /// The default frame rate limit.
+    pub const MAX_FPS: u32 = 30;
+
     pub fn new(img_dir: Option<std::path::PathBuf>) -> Self {
         Self {
             img_dir: img_dir.unwrap_or(std::path::PathBuf::from(".")),
+            screenshot_supersample: 1,
+            min_frame_interval: Self::frame_interval(Self::MAX_FPS),
+        }
+    }
+
+    /// Convert a frame rate limit to the minimum time between frames; 0 disables the limit.
+    pub fn frame_interval(max_fps: u32) -> Duration {
+        if max_fps == 0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs(1) / max_fps
+        }
+    }
+}
+
+/// Determine when the next frame may be drawn, given when the last frame was drawn.
+///
+/// Frames are drawn immediately when the minimum interval has already passed.
+pub fn next_frame_at(last_frame: Option<Instant>, min_interval: Duration, now: Instant) -> Instant {
+    match last_frame {
+        Some(last) => now.max(last + min_interval),
+        None => now,
+    }
+}
+
+/// Terminal input read on a background thread, so pending input can be drained without blocking.
+struct InputQueue {
+    events: std::sync::Mutex<std::sync::mpsc::Receiver<io::Result<Event>>>,
+}
+
+impl InputQueue {
+    fn spawn() -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for event in io::stdin().events() {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self::from_receiver(receiver)
+    }
+
+    fn from_receiver(receiver: std::sync::mpsc::Receiver<io::Result<Event>>) -> Self {
+        Self {
+            events: std::sync::Mutex::new(receiver),
         }
     }
+
+    /// Wait for the next event, or return `None` once stdin is closed.
+    fn next(&self) -> Option<io::Result<Event>> {
+        self.events.lock().ok()?.recv().ok()
+    }
+
+    /// Take the next event if one arrives before the deadline.
+    fn next_before(&self, deadline: Instant) -> Option<io::Result<Event>> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.events.lock().ok()?.recv_timeout(timeout).ok()
+    }
 }
 
 impl From<Key> for AppCmd {
@@ -71,111 +174,684 @@ impl From<Key> for AppCmd {
             Key::Char('y') => AppCmd::Transform(RctxTransform::IncExp),
             Key::Char('h') => AppCmd::Transform(RctxTransform::DecExp),
 
+            // Stretch/compress and cycle the palette without evaluating escapes again.
+            Key::Char(']') => AppCmd::Transform(RctxTransform::IncColorPeriod),
+            Key::Char('[') => AppCmd::Transform(RctxTransform::DecColorPeriod),
+            Key::Char('.') => AppCmd::Transform(RctxTransform::ShiftPhaseForward),
+            Key::Char(',') => AppCmd::Transform(RctxTransform::ShiftPhaseBackward),
+
             // Toggle between the Julia sets and the Mandelbrot sets.
             Key::Char('x') => AppCmd::Transform(RctxTransform::SwitchFn),
 
+            // Cycle between the Mandelbrot set, the Burning Ship, the tricorn, and the Newton
+            // fractal.
+            Key::Char('X') => AppCmd::Transform(RctxTransform::SwitchFamily),
+
             // Reset the zoom level to default.
             Key::Char('m') => AppCmd::Transform(RctxTransform::Reset),
 
             // Generate a state file and image for the current location.
             Key::Char('p') => AppCmd::Save,
 
+            // Jump back to the set after panning away from it.
+            Key::Char('c') => AppCmd::Center,
+
+            // Find somewhere interesting near the set boundary.
+            Key::Char('f') => AppCmd::CenterOnMaxEscape,
+
+            // Redraw at full quality after exceeding the frame budget.
+            Key::Char('r') => AppCmd::Refine,
+
+            // Bookmark a location, and return to it after exploring.
+            Key::Char('b') => AppCmd::SetHome,
+            Key::Char('o') | Key::Home => AppCmd::Home,
+
+            // Tune the iteration limit to the detail in the current frame.
+            Key::Char('i') => AppCmd::AutoIterations,
+
+            // Step back and forth through the views visited while navigating. `r` refines, so redo
+            // takes the shifted key or ctrl-r instead.
+            Key::Char('u') => AppCmd::Undo,
+            Key::Char('U') | Key::Ctrl('r') => AppCmd::Redo,
+
+            // Trade the sharper half block preview for full cells, or back.
+            Key::Char('v') => AppCmd::ToggleHalfBlock,
+
+            // Enter a command at the prompt.
+            Key::Char(':') => AppCmd::Prompt,
+
             u => AppCmd::Unhandled(u),
         }
     }
 }
 
-/// Run a closure in an alternate screen, and disable the alternate screen before handling a
-/// panic.
-pub fn run_with_altscreen<F: FnOnce() -> T + std::panic::UnwindSafe, T>(f: F) -> T {
-    let result = std::panic::catch_unwind(f);
+/// The terminal that frontends draw to: raw mode with mouse reporting, on the alternate screen.
+pub type Screen = AltScreenGuard<MouseTerminal<termion::raw::RawTerminal<io::Stdout>>>;
 
-    match result {
-        Err(err) => {
-            // XXX: It is unclear what should happen when the terminal cannot be reset to the main
-            // screen.
-            //
-            // Cases:
-            //  - A terminal is not attached: we can't shut down. Exit silently.
-            //  - We encountered an error when resetting the terminal. Panic hard.
-            //
-            let _ = reset_terminal();
-            std::panic::resume_unwind(err)
-        }
-        Ok(t) => t,
+/// Switches a terminal to the alternate screen and hides the cursor for as long as it's held.
+///
+/// Dropping the guard returns to the main screen and shows the cursor, including while unwinding
+/// from a panic. The wrapped writer is dropped afterwards, so raw mode and mouse reporting are
+/// disabled once the main screen is restored.
+pub struct AltScreenGuard<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> AltScreenGuard<W> {
+    /// Enter the alternate screen on a writer.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        write!(
+            inner,
+            "{}{}",
+            termion::screen::ToAlternateScreen,
+            termion::cursor::Hide
+        )?;
+        inner.flush()?;
+        Ok(Self { inner })
     }
 }
 
-fn reset_terminal() -> std::io::Result<()> {
-    std::io::stdout()
-        .into_raw_mode()
-        .map(|stdout| termion::screen::AlternateScreen::from(stdout))
-        .and_then(|mut screen| {
-            write!(
-                screen,
-                "{}{}",
-                termion::screen::ToMainScreen,
-                termion::cursor::Show
-            )?;
-            Ok(screen)
-        })
-        .and_then(|mut screen| screen.flush())
+impl AltScreenGuard<MouseTerminal<termion::raw::RawTerminal<io::Stdout>>> {
+    /// Put stdout in raw mode with mouse reporting and enter the alternate screen.
+    pub fn stdout() -> io::Result<Screen> {
+        let stdout = io::stdout().into_raw_mode()?;
+        Self::new(MouseTerminal::from(stdout))
+    }
+}
+
+impl<W: Write> Write for AltScreenGuard<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for AltScreenGuard<W> {
+    fn drop(&mut self) {
+        // There's nowhere to report a terminal that can't be restored, such as one that has
+        // already gone away, so errors are ignored.
+        let _ = write!(
+            self.inner,
+            "{}{}",
+            termion::screen::ToMainScreen,
+            termion::cursor::Show
+        );
+        let _ = self.inner.flush();
+    }
 }
 
 /// Generate an image and location data for a given render context and bounds.
 ///
-/// TODO: handle write errors without panicking.
+/// The image is rendered at `supersample` times the spec resolution and downsampled.
 fn screenshot(
     rctx: &Rctx,
     old_bounds: &Bounds,
     img_dir: &std::path::Path,
+    supersample: u32,
 ) -> Result<(), crate::Error> {
-    let new_bounds = Bounds {
-        width: 4000,
-        height: 4000,
-    };
+    let new_bounds = crate::rctx::SPEC_BOUNDS;
 
-    let imgen_rctx = Rctx {
-        loc: rctx
-            .loc
-            .scale(old_bounds, &new_bounds, crate::loc::ScaleMethod::Min),
-        comp: (1., 1.),
-        ..rctx.clone()
-    };
+    let imgen_rctx = rctx.for_image(old_bounds, &new_bounds);
 
     let unix_secs = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
-        .map(|duration| u64::from(duration.as_secs()))
+        .map(|duration| duration.as_secs())
         .unwrap();
 
     let mut json_path = std::path::PathBuf::from(img_dir);
     json_path.push(format!("mb-{}.json", unix_secs));
-    File::create(json_path).and_then(|mut f| {
-        let buf = serde_json::to_string(&imgen_rctx).unwrap();
-        f.write_all(&buf.as_bytes())
-    })?;
+    let buf = imgen_rctx.to_spec_string()?;
+    File::create(json_path).and_then(|mut f| f.write_all(buf.as_bytes()))?;
 
     let mut png_path = std::path::PathBuf::from(img_dir);
     png_path.push(format!("mb-{}.png", unix_secs));
-    imgen_rctx
-        .bind(new_bounds)
-        .to_ematrix()
-        .to_img(&imgen_rctx.colorer)
+    screenshot_img(rctx, old_bounds, new_bounds, supersample)
         .save(png_path)
-        .map_err(|e| crate::Error::from(e))
+        .map_err(crate::Error::from)
+}
+
+/// Render the frame shown within `old_bounds` as an image with the given bounds.
+///
+/// The frame is rendered at `supersample` times the image bounds and downsampled.
+fn screenshot_img(
+    rctx: &Rctx,
+    old_bounds: &Bounds,
+    image: Bounds,
+    supersample: u32,
+) -> image::RgbImage {
+    let sample_bounds = image.scaled(f64::from(supersample));
+    let sample_rctx = rctx.for_image(old_bounds, &sample_bounds);
+    let img = sample_rctx
+        .bind(sample_bounds)
+        .to_ematrix()
+        .to_img(sample_rctx.palette().as_ref());
+    crate::output::downsample(&img, supersample)
+}
+
+/// How overlay labels are drawn over the fractal.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LabelStyle {
+    /// Draw labels in the terminal's default colors.
+    Plain,
+
+    /// Draw labels over a veil of the fractal beneath them, with text that contrasts with it.
+    #[default]
+    Contrast,
+}
+
+#[derive(Debug)]
+pub struct LabelStyleParseError(String);
+
+impl std::fmt::Display for LabelStyleParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to label style", self.0)
+    }
+}
+
+impl std::error::Error for LabelStyleParseError {}
+
+impl std::str::FromStr for LabelStyle {
+    type Err = LabelStyleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(LabelStyle::Plain),
+            "contrast" => Ok(LabelStyle::Contrast),
+            _ => Err(LabelStyleParseError(s.to_string())),
+        }
+    }
+}
+
+/// Choose the text and background colors of a label cell drawn over a fractal color.
+///
+/// Dark colors are darkened further and given light text, and light colors are lightened and
+/// given dark text, so labels stay legible while the fractal shows through.
+pub fn label_colors(under: [u8; 3]) -> ([u8; 3], [u8; 3]) {
+    let luma =
+        0.299 * f64::from(under[0]) + 0.587 * f64::from(under[1]) + 0.114 * f64::from(under[2]);
+    let (text, veil) = if luma < 128. {
+        ([255, 255, 255], 0.)
+    } else {
+        ([0, 0, 0], 255.)
+    };
+    let blend = |c: u8| ((f64::from(c) + veil) / 2.).round() as u8;
+    (text, [blend(under[0]), blend(under[1]), blend(under[2])])
+}
+
+/// Determine the 1-based row of the status line `offset` lines up from the bottom of the screen.
+///
+/// Status lines stack upward from the line above the prompt, and lines that would run off the top
+/// of a short terminal are dropped.
+fn status_row(height: u16, offset: usize) -> Option<u16> {
+    let row = usize::from(height).checked_sub(offset)?.checked_sub(1)?;
+    Some(row as u16).filter(|&row| row > 0)
+}
+
+/// Warn when the frame has reached the limit of the orbit precision, explaining why deeper zooms
+/// turn into blocks.
+pub fn precision_warning(rctx: &Rctx, bounds: Bounds) -> Option<&'static str> {
+    if !rctx.at_precision_limit(bounds) {
+        None
+    } else if rctx.uses_double_double() {
+        Some("PRECISION LIMIT: pixels repeat even in double-double")
+    } else {
+        Some("PRECISION LIMIT: pixels repeat in f64, deeper zoom needs double-double")
+    }
+}
+
+/// How key input is interpreted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputMode {
+    /// Keys are mapped to app commands.
+    Normal,
+
+    /// Keys are collected into a command line for the prompt.
+    Prompt(String),
+}
+
+/// How a status line is presented.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Feedback on a command that succeeded, drawn like the other labels.
+    Info,
+
+    /// A non-fatal error, highlighted so it stands out from the labels.
+    Error,
+}
+
+/// Recent status messages and non-fatal errors, shown as status lines until they expire.
+///
+/// While messages are shown the frontends wait for input only until the earliest one expires,
+/// then redraw without it.
+#[derive(Debug, Default)]
+pub struct StatusLog {
+    lines: Vec<(Instant, Severity, String)>,
+}
+
+impl StatusLog {
+    /// How long a message is displayed.
+    pub const TTL: Duration = Duration::from_secs(5);
+
+    /// Record a status message.
+    pub fn info<M: std::fmt::Display>(&mut self, msg: M) {
+        self.lines
+            .push((Instant::now(), Severity::Info, msg.to_string()));
+    }
+
+    /// Record an error.
+    pub fn error<E: std::fmt::Display>(&mut self, err: E) {
+        self.lines
+            .push((Instant::now(), Severity::Error, err.to_string()));
+    }
+
+    /// Determine when the earliest message expires, if any are shown.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.lines.iter().map(|(at, _, _)| *at + Self::TTL).min()
+    }
+
+    /// Discard messages that have expired as of `now`, and return the rest with their severity.
+    pub fn status_lines(&mut self, now: Instant) -> Vec<(Severity, String)> {
+        self.lines
+            .retain(|(at, _, _)| now.saturating_duration_since(*at) < Self::TTL);
+        self.lines
+            .iter()
+            .map(|(_, severity, msg)| (*severity, msg.clone()))
+            .collect()
+    }
+}
+
+/// The part of a rendering context changed by navigating: the location and the fractal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    pub loc: Loc,
+    pub complexfn: PolyComplexFn,
+}
+
+impl View {
+    pub fn of(rctx: &Rctx) -> Self {
+        Self {
+            loc: rctx.loc.clone(),
+            complexfn: rctx.complexfn.clone(),
+        }
+    }
+
+    /// Return a rendering context to this view, keeping its other settings.
+    pub fn restore(self, rctx: &mut Rctx) {
+        rctx.loc = self.loc;
+        rctx.complexfn = self.complexfn;
+    }
+}
+
+/// Undo and redo stacks of the views visited while navigating.
+///
+/// Only the most recent `capacity` views can be undone; older views are forgotten as new ones
+/// are recorded.
+#[derive(Debug)]
+pub struct History {
+    undo: std::collections::VecDeque<View>,
+    redo: Vec<View>,
+    capacity: usize,
+}
+
+impl History {
+    /// The default number of views that can be undone.
+    pub const CAPACITY: usize = 100;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: std::collections::VecDeque::new(),
+            redo: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Record the view navigated away from. Navigating discards the views that were undone.
+    pub fn record(&mut self, previous: View) {
+        self.push_undo(previous);
+        self.redo.clear();
+    }
+
+    /// Step back from the current view, returning the view to show instead.
+    pub fn undo(&mut self, current: View) -> Option<View> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Reapply the last undone view, returning the view to show instead of the current one.
+    pub fn redo(&mut self, current: View) -> Option<View> {
+        let next = self.redo.pop()?;
+        self.push_undo(current);
+        Some(next)
+    }
+
+    fn push_undo(&mut self, view: View) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(view);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(Self::CAPACITY)
+    }
+}
+
+/// A bar of cells labeled with the width of the complex plane it spans.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleBar {
+    pub cells: u16,
+    pub width: f64,
+}
+
+impl ScaleBar {
+    /// Measure a bar of `cells` cells at the current location. Bars are at least two cells long
+    /// so both ends can be drawn.
+    pub fn new(rctx: &Rctx, cells: u16) -> Self {
+        let cells = cells.max(2);
+        Self {
+            cells,
+            width: rctx.span(cells),
+        }
+    }
+
+    /// Draw the bar followed by its width, e.g. `|--------| 1.2e-6`.
+    pub fn label(&self) -> String {
+        format!(
+            "|{}| {:.1e}",
+            "-".repeat(usize::from(self.cells) - 2),
+            self.width
+        )
+    }
+}
+
+/// How the displayed frame was produced, so users can tell whether it's worth waiting for.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Provenance {
+    /// Every cell was evaluated at the current settings.
+    #[default]
+    Rendered,
+
+    /// The escapes of the previous frame were reused and colored with the current palette.
+    Cached,
+
+    /// The interior cells of the previous frame were evaluated again at a higher iteration limit.
+    Extended,
+
+    /// A low resolution preview was drawn because the frame exceeded the frame budget. The full
+    /// resolution frame is refined while the explorer is idle.
+    Reduced,
+}
+
+impl Provenance {
+    /// Describe the frame for the status labels. Incomplete frames are marked with an asterisk.
+    pub fn label(self) -> &'static str {
+        match self {
+            Provenance::Rendered => "full",
+            Provenance::Cached => "full, cached",
+            Provenance::Extended => "full, extended",
+            Provenance::Reduced => "reduced*, refining",
+        }
+    }
+}
+
+/// The interactive state of a frontend, apart from the rendering context.
+#[derive(Debug)]
+pub struct Session {
+    pub mode: InputMode,
+    pub status: StatusLog,
+
+    /// How the last frame was produced.
+    pub provenance: Provenance,
+
+    /// Draw the next frame at full quality regardless of the frame budget.
+    pub refine: bool,
+
+    /// The corners of the zoom box being dragged with the mouse.
+    pub selection: Option<(Pos, Pos)>,
+
+    /// Draw two rows of samples per cell with half block glyphs, where the frontend supports it.
+    pub half_block: bool,
+
+    /// The views visited while navigating, for undo and redo.
+    pub history: History,
+
+    /// The location restored by the home key.
+    ///
+    /// Until a location is bookmarked this is the location the session started at.
+    pub home: Option<Loc>,
+
+    /// When the last frame was drawn.
+    pub last_frame: Option<Instant>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            mode: InputMode::Normal,
+            status: StatusLog::default(),
+            provenance: Provenance::default(),
+            refine: false,
+            selection: None,
+            half_block: false,
+            history: History::default(),
+            home: None,
+            last_frame: None,
+        }
+    }
+
+    /// Remember the location the session started at as home, unless a home is already set.
+    fn remember_home(&mut self, rctx: &Rctx) {
+        if self.home.is_none() {
+            self.home = Some(rctx.loc.clone());
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Accept a key input while the prompt is open, and run the command line when it's submitted.
+fn handle_prompt_key(key: Key, session: &mut Session, rctx: &mut Rctx) {
+    if let InputMode::Prompt(ref mut line) = session.mode {
+        match key {
+            Key::Char('\n') => {
+                match line.parse::<PromptCmd>() {
+                    Ok(cmd) => cmd.apply(rctx),
+                    Err(e) => session.status.error(e),
+                }
+                session.mode = InputMode::Normal;
+            }
+            Key::Esc => session.mode = InputMode::Normal,
+            Key::Backspace => {
+                line.pop();
+            }
+            Key::Char(c) => line.push(c),
+            _ => {}
+        }
+    }
+}
+
+/// Accept a mouse event, dragging out a zoom box with the left button and zooming to it on
+/// release. A click without a drag recenters on the clicked cell.
+///
+/// Presses outside of the bounds are ignored, and drags that leave the bounds are clamped to
+/// their edges.
+fn handle_mouse(event: MouseEvent, session: &mut Session, rctx: &mut Rctx, bounds: &Bounds) {
+    session.remember_home(rctx);
+
+    // Mouse coordinates are 1 based.
+    let pos = |x: u16, y: u16| Pos {
+        x: x.saturating_sub(1).min(bounds.width.saturating_sub(1)),
+        y: y.saturating_sub(1).min(bounds.height.saturating_sub(1)),
+    };
+
+    match event {
+        MouseEvent::Press(MouseButton::Left, x, y) => {
+            let inside = (1..=bounds.width).contains(&x) && (1..=bounds.height).contains(&y);
+            if inside {
+                session.selection = Some((pos(x, y), pos(x, y)));
+            }
+        }
+        MouseEvent::Hold(x, y) => {
+            if let Some((start, _)) = session.selection {
+                session.selection = Some((start, pos(x, y)));
+            }
+        }
+        MouseEvent::Release(x, y) => {
+            if let Some((start, _)) = session.selection.take() {
+                let before = View::of(rctx);
+                let end = pos(x, y);
+                if start.x != end.x || start.y != end.y {
+                    rctx.zoom_to_box(*bounds, start, end);
+                } else {
+                    // A click without a drag isn't a selection.
+                    rctx.loc.move_to(rctx.complex_at(*bounds, start));
+                }
+                if View::of(rctx) != before {
+                    session.history.record(before);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Translate a mouse event into the coordinates of an area whose top left cell is at the 0 based
+/// column `x` and row `y`. Events above or left of the area land outside of it, on row or column 0.
+fn relative_mouse_event(event: MouseEvent, x: u16, y: u16) -> MouseEvent {
+    let relative = |ex: u16, ey: u16| (ex.saturating_sub(x), ey.saturating_sub(y));
+    match event {
+        MouseEvent::Press(button, ex, ey) => {
+            let (ex, ey) = relative(ex, ey);
+            MouseEvent::Press(button, ex, ey)
+        }
+        MouseEvent::Release(ex, ey) => {
+            let (ex, ey) = relative(ex, ey);
+            MouseEvent::Release(ex, ey)
+        }
+        MouseEvent::Hold(ex, ey) => {
+            let (ex, ey) = relative(ex, ey);
+            MouseEvent::Hold(ex, ey)
+        }
+    }
 }
 
 /// Accept a key input, act on that input, and indicate if the app should keep going.
-fn handle_key(key: Key, rctx: &mut Rctx, bounds: &Bounds, run_options: &RunOptions) -> Option<()> {
+///
+/// Keys that change the view, including prompt commands, record the previous view for undo.
+fn handle_key(
+    key: Key,
+    session: &mut Session,
+    rctx: &mut Rctx,
+    bounds: &Bounds,
+    run_options: &RunOptions,
+) -> Option<()> {
+    let before = View::of(rctx);
+    let stepping = session.mode == InputMode::Normal
+        && matches!(AppCmd::from(key), AppCmd::Undo | AppCmd::Redo);
+
+    let result = dispatch_key(key, session, rctx, bounds, run_options);
+    if !stepping && View::of(rctx) != before {
+        session.history.record(before);
+    }
+    result
+}
+
+/// Act on a key input and indicate if the app should keep going.
+fn dispatch_key(
+    key: Key,
+    session: &mut Session,
+    rctx: &mut Rctx,
+    bounds: &Bounds,
+    run_options: &RunOptions,
+) -> Option<()> {
+    session.remember_home(rctx);
+
+    if session.mode != InputMode::Normal {
+        handle_prompt_key(key, session, rctx);
+        return Some(());
+    }
+
     let cmd = AppCmd::from(key);
     match &cmd {
         AppCmd::Transform(t) => {
-            rctx.transform(&t);
+            rctx.transform(t);
             Some(())
         }
         AppCmd::Save => {
-            // TODO: handle errors when generating screenshots.
-            let _ = screenshot(&rctx, bounds, run_options.img_dir.as_path());
+            let result = screenshot(
+                rctx,
+                bounds,
+                run_options.img_dir.as_path(),
+                run_options.screenshot_supersample,
+            );
+            if let Err(e) = result {
+                session.status.error(format!("Screenshot failed: {}", e));
+            }
+            Some(())
+        }
+        AppCmd::Prompt => {
+            session.mode = InputMode::Prompt(String::new());
+            Some(())
+        }
+        AppCmd::Center => {
+            rctx.center_on_interior(*bounds);
+            Some(())
+        }
+        AppCmd::CenterOnMaxEscape => {
+            rctx.center_on_max_escape(*bounds);
+            Some(())
+        }
+        AppCmd::Refine => {
+            session.refine = true;
+            Some(())
+        }
+        AppCmd::SetHome => {
+            session.home = Some(rctx.loc.clone());
+            Some(())
+        }
+        AppCmd::Home => {
+            if let Some(home) = &session.home {
+                rctx.loc = home.clone();
+            }
+            Some(())
+        }
+        AppCmd::AutoIterations => {
+            let previous = rctx.loc.max_iter;
+            rctx.loc.max_iter = rctx.suggest_max_iter(*bounds);
+            session
+                .status
+                .info(format!("max_iter {} -> {}", previous, rctx.loc.max_iter));
+            Some(())
+        }
+        AppCmd::ToggleHalfBlock => {
+            session.half_block = !session.half_block;
+            Some(())
+        }
+        AppCmd::Undo => {
+            match session.history.undo(View::of(rctx)) {
+                Some(view) => view.restore(rctx),
+                None => session.status.error("Nothing to undo"),
+            }
+            Some(())
+        }
+        AppCmd::Redo => {
+            match session.history.redo(View::of(rctx)) {
+                Some(view) => view.restore(rctx),
+                None => session.status.error("Nothing to redo"),
+            }
             Some(())
         }
         AppCmd::Unhandled(_) => Some(()),
@@ -183,7 +859,7 @@ fn handle_key(key: Key, rctx: &mut Rctx, bounds: &Bounds, run_options: &RunOptio
     }
 }
 
-pub trait Frontend: Send + Sync + std::panic::UnwindSafe {
+pub trait Frontend: Send + Sync {
     fn run(
         &mut self,
         initial_rctx: Rctx,
@@ -216,25 +892,257 @@ pub trait Frontend: Send + Sync + std::panic::UnwindSafe {
 }
 
 pub struct Termion {
-    stdin: std::io::Stdin,
-    screen: termion::screen::AlternateScreen<termion::raw::RawTerminal<std::io::Stdout>>,
+    input: InputQueue,
+    screen: Screen,
+    session: Session,
+    label_style: LabelStyle,
+    scale_bar: Option<u16>,
+    frame_budget: Option<Duration>,
+    frames: FrameCache,
+}
+
+/// The frames kept between draws, so later frames can reuse their escapes.
+#[derive(Default)]
+struct FrameCache {
+    /// The last frame rendered at full detail, reused when only the coloring changes or the
+    /// iteration limit increases.
+    previous: Option<(Rctx, Bounds, EMatrix)>,
+    /// The frame being refined after it exceeded the frame budget.
+    refinement: Option<Refinement>,
+}
+
+/// A frame that exceeded the frame budget, rendered at full resolution a budget at a time.
+struct Refinement {
+    rctx: Rctx,
+    bounds: Bounds,
+
+    /// The reduced resolution pass, drawn until the full resolution pass completes.
+    preview: EMatrix,
+
+    /// The full resolution pass evaluated so far.
+    partial: PartialEMatrix,
+}
+
+impl FrameCache {
+    /// The factor frames are scaled down by when they exceed the frame budget.
+    const REDUCED_SCALE: f64 = 0.25;
+
+    /// Render a frame as an image with one pixel per sample, and indicate how it was produced.
+    ///
+    /// When only the coloring changed since the previous frame its escapes are reused, and when
+    /// only the iteration limit increased only its interior cells are evaluated again.
+    fn render(
+        &mut self,
+        rctx: &Rctx,
+        bounds: Bounds,
+        budget: Option<Duration>,
+    ) -> (image::RgbImage, Provenance) {
+        let palette = rctx.palette();
+        let refinement = self.refinement.take();
+        let (ematrix, provenance) = match self.previous.take() {
+            Some((previous, previous_bounds, ematrix))
+                if previous_bounds == bounds && rctx.same_escapes(&previous) =>
+            {
+                (ematrix, Provenance::Cached)
+            }
+            Some((previous, previous_bounds, ematrix))
+                if previous_bounds == bounds && rctx.extends_iterations(&previous) =>
+            {
+                let ematrix = rctx.bind(bounds).to_ematrix_extended(ematrix);
+                (ematrix, Provenance::Extended)
+            }
+            _ => match budget {
+                None => (rctx.bind(bounds).to_ematrix(), Provenance::Rendered),
+                Some(budget) => {
+                    let deadline = Instant::now() + budget;
+                    match self.render_progressively(rctx, bounds, refinement, deadline) {
+                        Some(ematrix) => (ematrix, Provenance::Rendered),
+                        None => {
+                            let preview = self.refinement.as_ref().map(|r| &r.preview);
+                            let img = image::imageops::resize(
+                                &preview
+                                    .expect("a refinement is kept")
+                                    .to_img(palette.as_ref()),
+                                u32::from(bounds.width),
+                                u32::from(bounds.height),
+                                image::imageops::FilterType::Nearest,
+                            );
+                            return (img, Provenance::Reduced);
+                        }
+                    }
+                }
+            },
+        };
+
+        let img = ematrix.to_img(palette.as_ref());
+        self.previous = Some((rctx.clone(), bounds, ematrix));
+        (img, provenance)
+    }
+
+    /// Render a frame in progressively finer passes, stopping once the deadline passes.
+    ///
+    /// A reduced resolution pass comes first so there is always something to draw, followed by
+    /// the full resolution pass. A frame that misses the deadline is kept as the refinement, and
+    /// rendering the same frame again with it picks up where the full resolution pass stopped.
+    fn render_progressively(
+        &mut self,
+        rctx: &Rctx,
+        bounds: Bounds,
+        refinement: Option<Refinement>,
+        deadline: Instant,
+    ) -> Option<EMatrix> {
+        let mut refinement = match refinement {
+            Some(refinement)
+                if refinement.bounds == bounds && rctx.same_escapes(&refinement.rctx) =>
+            {
+                refinement
+            }
+            _ => {
+                let reduced_bounds = bounds.scaled(Self::REDUCED_SCALE);
+                let reduced = rctx.rescaled(&bounds, &reduced_bounds);
+                let reduced = reduced.bind(reduced_bounds);
+                let preview = reduced
+                    .to_ematrix_until(deadline)
+                    .unwrap_or_else(|partial| reduced.partial_ematrix(&partial));
+                Refinement {
+                    rctx: rctx.clone(),
+                    bounds,
+                    preview,
+                    partial: PartialEMatrix::new(bounds),
+                }
+            }
+        };
+
+        match rctx.bind(bounds).resume_until(refinement.partial, deadline) {
+            Ok(ematrix) => Some(ematrix),
+            Err(partial) => {
+                refinement.partial = partial;
+                self.refinement = Some(refinement);
+                None
+            }
+        }
+    }
 }
 
 impl Termion {
     pub fn build() -> Result<Self, crate::Error> {
-        // Terminal initialization
-        let stdin = io::stdin();
-        let stdout = io::stdout().into_raw_mode().unwrap();
-        let mut screen = termion::screen::AlternateScreen::from(stdout);
+        Ok(Termion {
+            input: InputQueue::spawn(),
+            screen: Screen::stdout()?,
+            session: Session::new(),
+            label_style: LabelStyle::default(),
+            scale_bar: None,
+            frame_budget: None,
+            frames: FrameCache::default(),
+        })
+    }
 
-        write!(
-            screen,
-            "{}{}",
-            termion::screen::ToAlternateScreen,
-            termion::cursor::Hide
-        )?;
+    /// Limit the time spent rendering each frame.
+    ///
+    /// Frames that exceed the budget are drawn at a reduced resolution, then refined at full
+    /// resolution while no input is pending.
+    pub fn frame_budget(mut self, budget: Option<Duration>) -> Self {
+        self.frame_budget = budget;
+        self
+    }
+
+    /// Draw two rows of samples per cell with half block glyphs.
+    ///
+    /// Each cell is sampled twice along the imaginary axis, so samples are close to square and
+    /// the preview has twice the vertical resolution.
+    ///
+    /// The `v` key switches between half block and full cell drawing while running.
+    pub fn half_block(mut self, enabled: bool) -> Self {
+        self.session.half_block = enabled;
+        self
+    }
 
-        Ok(Termion { stdin, screen })
+    /// Choose how overlay labels are drawn.
+    pub fn label_style(mut self, style: LabelStyle) -> Self {
+        self.label_style = style;
+        self
+    }
+
+    /// Draw a scale bar of this many cells in the bottom right corner.
+    pub fn scale_bar(mut self, cells: Option<u16>) -> Self {
+        self.scale_bar = cells;
+        self
+    }
+
+    /// Format a label written at column `x` and row `y`, both 1-based, over a rendered frame.
+    fn label(&self, img: &image::RgbImage, x: u16, y: u16, text: &str) -> String {
+        let mut buf = String::from(termion::cursor::Goto(x, y));
+        match self.label_style {
+            LabelStyle::Plain => {
+                buf.push_str(termion::style::Reset.as_ref());
+                buf.push_str(text);
+            }
+            LabelStyle::Contrast => {
+                // Half block frames have two pixel rows per cell.
+                let rows = if self.session.half_block { 2 } else { 1 };
+                for (offset, ch) in text.chars().enumerate() {
+                    let px = u32::from(x.saturating_sub(1)) + offset as u32;
+                    let py = u32::from(y.saturating_sub(1)) * rows;
+                    let under = if px < img.width() && py < img.height() {
+                        img.get_pixel(px, py).0
+                    } else {
+                        [0, 0, 0]
+                    };
+                    let (fg, bg) = label_colors(under);
+                    buf.push_str(&termion::color::Rgb(fg[0], fg[1], fg[2]).fg_string());
+                    buf.push_str(&termion::color::Rgb(bg[0], bg[1], bg[2]).bg_string());
+                    buf.push(ch);
+                }
+                buf.push_str(termion::style::Reset.as_ref());
+            }
+        }
+        buf
+    }
+
+    /// Outline the zoom box spanned by two corners.
+    fn draw_selection(&mut self, a: Pos, b: Pos) -> Result<(), crate::Error> {
+        let (x0, x1) = (a.x.min(b.x), a.x.max(b.x));
+        let (y0, y1) = (a.y.min(b.y), a.y.max(b.y));
+        let white = termion::color::Rgb(255, 255, 255).bg_string();
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if x == x0 || x == x1 || y == y0 || y == y1 {
+                    write!(
+                        self.screen,
+                        "{}{} ",
+                        termion::cursor::Goto(x + 1, y + 1),
+                        white
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert an RGB image with two rows per cell to ANSI escape sequences that paint the upper
+    /// row with the foreground of a half block glyph and the lower row with the background.
+    fn img_to_half_block_ansi(&self, img: &image::RgbImage, bounds: &Bounds) -> String {
+        let mut buf = String::new();
+        for yi in 0..bounds.height {
+            for xi in 0..bounds.width {
+                let upper = img.get_pixel(xi.into(), u32::from(yi) * 2);
+                let lower = img.get_pixel(xi.into(), u32::from(yi) * 2 + 1);
+                buf.push_str(String::from(termion::cursor::Goto(xi + 1, yi + 1)).as_str());
+                buf.push_str(
+                    termion::color::Rgb(upper[0], upper[1], upper[2])
+                        .fg_string()
+                        .as_str(),
+                );
+                buf.push_str(
+                    termion::color::Rgb(lower[0], lower[1], lower[2])
+                        .bg_string()
+                        .as_str(),
+                );
+                buf.push('\u{2580}');
+            }
+        }
+        buf
     }
 
     /// Convert an RGB image to a series of ANSI escape sequences that set the cursor and paint the
@@ -261,19 +1169,41 @@ impl Termion {
 impl Frontend for Termion {
     fn draw(&mut self, rctx: &Rctx, bounds: &Bounds) -> Result<(), crate::Error> {
         let render_start: Instant = Instant::now();
-        let img = rctx.bind(*bounds).to_ematrix().to_img(&rctx.colorer);
-        let ansi = self.img_to_ansi(&img, bounds);
+        let budget = if self.session.refine {
+            None
+        } else {
+            self.frame_budget
+        };
+        self.session.refine = false;
+
+        let (img, ansi) = if self.session.half_block {
+            let sample_bounds = Bounds {
+                height: bounds.height.saturating_mul(2),
+                width: bounds.width,
+            };
+            let (img, provenance) =
+                self.frames
+                    .render(&rctx.with_vertical_oversample(2), sample_bounds, budget);
+            self.session.provenance = provenance;
+            let ansi = self.img_to_half_block_ansi(&img, bounds);
+            (img, ansi)
+        } else {
+            let (img, provenance) = self.frames.render(rctx, *bounds, budget);
+            self.session.provenance = provenance;
+            let ansi = self.img_to_ansi(&img, bounds);
+            (img, ansi)
+        };
         let render_stop: Instant = Instant::now();
 
         let draw_start = Instant::now();
-        write!(self.screen, "{}", ansi).unwrap();
+        write!(self.screen, "{}", ansi)?;
         self.screen.flush()?;
         let draw_stop = Instant::now();
 
         let render_delta = render_stop - render_start;
         let draw_delta = draw_stop - draw_start;
 
-        let labels = vec![
+        let labels = [
             format!("exp    = {:.4e}", &rctx.complexfn.exp()),
             format!("re     = {:.4e}", rctx.loc.re0),
             format!("im     = {:.4e}", rctx.loc.im0),
@@ -281,94 +1211,242 @@ impl Frontend for Termion {
             format!("scalar = {:.4e}", rctx.loc.scalar),
             format!("render = {}ms", render_delta.as_millis()),
             format!("draw   = {}ms", draw_delta.as_millis()),
+            format!("detail = {}", self.session.provenance.label()),
         ];
 
         for (offset, label) in labels.iter().enumerate() {
+            let label = self.label(&img, 1, offset as u16 + 1, label);
+            write!(self.screen, "{}", label)?
+        }
+
+        if let Some(warning) = precision_warning(rctx, *bounds) {
             write!(
                 self.screen,
-                "{}{}{}",
-                termion::cursor::Goto(1, offset as u16 + 1),
+                "{}{}{}{}{}{}",
+                termion::cursor::Goto(1, labels.len() as u16 + 2),
+                termion::color::Bg(termion::color::Red),
+                termion::color::Fg(termion::color::White),
+                termion::style::Bold,
+                warning,
+                termion::style::Reset
+            )?
+        }
+
+        let status_lines = self.session.status.status_lines(Instant::now());
+        for (offset, (severity, line)) in status_lines.iter().rev().enumerate() {
+            let row = match status_row(bounds.height, offset) {
+                Some(row) => row,
+                None => break,
+            };
+            match severity {
+                Severity::Info => {
+                    let line = self.label(&img, 1, row, line);
+                    write!(self.screen, "{}", line)?
+                }
+                Severity::Error => write!(
+                    self.screen,
+                    "{}{}{}{}{}{}",
+                    termion::cursor::Goto(1, row),
+                    termion::color::Bg(termion::color::Red),
+                    termion::color::Fg(termion::color::White),
+                    termion::style::Bold,
+                    line,
+                    termion::style::Reset
+                )?,
+            }
+        }
+
+        if let Some(cells) = self.scale_bar {
+            let bar = ScaleBar::new(rctx, cells).label();
+            let x = bounds.width.saturating_sub(bar.len() as u16) + 1;
+            let bar = self.label(&img, x, bounds.height, &bar);
+            write!(self.screen, "{}", bar)?
+        }
+
+        if let Some((a, b)) = self.session.selection {
+            self.draw_selection(a, b)?;
+        }
+
+        if let InputMode::Prompt(ref line) = self.session.mode {
+            write!(
+                self.screen,
+                "{}{}:{}",
+                termion::cursor::Goto(1, bounds.height),
                 termion::style::Reset,
-                label
+                line
             )?
         }
 
         self.screen.flush()?;
+        self.session.last_frame = Some(Instant::now());
         Ok(())
     }
 
-    /// XXX this code looks pathological, refactor soon
+    /// Handle input until the next frame is due, coalescing input that arrives in the meantime.
     fn update(
         &mut self,
         rctx: &mut Rctx,
         bounds: &Bounds,
         run_options: &RunOptions,
     ) -> Result<Option<()>, crate::Error> {
-        match (&mut self.stdin).keys().next() {
-            None | Some(Err(_)) => Ok(None), // Stdin was closed or could not be read, shut down.
-            Some(Ok(key)) => Ok(handle_key(key, rctx, &bounds, &run_options)),
+        let mut next = if self.frames.refinement.is_some() {
+            // Keep refining a reduced frame until input arrives.
+            match self.input.next_before(Instant::now()) {
+                Some(event) => Some(event),
+                None => return Ok(Some(())),
+            }
+        } else if let Some(expiry) = self.session.status.next_expiry() {
+            // Redraw once the earliest message expires, clearing it without waiting for input.
+            match self.input.next_before(expiry) {
+                Some(event) => Some(event),
+                None => return Ok(Some(())),
+            }
+        } else {
+            self.input.next()
+        };
+        loop {
+            let handled = match next {
+                None | Some(Err(_)) => None, // Stdin was closed or could not be read, shut down.
+                Some(Ok(Event::Mouse(event))) => {
+                    handle_mouse(event, &mut self.session, rctx, bounds);
+                    Some(())
+                }
+                Some(Ok(Event::Unsupported(_))) => Some(()),
+                Some(Ok(Event::Key(key))) => {
+                    handle_key(key, &mut self.session, rctx, bounds, run_options)
+                }
+            };
+            if handled.is_none() {
+                return Ok(None);
+            }
+
+            let deadline = next_frame_at(
+                self.session.last_frame,
+                run_options.min_frame_interval,
+                Instant::now(),
+            );
+            next = match self.input.next_before(deadline) {
+                Some(event) => Some(event),
+                None => return Ok(Some(())),
+            };
         }
     }
 }
 
-impl Drop for Termion {
-    fn drop(&mut self) {
-        let _w = write!(
-            self.screen,
-            "{}{}",
-            termion::screen::ToMainScreen,
-            termion::cursor::Show
-        );
-        let _f = self.screen.flush();
+/// The outline of the zoom box spanned by two corners, relative to the area it's drawn over.
+struct SelectionBox {
+    a: Pos,
+    b: Pos,
+}
+
+impl Widget for SelectionBox {
+    fn draw(&mut self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let (x0, x1) = (self.a.x.min(self.b.x), self.a.x.max(self.b.x));
+        let (y0, y1) = (self.a.y.min(self.b.y), self.a.y.max(self.b.y));
+
+        for y in y0..=y1.min(area.height.saturating_sub(1)) {
+            for x in x0..=x1.min(area.width.saturating_sub(1)) {
+                if x == x0 || x == x1 || y == y0 || y == y1 {
+                    buf.get_mut(area.x + x, area.y + y).set_bg(Color::White);
+                }
+            }
+        }
     }
 }
 
 pub struct Tui {
-    stdin: std::io::Stdin,
-    terminal: tui::Terminal<
-        tui::backend::TermionBackend<
-            termion::screen::AlternateScreen<
-                MouseTerminal<termion::raw::RawTerminal<std::io::Stdout>>,
-            >,
-        >,
-    >,
+    input: InputQueue,
+    terminal: tui::Terminal<tui::backend::TermionBackend<Screen>>,
+    session: Session,
+    scale_bar: Option<u16>,
+    /// Where the fractal was last drawn, so mouse input can be mapped onto it.
+    fractal_area: Option<tui::layout::Rect>,
 }
 
 impl Tui {
     pub fn build() -> Result<Self, crate::Error> {
-        let stdin = std::io::stdin();
-        let stdout = std::io::stdout().into_raw_mode()?;
-        let stdout = MouseTerminal::from(stdout);
-        let stdout = termion::screen::AlternateScreen::from(stdout);
-        let backend = TermionBackend::new(stdout);
+        let backend = TermionBackend::new(Screen::stdout()?);
         let mut terminal = Terminal::new(backend)?;
         terminal.hide_cursor()?;
 
-        Ok(Self { stdin, terminal })
+        Ok(Self {
+            input: InputQueue::spawn(),
+            terminal,
+            session: Session::new(),
+            scale_bar: None,
+            fractal_area: None,
+        })
+    }
+
+    /// Show a scale bar of this many cells at the bottom of the sidebar.
+    pub fn scale_bar(mut self, cells: Option<u16>) -> Self {
+        self.scale_bar = cells;
+        self
     }
 }
 
 impl Frontend for Tui {
     /// Redraw the UI with TUI
-    ///
-    /// TODO: clean up `_bounds` arg
-    fn draw(&mut self, rctx: &Rctx, _bounds: &Bounds) -> Result<(), crate::Error> {
-        self.terminal
-            .draw(|mut frame| {
-                let sections = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
-                    .split(frame.size());
-
-                Block::default()
-                    .title("Sidebar")
-                    .borders(Borders::ALL)
-                    .render(&mut frame, sections[0]);
-
-                // XXX bad clone, shouldn't be necessary
-                rctx.clone().render(&mut frame, sections[1]);
+    fn draw(&mut self, rctx: &Rctx, bounds: &Bounds) -> Result<(), crate::Error> {
+        let title = match self.session.mode {
+            InputMode::Normal => String::from("Sidebar"),
+            InputMode::Prompt(ref line) => format!(":{}", line),
+        };
+        let mut status_lines: Vec<Text> = self
+            .session
+            .status
+            .status_lines(Instant::now())
+            .into_iter()
+            .map(|(severity, line)| match severity {
+                Severity::Info => Text::raw(format!("{}\n", line)),
+                Severity::Error => {
+                    Text::styled(format!("{}\n", line), Style::default().fg(Color::Red))
+                }
             })
-            .map_err(|e| e.into())
+            .collect();
+        if let Some(warning) = precision_warning(rctx, *bounds) {
+            status_lines.insert(
+                0,
+                Text::styled(
+                    format!("{}\n", warning),
+                    Style::default()
+                        .fg(Color::White)
+                        .bg(Color::Red)
+                        .modifier(Modifier::BOLD),
+                ),
+            );
+        }
+        if let Some(cells) = self.scale_bar {
+            status_lines.push(Text::raw(format!(
+                "{}\n",
+                ScaleBar::new(rctx, cells).label()
+            )));
+        }
+
+        let mut fractal_area = None;
+        let selection = self.session.selection;
+        self.terminal.draw(|mut frame| {
+            let sections = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+                .split(frame.size());
+
+            Paragraph::new(status_lines.iter())
+                .block(Block::default().title(&title).borders(Borders::ALL))
+                .wrap(true)
+                .render(&mut frame, sections[0]);
+
+            // XXX bad clone, shouldn't be necessary
+            rctx.clone().render(&mut frame, sections[1]);
+            fractal_area = Some(sections[1]);
+
+            if let Some((a, b)) = selection {
+                SelectionBox { a, b }.render(&mut frame, sections[1]);
+            }
+        })?;
+        self.fractal_area = fractal_area;
+        self.session.last_frame = Some(Instant::now());
+        Ok(())
     }
 
     fn update(
@@ -377,12 +1455,363 @@ impl Frontend for Tui {
         bounds: &Bounds,
         run_options: &RunOptions,
     ) -> Result<Option<()>, crate::Error> {
-        match (&mut self.stdin).keys().next() {
-            None | Some(Err(_)) => {
-                std::thread::sleep(std::time::Duration::from_millis(100));
-                Ok(Some(()))
+        // Waking when the earliest message expires redraws the frame without it.
+        let mut next = match self.session.status.next_expiry() {
+            Some(expiry) => self.input.next_before(expiry),
+            None => self.input.next(),
+        };
+        loop {
+            let handled = match next {
+                None | Some(Err(_)) => {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                    return Ok(Some(()));
+                }
+                Some(Ok(Event::Key(key))) => {
+                    handle_key(key, &mut self.session, rctx, bounds, run_options)
+                }
+                Some(Ok(Event::Mouse(event))) => {
+                    if let Some(area) = self.fractal_area {
+                        let area_bounds = Bounds {
+                            width: area.width,
+                            height: area.height,
+                        };
+                        let event = relative_mouse_event(event, area.x, area.y);
+                        handle_mouse(event, &mut self.session, rctx, &area_bounds);
+                    }
+                    Some(())
+                }
+                Some(Ok(_)) => Some(()),
+            };
+            if handled.is_none() {
+                return Ok(None);
             }
-            Some(Ok(key)) => Ok(handle_key(key, rctx, &bounds, &run_options)),
+
+            let deadline = next_frame_at(
+                self.session.last_frame,
+                run_options.min_frame_interval,
+                Instant::now(),
+            );
+            next = match self.input.next_before(deadline) {
+                Some(event) => Some(event),
+                None => return Ok(Some(())),
+            };
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_colors_contrast_with_the_cell_underneath() {
+        let (text, veil) = label_colors([10, 20, 30]);
+        assert_eq!(text, [255, 255, 255]);
+        assert!(veil.iter().zip([10, 20, 30]).all(|(&v, u)| v <= u));
+
+        let (text, veil) = label_colors([240, 230, 220]);
+        assert_eq!(text, [0, 0, 0]);
+        assert!(veil.iter().zip([240, 230, 220]).all(|(&v, u)| v >= u));
+    }
+
+    #[test]
+    fn status_rows_stop_above_the_top_of_the_screen() {
+        assert_eq!(status_row(24, 0), Some(23));
+        assert_eq!(status_row(24, 22), Some(1));
+        assert_eq!(status_row(24, 23), None);
+        assert_eq!(status_row(1, 0), None);
+        assert_eq!(status_row(0, 0), None);
+        assert_eq!(status_row(u16::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    fn scale_bars_span_the_complex_width_of_their_cells() {
+        let rctx = Rctx {
+            loc: Loc {
+                scalar: 1e-6,
+                ..Loc::default()
+            },
+            comp: (2., 0.5),
+            ..Rctx::default()
+        };
+        let bounds = Bounds {
+            width: 80,
+            height: 24,
+        };
+
+        let bar = ScaleBar::new(&rctx, 10);
+        assert_eq!(bar.cells, 10);
+        assert!((bar.width - 5e-6).abs() < 1e-18, "{}", bar.width);
+        let left = rctx.coord_at(bounds, 20, 5).unwrap();
+        let right = rctx.coord_at(bounds, 30, 5).unwrap();
+        assert!((right.re - left.re - bar.width).abs() < 1e-18);
+        assert_eq!(bar.label(), "|--------| 5.0e-6");
+
+        // Bars are long enough to draw both ends.
+        let short = ScaleBar::new(&rctx, 0);
+        assert_eq!(short.cells, 2);
+        assert_eq!(short.label(), "|| 1.0e-6");
+    }
+
+    #[test]
+    fn alt_screen_guards_enter_and_leave_in_order() {
+        let mut out = Vec::new();
+        {
+            let mut screen = AltScreenGuard::new(&mut out).unwrap();
+            write!(screen, "frame").unwrap();
+        }
+
+        let expected = format!(
+            "{}{}frame{}{}",
+            termion::screen::ToAlternateScreen,
+            termion::cursor::Hide,
+            termion::screen::ToMainScreen,
+            termion::cursor::Show
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn frames_are_paced_by_the_minimum_interval() {
+        let interval = RunOptions::frame_interval(30);
+        assert_eq!(interval, Duration::from_secs(1) / 30);
+        assert_eq!(RunOptions::frame_interval(0), Duration::from_secs(0));
+
+        let start = Instant::now();
+        assert_eq!(next_frame_at(None, interval, start), start);
+
+        // Held keys arriving sooner than the interval wait for the next frame.
+        let soon = start + interval / 4;
+        assert_eq!(next_frame_at(Some(start), interval, soon), start + interval);
+
+        // Input after a long idle is drawn immediately.
+        let later = start + interval * 3;
+        assert_eq!(next_frame_at(Some(start), interval, later), later);
+
+        let mut last = None;
+        let mut frames = vec![];
+        for tick in 0..20 {
+            let now = start + Duration::from_millis(5 * tick);
+            let at = next_frame_at(last, interval, now);
+            assert!(at >= now);
+            frames.push(at);
+            last = Some(at);
+        }
+        for pair in frames.windows(2) {
+            assert!(pair[1] - pair[0] >= interval);
+        }
+
+        let unlimited = RunOptions::frame_interval(0);
+        assert_eq!(next_frame_at(Some(soon), unlimited, soon), soon);
+    }
+
+    #[test]
+    fn supersampled_screenshots_are_rendered_at_twice_the_image_bounds() {
+        let rctx = Rctx::for_terminal(None);
+        let frame = Bounds {
+            width: 40,
+            height: 20,
+        };
+        let image = Bounds {
+            width: 30,
+            height: 30,
+        };
+
+        let img = screenshot_img(&rctx, &frame, image, 2);
+        assert_eq!(img.dimensions(), (30, 30));
+
+        let sample_bounds = Bounds {
+            width: 60,
+            height: 60,
+        };
+        let sample_rctx = rctx.for_image(&frame, &sample_bounds);
+        let samples = sample_rctx
+            .bind(sample_bounds)
+            .to_ematrix()
+            .to_img(sample_rctx.palette().as_ref());
+        assert_eq!(img, crate::output::downsample(&samples, 2));
+        assert_ne!(img, screenshot_img(&rctx, &frame, image, 1));
+    }
+
+    #[test]
+    fn messages_show_as_status_lines_until_they_expire() {
+        let mut status = StatusLog::default();
+        let pushed = Instant::now();
+        status.error("Screenshot failed: disk full");
+        status.info(format!("max_iter {} -> {}", 100, 200));
+
+        assert_eq!(
+            status.status_lines(pushed),
+            vec![
+                (
+                    Severity::Error,
+                    String::from("Screenshot failed: disk full")
+                ),
+                (Severity::Info, String::from("max_iter 100 -> 200"))
+            ]
+        );
+        assert_eq!(status.status_lines(pushed + StatusLog::TTL / 2).len(), 2);
+        assert!(status
+            .status_lines(pushed + StatusLog::TTL + Duration::from_secs(1))
+            .is_empty());
+    }
+
+    /// Press each key in turn, as the explorer would.
+    fn press(keys: &str, session: &mut Session, rctx: &mut Rctx) {
+        let bounds = Bounds {
+            width: 80,
+            height: 24,
+        };
+        let run_options = RunOptions::new(None);
+        for c in keys.chars() {
+            assert_eq!(
+                handle_key(Key::Char(c), session, rctx, &bounds, &run_options),
+                Some(())
+            );
+        }
+    }
+
+    #[test]
+    fn expired_messages_clear_without_input() {
+        let (_sender, receiver) = std::sync::mpsc::channel();
+        let input = InputQueue::from_receiver(receiver);
+
+        let mut status = StatusLog::default();
+        assert_eq!(status.next_expiry(), None);
+        let pushed = Instant::now() - StatusLog::TTL + Duration::from_millis(20);
+        status
+            .lines
+            .push((pushed, Severity::Error, String::from("Nothing to undo")));
+        status.error("Nothing to redo");
+
+        // Waiting for input gives up once the earliest message expires, and the redraw drops it.
+        let expiry = status.next_expiry().unwrap();
+        assert_eq!(expiry, pushed + StatusLog::TTL);
+        assert!(input.next_before(expiry).is_none());
+        assert!(Instant::now() >= expiry);
+        assert_eq!(
+            status.status_lines(Instant::now()),
+            vec![(Severity::Error, String::from("Nothing to redo"))]
+        );
+    }
+
+    #[test]
+    fn home_restores_the_bookmarked_location() {
+        let mut session = Session::new();
+        let mut rctx = Rctx::for_terminal(None);
+        let start = rctx.loc.clone();
+
+        // Without a bookmark, home is where the session started.
+        press("++ad", &mut session, &mut rctx);
+        assert_ne!(rctx.loc, start);
+        press("o", &mut session, &mut rctx);
+        assert_eq!(rctx.loc, start);
+
+        press("+wdb", &mut session, &mut rctx);
+        let home = rctx.loc.clone();
+        press("-sstm", &mut session, &mut rctx);
+        assert_ne!(rctx.loc, home);
+        press("o", &mut session, &mut rctx);
+        assert_eq!(rctx.loc, home);
+    }
+
+    #[test]
+    fn auto_iterations_raise_the_limit_of_deep_zooms() {
+        let bounds = Bounds {
+            width: 80,
+            height: 24,
+        };
+        let mut session = Session::new();
+        let mut rctx = Rctx::for_terminal(Some(Loc {
+            max_iter: Loc::default().max_iter,
+            ..crate::loc::LocPreset::Seahorse.loc(bounds)
+        }));
+
+        press("i", &mut session, &mut rctx);
+        assert!(rctx.loc.max_iter > Loc::default().max_iter);
+        assert_eq!(
+            session.status.status_lines(Instant::now()),
+            vec![(
+                Severity::Info,
+                format!(
+                    "max_iter {} -> {}",
+                    Loc::default().max_iter,
+                    rctx.loc.max_iter
+                )
+            )]
+        );
+    }
+
+    #[test]
+    fn frame_provenance_follows_the_render_path() {
+        let bounds = Bounds {
+            width: 40,
+            height: 20,
+        };
+        let mut frames = FrameCache::default();
+        let mut rctx = Rctx::for_terminal(None);
+
+        let (full, provenance) = frames.render(&rctx, bounds, None);
+        assert_eq!(provenance, Provenance::Rendered);
+
+        rctx.transform(&RctxTransform::ShiftPhaseForward);
+        let (recolored, provenance) = frames.render(&rctx, bounds, None);
+        assert_eq!(provenance, Provenance::Cached);
+        assert_ne!(recolored, full);
+
+        rctx.transform(&RctxTransform::IncIterations);
+        assert_eq!(frames.render(&rctx, bounds, None).1, Provenance::Extended);
+
+        // A frame over budget is drawn reduced, then refined to completion.
+        rctx.transform(&RctxTransform::TranslateLeft);
+        let (reduced, provenance) = frames.render(&rctx, bounds, Some(Duration::ZERO));
+        assert_eq!(provenance, Provenance::Reduced);
+        assert_eq!(reduced.dimensions(), (40, 20));
+        assert!(frames.refinement.is_some());
+
+        let (refined, provenance) = frames.render(&rctx, bounds, Some(Duration::from_secs(60)));
+        assert_eq!(provenance, Provenance::Rendered);
+        assert!(frames.refinement.is_none());
+        assert_eq!(
+            refined,
+            rctx.bind(bounds)
+                .to_ematrix()
+                .to_img(rctx.palette().as_ref())
+        );
+    }
+
+    #[test]
+    fn undo_restores_the_previous_view_and_redo_reapplies_it() {
+        let view = |scalar: f64| View {
+            loc: Loc {
+                scalar,
+                ..Loc::default()
+            },
+            complexfn: PolyComplexFn::default(),
+        };
+        let mut history = History::new(2);
+        assert_eq!(history.undo(view(1.)), None);
+
+        // Zoom from 1 to 0.5 to 0.25.
+        history.record(view(1.));
+        history.record(view(0.5));
+        assert_eq!(history.undo(view(0.25)), Some(view(0.5)));
+        assert_eq!(history.undo(view(0.5)), Some(view(1.)));
+        assert_eq!(history.undo(view(1.)), None);
+        assert_eq!(history.redo(view(1.)), Some(view(0.5)));
+        assert_eq!(history.redo(view(0.5)), Some(view(0.25)));
+        assert_eq!(history.redo(view(0.25)), None);
+
+        // Navigating somewhere new discards the undone views.
+        assert_eq!(history.undo(view(0.25)), Some(view(0.5)));
+        history.record(view(0.5));
+        assert_eq!(history.redo(view(2.)), None);
+
+        // Only the most recent views within the capacity are kept.
+        history.record(view(2.));
+        history.record(view(3.));
+        assert_eq!(history.undo(view(4.)), Some(view(3.)));
+        assert_eq!(history.undo(view(3.)), Some(view(2.)));
+        assert_eq!(history.undo(view(2.)), None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Escape matrix evaluation in a wgpu compute shader.
+//!
+//! The shader evaluates the quadratic Mandelbrot and Julia sets with smoothed escapes in `f32`,
+//! which is much faster than the CPU path for large renders but loses precision at deep zooms.
+//! Other functions and color modes are evaluated on the CPU.
+
+use crate::coloring::ColorMode;
+use crate::ematrix::EMatrix;
+use crate::polycomplex::{ComplexFn, PolyComplexFn};
+use crate::rctx::Rctx;
+use crate::{Bounds, Escape, Pos};
+use wgpu::util::DeviceExt;
+
+/// Escapes at or below this value mark interior points.
+const INTERIOR: f32 = -1e38;
+
+/// The number of invocations per workgroup, matching `@workgroup_size` in the shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The number of workgroups dispatched along each row.
+const ROW_WORKGROUPS: u32 = 1024;
+
+/// The uniform parameters of the shader.
+struct Params {
+    max_iter: u32,
+    julia: u32,
+    escape_value: f32,
+    c_offset: [f32; 2],
+}
+
+impl Params {
+    /// Lay out the parameters to match the `Params` struct in the shader.
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.max_iter.to_ne_bytes(),
+            self.julia.to_ne_bytes(),
+            self.escape_value.to_ne_bytes(),
+            0f32.to_ne_bytes(),
+            self.c_offset[0].to_ne_bytes(),
+            self.c_offset[1].to_ne_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// A GPU device with the escape pipeline compiled.
+pub struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl Gpu {
+    /// Acquire the default GPU adapter and compile the escape pipeline.
+    pub fn new() -> Result<Self, crate::Error> {
+        pollster::block_on(Self::request())
+    }
+
+    async fn request() -> Result<Self, crate::Error> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .ok_or_else(|| crate::Error::from(String::from("No GPU adapter is available")))?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    required_features: wgpu::Features::empty(),
+                    // Large renders need storage buffers beyond the default limits.
+                    required_limits: adapter.limits(),
+                },
+                None,
+            )
+            .await
+            .map_err(|e| crate::Error::from(format!("Cannot open GPU device: {}", e)))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("escape"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("escape.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("escape"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Determine if the shader can evaluate escapes for the rctx.
+    ///
+    /// Only quadratic Mandelbrot and Julia functions colored by escape are supported, and
+    /// Mandelbrot orbits must start on the critical point. Zooms deep enough for double-double
+    /// orbits, or for neighboring cells to round to the same `f32` point, are left to the CPU.
+    pub fn supports(rctx: &Rctx) -> bool {
+        let quadratic = match &rctx.complexfn {
+            PolyComplexFn::Mandelbrot(m) => m.exp == 2. && m.z0 == num::Complex::default(),
+            PolyComplexFn::Julia(j) => j.exp == 2.,
+            PolyComplexFn::BurningShip(_)
+            | PolyComplexFn::Tricorn(_)
+            | PolyComplexFn::Newton(_) => false,
+        };
+        quadratic
+            && rctx.color_mode == ColorMode::Escape
+            && !rctx.uses_double_double()
+            && Self::resolves_in_f32(rctx)
+    }
+
+    /// Determine if neighboring cells around the center of the frame are distinct in `f32`.
+    fn resolves_in_f32(rctx: &Rctx) -> bool {
+        let step = |comp: f64| (comp * rctx.loc.scalar).abs();
+        let resolution = |coord: f64| coord.abs() * f64::from(f32::EPSILON);
+        step(rctx.comp.1) >= resolution(rctx.loc.re0)
+            && step(rctx.comp.0) >= resolution(rctx.loc.im0)
+    }
+
+    /// Evaluate the escape matrix for an rctx within the given bounds.
+    ///
+    /// Early bailout is ignored since every orbit is iterated in parallel anyway.
+    pub fn to_ematrix(&self, rctx: &Rctx, bounds: Bounds) -> Result<EMatrix, crate::Error> {
+        let unsupported = || {
+            crate::Error::from(String::from(
+                "GPU rendering only supports quadratic Mandelbrot and Julia functions colored by \
+                 escape at zooms within f32 precision",
+            ))
+        };
+        if !Self::supports(rctx) {
+            return Err(unsupported());
+        }
+
+        let height = usize::from(bounds.height);
+        let width = usize::from(bounds.width);
+        let cells = height * width;
+        if cells == 0 {
+            return EMatrix::from_vec(height, width, vec![]);
+        }
+
+        // Points are derived on the CPU so they match `Rctx::complex_at` exactly, and stored in
+        // the column major layout of an `EMatrix`.
+        let points: Vec<[f32; 2]> = (0..cells)
+            .map(|index| {
+                let pos = Pos {
+                    x: (index / height) as u16,
+                    y: (index % height) as u16,
+                };
+                let c = rctx.complex_at(bounds, pos);
+                [c.re as f32, c.im as f32]
+            })
+            .collect();
+
+        let params = match &rctx.complexfn {
+            PolyComplexFn::Mandelbrot(m) => Params {
+                max_iter: rctx.loc.max_iter,
+                julia: 0,
+                escape_value: m.escape_value() as f32,
+                c_offset: [0., 0.],
+            },
+            PolyComplexFn::Julia(j) => Params {
+                max_iter: rctx.loc.max_iter,
+                julia: 1,
+                escape_value: j.escape_value() as f32,
+                c_offset: [j.c_offset.re as f32, j.c_offset.im as f32],
+            },
+            PolyComplexFn::BurningShip(_)
+            | PolyComplexFn::Tricorn(_)
+            | PolyComplexFn::Newton(_) => return Err(unsupported()),
+        };
+
+        let escapes = self.dispatch(&params, &points)?;
+
+        let escapes: Vec<Escape> = escapes
+            .into_iter()
+            .map(|escape| {
+                if escape <= INTERIOR {
+                    None
+                } else {
+                    Some(f64::from(escape))
+                }
+            })
+            .collect();
+
+        EMatrix::from_vec(height, width, escapes)
+    }
+
+    /// Run the shader over the points and read back the escapes.
+    fn dispatch(&self, params: &Params, points: &[[f32; 2]]) -> Result<Vec<f32>, crate::Error> {
+        let size = (points.len() * std::mem::size_of::<f32>()) as wgpu::BufferAddress;
+
+        let params_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: &params.to_bytes(),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let points_buf = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("points"),
+                contents: bytemuck::cast_slice(points),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let escapes_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("escapes"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("escape"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: escapes_buf.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("escape"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            let workgroups = (points.len() as u32).div_ceil(WORKGROUP_SIZE);
+            let rows = workgroups.div_ceil(ROW_WORKGROUPS);
+            pass.dispatch_workgroups(workgroups.min(ROW_WORKGROUPS), rows, 1);
+        }
+        encoder.copy_buffer_to_buffer(&escapes_buf, 0, &readback_buf, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback_buf.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| crate::Error::from(format!("GPU readback was abandoned: {}", e)))?
+            .map_err(|e| crate::Error::from(format!("Cannot read back GPU escapes: {}", e)))?;
+
+        let escapes = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        readback_buf.unmap();
+
+        Ok(escapes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpu_escapes_agree_with_cpu_escapes() {
+        // Machines without a GPU adapter, such as most CI runners, skip the comparison.
+        let gpu = match Gpu::new() {
+            Ok(gpu) => gpu,
+            Err(e) => {
+                eprintln!("Skipping GPU comparison: {}", e);
+                return;
+            }
+        };
+
+        let bounds = Bounds {
+            width: 64,
+            height: 48,
+        };
+        let rctx = Rctx::default();
+        let gpu_mat = gpu.to_ematrix(&rctx, bounds).unwrap();
+        let cpu_mat = rctx.bind(bounds).to_ematrix();
+        assert_eq!(gpu_mat.len(), cpu_mat.len());
+
+        // Orbits near the boundary can land on either side of the escape limit in f32.
+        let mismatched = gpu_mat
+            .iter()
+            .zip(cpu_mat.iter())
+            .filter(|(gpu_esc, cpu_esc)| match (gpu_esc, cpu_esc) {
+                (Some(g), Some(c)) => (g - c).abs() > 1e-2 * c.abs().max(1.),
+                (None, None) => false,
+                _ => true,
+            })
+            .count();
+        assert!(
+            mismatched * 100 <= cpu_mat.len(),
+            "{} of {} escapes mismatched",
+            mismatched,
+            cpu_mat.len()
+        );
+    }
+
+    #[test]
+    fn zooms_beyond_f32_precision_are_left_to_the_cpu() {
+        let at = |scalar: f64| Rctx {
+            loc: crate::loc::Loc {
+                re0: -0.75,
+                im0: 0.1,
+                scalar,
+                ..crate::loc::Loc::default()
+            },
+            ..Rctx::default()
+        };
+        assert!(Gpu::supports(&Rctx::default()));
+        assert!(Gpu::supports(&at(1e-5)));
+
+        // Cells 1e-9 apart round together in f32 near -0.75, well before double-double orbits.
+        let rounded = at(1e-9);
+        assert!(!rounded.uses_double_double());
+        assert!(!Gpu::supports(&rounded));
+
+        let deep = at(1e-17);
+        assert!(deep.uses_double_double());
+        assert!(!Gpu::supports(&deep));
+    }
+}
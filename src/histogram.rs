@@ -0,0 +1,105 @@
+//! Histograms of escape values, for choosing iteration limits and understanding how escapes are
+//! distributed across a frame.
+
+use crate::ematrix::EMatrix;
+
+/// A histogram of the escapes of a frame, with equal width bins spanning the escape range.
+///
+/// Interior points have no escape and aren't counted.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// The lowest escape value.
+    pub min: f64,
+
+    /// The highest escape value.
+    pub max: f64,
+
+    /// The number of escapes in each bin.
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bin the escapes of a matrix into the given number of bins.
+    pub fn from_ematrix(ematrix: &EMatrix, bins: usize) -> Self {
+        let bins = bins.max(1);
+        let (min, max) = ematrix
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(*v), hi.max(*v))
+            });
+
+        let mut counts = vec![0; bins];
+        if min > max {
+            // No escapes at all.
+            return Self {
+                min: 0.,
+                max: 0.,
+                counts,
+            };
+        }
+
+        let width = (max - min) / bins as f64;
+        for v in ematrix.iter().flatten() {
+            let bin = if width > 0. {
+                (((v - min) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+
+        Self { min, max, counts }
+    }
+
+    /// The lower and upper edges of a bin.
+    pub fn bin_edges(&self, bin: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.counts.len() as f64;
+        (
+            self.min + width * bin as f64,
+            self.min + width * (bin + 1) as f64,
+        )
+    }
+
+    /// Format the histogram as CSV with `lower,upper,count` rows.
+    pub fn to_csv(&self) -> String {
+        let mut buf = String::from("lower,upper,count\n");
+        for (bin, count) in self.counts.iter().enumerate() {
+            let (lower, upper) = self.bin_edges(bin);
+            buf.push_str(&format!("{},{},{}\n", lower, upper, count));
+        }
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bin_counts_sum_to_the_escaping_cells() {
+        let escapes = vec![
+            Some(1.),
+            None,
+            Some(2.5),
+            Some(9.),
+            None,
+            Some(4.),
+            Some(10.),
+            Some(1.),
+            None,
+        ];
+        let ematrix = EMatrix::from_vec(3, 3, escapes).unwrap();
+
+        for bins in [1, 3, 4, 100] {
+            let histogram = Histogram::from_ematrix(&ematrix, bins);
+            assert_eq!(histogram.counts.len(), bins);
+            assert_eq!(histogram.counts.iter().sum::<usize>(), 6, "{} bins", bins);
+        }
+
+        // Bins of width 3 from 1 to 10, with the maximum in the last bin.
+        let histogram = Histogram::from_ematrix(&ematrix, 3);
+        assert_eq!(histogram.counts, vec![3, 1, 2]);
+        assert_eq!(histogram.to_csv().lines().count(), 4);
+    }
+}
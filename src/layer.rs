@@ -0,0 +1,165 @@
+//! Layered specs composite several renders, each with its own function, location, and palette,
+//! into a single image.
+
+use crate::rctx::Rctx;
+use crate::Bounds;
+use image::{RgbImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// How the colors of a layer combine with the layers beneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlendMode {
+    /// Replace the colors beneath.
+    #[default]
+    Normal,
+
+    /// Multiply with the colors beneath, darkening them.
+    Multiply,
+
+    /// Invert, multiply, and invert again, lightening the colors beneath.
+    Screen,
+
+    /// Add to the colors beneath, saturating at white.
+    Add,
+}
+
+impl BlendMode {
+    /// Blend a layer channel over the channel beneath it, with both normalized to `[0, 1]`.
+    fn blend(self, under: f64, over: f64) -> f64 {
+        match self {
+            BlendMode::Normal => over,
+            BlendMode::Multiply => under * over,
+            BlendMode::Screen => 1. - (1. - under) * (1. - over),
+            BlendMode::Add => (under + over).min(1.),
+        }
+    }
+}
+
+fn default_opacity() -> f64 {
+    1.
+}
+
+/// A render composited as one layer of a layered spec.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Layer {
+    /// The rendering context of the layer, in the same form as a spec.
+    pub rctx: Rctx,
+
+    /// The opacity of the layer, from 0 for transparent to 1 for opaque.
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+
+    /// How the layer combines with the layers beneath it.
+    #[serde(default)]
+    pub blend: BlendMode,
+}
+
+impl Layer {
+    /// Render the layer with its opacity as the alpha of every pixel.
+    pub fn render(&self, bounds: Bounds) -> RgbaImage {
+        let img = self
+            .rctx
+            .bind(bounds)
+            .to_ematrix()
+            .to_img(self.rctx.palette().as_ref());
+        let alpha = (self.opacity.clamp(0., 1.) * 255.).round() as u8;
+
+        RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+            let [r, g, b] = img.get_pixel(x, y).0;
+            image::Rgba([r, g, b, alpha])
+        })
+    }
+}
+
+/// A list of layers, from the bottom up.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LayeredSpec {
+    pub layers: Vec<Layer>,
+}
+
+impl LayeredSpec {
+    /// Deserialize a layered spec from JSON.
+    pub fn from_spec_string(spec: &str) -> Result<Self, crate::Error> {
+        serde_json::from_str(spec).map_err(crate::Error::from)
+    }
+
+    /// Render every layer and composite them, from the bottom up, over black.
+    pub fn render(&self, bounds: Bounds) -> RgbImage {
+        let mut img = RgbImage::new(u32::from(bounds.width), u32::from(bounds.height));
+        for layer in &self.layers {
+            composite(&mut img, &layer.render(bounds), layer.blend);
+        }
+        img
+    }
+}
+
+/// Composite a layer over an image in place.
+///
+/// Each pixel is blended with the pixel beneath it and then mixed back in by the layer's alpha,
+/// so a half transparent normal layer averages the two.
+pub fn composite(under: &mut RgbImage, over: &RgbaImage, blend: BlendMode) {
+    for (dst, src) in under.pixels_mut().zip(over.pixels()) {
+        let alpha = f64::from(src[3]) / 255.;
+        for channel in 0..3 {
+            let below = f64::from(dst[channel]) / 255.;
+            let above = f64::from(src[channel]) / 255.;
+            let mixed = below + (blend.blend(below, above) - below) * alpha;
+            dst[channel] = (mixed * 255.).round().clamp(0., 255.) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polycomplex::{Julia, PolyComplexFn};
+
+    #[test]
+    fn half_opaque_layers_average_with_the_layers_beneath() {
+        let mut under = RgbImage::from_pixel(2, 1, image::Rgb([200, 100, 0]));
+        let over = RgbaImage::from_pixel(2, 1, image::Rgba([0, 100, 255, 128]));
+        composite(&mut under, &over, BlendMode::Normal);
+        assert_eq!(under.get_pixel(1, 0).0, [100, 100, 128]);
+
+        let mut under = RgbImage::from_pixel(1, 1, image::Rgb([200, 100, 0]));
+        let over = RgbaImage::from_pixel(1, 1, image::Rgba([0, 100, 255, 255]));
+        composite(&mut under, &over, BlendMode::Multiply);
+        assert_eq!(under.get_pixel(0, 0).0, [0, 39, 0]);
+
+        let bounds = Bounds {
+            width: 24,
+            height: 16,
+        };
+        let spec = LayeredSpec {
+            layers: vec![
+                Layer {
+                    rctx: Rctx::default(),
+                    opacity: 1.,
+                    blend: BlendMode::Normal,
+                },
+                Layer {
+                    rctx: Rctx {
+                        complexfn: PolyComplexFn::Julia(Julia::default()),
+                        ..Rctx::default()
+                    },
+                    opacity: 0.5,
+                    blend: BlendMode::Normal,
+                },
+            ],
+        };
+        let bottom = spec.layers[0].render(bounds);
+        let top = spec.layers[1].render(bounds);
+        let img = spec.render(bounds);
+
+        for (x, y, pixel) in img.enumerate_pixels() {
+            let (below, above) = (bottom.get_pixel(x, y), top.get_pixel(x, y));
+            assert_eq!(above[3], 128);
+            for channel in 0..3 {
+                let (b, a) = (f64::from(below[channel]), f64::from(above[channel]));
+                let expected = (b + (a - b) * 128. / 255.).round() as u8;
+                assert_eq!(pixel[channel], expected, "{:?}", (x, y, channel));
+            }
+        }
+    }
+}
@@ -9,10 +9,26 @@ extern crate serde;
 use serde::{Deserialize, Serialize};
 use std::io;
 
+pub mod coloring;
+pub mod compare;
+pub mod contact;
+pub mod doubledouble;
 pub mod ematrix;
 pub mod frontend;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod histogram;
+pub mod layer;
 pub mod loc;
+pub mod manifest;
+pub mod morph;
+pub mod order;
+pub mod output;
+pub mod palette;
 pub mod polycomplex;
+pub mod postprocess;
+pub mod progress;
+pub mod prompt;
 pub mod rctx;
 pub use polycomplex::*;
 
@@ -29,7 +45,7 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.source.as_ref().map(|e| &**e)
+        self.source.as_deref()
     }
 }
 
@@ -57,11 +73,19 @@ impl From<serde_json::error::Error> for Error {
     }
 }
 
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Self {
+            source: Some(msg.into()),
+        }
+    }
+}
+
 /// An Escape represents the status of an evaluated point's escape iteration.
 pub type Escape = Option<f64>;
 
 /// The bounds for a given image, in column major order.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Bounds {
     pub height: u16,
     pub width: u16,
@@ -74,6 +98,21 @@ impl Bounds {
             y: self.height / 2,
         }
     }
+
+    /// Scale both dimensions by a factor, rounding to the nearest cell.
+    ///
+    /// Each dimension is kept within `1..=u16::MAX`.
+    pub fn scaled(&self, factor: f64) -> Bounds {
+        let scale = |dim: u16| {
+            (f64::from(dim) * factor)
+                .round()
+                .clamp(1., f64::from(u16::MAX)) as u16
+        };
+        Bounds {
+            height: scale(self.height),
+            width: scale(self.width),
+        }
+    }
 }
 
 /// The dimensions of a TTY, in row major order.
@@ -136,12 +175,20 @@ impl From<PositionTuple> for Pos {
 }
 
 /// A single color channel for HSV/RGB conversion.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct SineChannel {
     pub coef: f64,
     pub freq: f64,
     pub phase: f64,
     pub offset: f64,
+
+    /// Gamma correction applied to this channel after the sine is evaluated.
+    #[serde(default = "default_gamma")]
+    pub gamma: f64,
+}
+
+fn default_gamma() -> f64 {
+    1.
 }
 
 fn saturate_channel(i: f64) -> u8 {
@@ -152,6 +199,15 @@ fn saturate_channel(i: f64) -> u8 {
     }
 }
 
+/// Gamma correct a channel value; values above 1 lighten midtones.
+fn gamma_correct(level: u8, gamma: f64) -> u8 {
+    if gamma == 1. {
+        level
+    } else {
+        saturate_channel((f64::from(level) / 255.).powf(1. / gamma) * 255.)
+    }
+}
+
 impl SineChannel {
     const COEF: f64 = 140.;
     const FREQ: f64 = 0.1;
@@ -159,7 +215,18 @@ impl SineChannel {
 
     pub fn compute(&self, i: f64) -> u8 {
         let input = self.coef * ((i * self.freq) + self.phase).sin() + self.offset;
-        saturate_channel(input)
+        gamma_correct(saturate_channel(input), self.gamma)
+    }
+
+    /// Generate a channel with random parameters drawn from ranges that produce pleasing colors.
+    fn random(rng: &mut SplitMix64) -> Self {
+        Self {
+            coef: 100. + rng.unit() * 50.,
+            freq: 0.05 + rng.unit() * 0.25,
+            phase: rng.unit() * std::f64::consts::PI * 2.,
+            offset: 100. + rng.unit() * 30.,
+            gamma: 0.8 + rng.unit() * 0.6,
+        }
     }
 
     pub fn sunset() -> (Self, Self, Self) {
@@ -169,47 +236,191 @@ impl SineChannel {
                 freq: Self::FREQ,
                 phase: std::f64::consts::PI * 9. / 6.,
                 offset: Self::OFFSET,
+                gamma: 1.,
             },
             Self {
                 coef: Self::COEF,
                 freq: Self::FREQ,
                 phase: std::f64::consts::PI * 10. / 6.,
                 offset: Self::OFFSET,
+                gamma: 1.,
             },
             Self {
                 coef: Self::COEF,
                 freq: Self::FREQ,
                 phase: std::f64::consts::PI * 11. / 6.,
                 offset: Self::OFFSET,
+                gamma: 1.,
             },
         )
     }
+
+    /// Channels with phases 120 degrees (2pi/3) apart, sweeping through the full rainbow.
+    pub fn rgb() -> (Self, Self, Self) {
+        let channel = |phase: f64| Self {
+            coef: Self::COEF,
+            freq: Self::FREQ,
+            phase: phase.rem_euclid(std::f64::consts::TAU),
+            offset: Self::OFFSET,
+            gamma: 1.,
+        };
+        let third = std::f64::consts::TAU / 3.;
+        let base = std::f64::consts::PI * 9. / 6.;
+        (
+            channel(base),
+            channel(base + third),
+            channel(base + 2. * third),
+        )
+    }
+}
+
+/// A small deterministic pseudorandom generator for seeded palettes.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a value in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A spec colorer, given either as explicit channels or by the name of a built in colorer.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SineRGBSpec {
+    Channels {
+        channels: (SineChannel, SineChannel, SineChannel),
+        #[serde(default)]
+        period: Option<f64>,
+    },
+    Named(SineRGBName),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SineRGBName {
+    Sunset,
+    Rainbow,
+}
+
+impl From<SineRGBSpec> for SineRGB {
+    fn from(spec: SineRGBSpec) -> Self {
+        match spec {
+            SineRGBSpec::Channels { channels, period } => Self { channels, period },
+            SineRGBSpec::Named(SineRGBName::Sunset) => Self::default(),
+            SineRGBSpec::Named(SineRGBName::Rainbow) => Self::rgb_true(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Specs may give the colorer as explicit channels or as `"sunset"` or `"rainbow"`; it's always
+/// written back out as channels.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "SineRGBSpec")]
 pub struct SineRGB {
     channels: (SineChannel, SineChannel, SineChannel),
+
+    /// The number of escape iterations after which the colors repeat exactly.
+    ///
+    /// Escapes are wrapped into `[0, period)` before the channels are evaluated. When unset the
+    /// colors cycle at the channel frequencies.
+    pub period: Option<f64>,
 }
 
 impl Default for SineRGB {
     fn default() -> Self {
         Self {
             channels: SineChannel::sunset(),
+            period: None,
         }
     }
 }
 
 impl SineRGB {
+    /// A true RGB colorer, with channel phases 120 degrees apart.
+    pub fn rgb_true() -> Self {
+        Self {
+            channels: SineChannel::rgb(),
+            period: None,
+        }
+    }
+
+    /// Deterministically generate a random palette from a seed.
+    ///
+    /// The same seed always produces the same palette, so a look can be shared by its seed.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
+        Self {
+            channels: (
+                SineChannel::random(&mut rng),
+                SineChannel::random(&mut rng),
+                SineChannel::random(&mut rng),
+            ),
+            period: None,
+        }
+    }
+
+    /// Repeat the colors every `period` escape iterations.
+    pub fn with_period(self, period: f64) -> Self {
+        Self {
+            period: Some(period),
+            ..self
+        }
+    }
+
+    /// Gamma correct every channel; values above 1 lighten midtones.
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        for channel in [
+            &mut self.channels.0,
+            &mut self.channels.1,
+            &mut self.channels.2,
+        ] {
+            channel.gamma = gamma;
+        }
+        self
+    }
+
+    /// Wrap an escape into the color period, if there is one.
+    fn wrap(&self, i: f64) -> f64 {
+        match self.period {
+            Some(period) if period > 0. => i.rem_euclid(period),
+            _ => i,
+        }
+    }
+
+    /// Shift the phase of every channel, cycling the colors along the escape gradient.
+    ///
+    /// The channels rotate together, so their registration is preserved. Phases are wrapped into
+    /// `[0, 2π)` so repeated shifts don't grow without bound.
+    pub fn shift_phase(&mut self, delta: f64) {
+        for channel in [
+            &mut self.channels.0,
+            &mut self.channels.1,
+            &mut self.channels.2,
+        ] {
+            channel.phase = (channel.phase + delta).rem_euclid(std::f64::consts::TAU);
+        }
+    }
+
     /// Convert Mandelbrot escape iterations to an RGB value.
     ///
     /// Color is computed by representing (approximate) RGB values with 3 sine waves.
     ///
     /// Note: To produce true RGB the sine waves need to be 120 degrees (2pi/3) apart.
-    /// Using a 60 degree phase offset produces some beautiful sunset colors, so this
-    /// isn't a true RGB conversion. It delights me to inform the reader that in this
-    /// case form trumps function, so deal with it.
+    /// Using a 60 degree phase offset produces some beautiful sunset colors, so the
+    /// default isn't a true RGB conversion. It delights me to inform the reader that in
+    /// this case form trumps function, so deal with it. `rgb_true` is there for the
+    /// pedants.
     pub fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
-        match escape.map(|iters| f64::from(iters)) {
+        match escape.map(|i| self.wrap(i)) {
             None => (0, 0, 0),
             Some(i) => (
                 self.channels.0.compute(i),
@@ -219,3 +430,193 @@ impl SineRGB {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_gamma_only_changes_its_channel() {
+        let plain = SineRGB::default();
+        let mut blue = SineRGB::default();
+        blue.channels.2.gamma = 2.2;
+
+        let mut blue_changed = false;
+        for i in 0..100 {
+            let (a, b) = (plain.rgb(Some(f64::from(i))), blue.rgb(Some(f64::from(i))));
+            assert_eq!((a.0, a.1), (b.0, b.1));
+            blue_changed |= a.2 != b.2;
+        }
+        assert!(blue_changed);
+    }
+
+    #[test]
+    fn channel_gamma_defaults_to_1() {
+        let json = r#"{"coef": 140.0, "freq": 0.1, "phase": 0.0, "offset": 112.0}"#;
+        let channel: SineChannel = serde_json::from_str(json).unwrap();
+
+        assert_eq!(channel.gamma, 1.);
+        assert_eq!(gamma_correct(77, channel.gamma), 77);
+    }
+
+    #[test]
+    fn scaled_bounds_keep_the_rescaled_center() {
+        let frame = Bounds {
+            width: 120,
+            height: 40,
+        };
+        let doubled = frame.scaled(2.);
+        assert_eq!(
+            doubled,
+            Bounds {
+                width: 240,
+                height: 80
+            }
+        );
+        assert_eq!(
+            frame.scaled(0.),
+            Bounds {
+                width: 1,
+                height: 1
+            }
+        );
+
+        let rctx = rctx::Rctx::with_loc(loc::Loc {
+            re0: -0.75,
+            im0: 0.1,
+            scalar: 0.01,
+            max_iter: 200,
+        });
+        let rescaled = rctx.rescaled(&frame, &doubled);
+        assert_eq!(
+            rescaled.complex_at(doubled, doubled.center()),
+            rctx.complex_at(frame, frame.center())
+        );
+        assert_eq!(rescaled.loc.scalar, rctx.loc.scalar / 2.);
+    }
+
+    #[test]
+    fn seeded_palettes_are_reproducible_and_valid() {
+        assert_eq!(SineRGB::from_seed(7), SineRGB::from_seed(7));
+        assert_ne!(SineRGB::from_seed(7), SineRGB::from_seed(8));
+
+        for seed in 0..64 {
+            let palette = SineRGB::from_seed(seed);
+            let (r, g, b) = &palette.channels;
+            for channel in [r, g, b] {
+                assert!(
+                    [channel.coef, channel.phase, channel.offset, channel.gamma]
+                        .iter()
+                        .all(|v| v.is_finite()),
+                    "{:?}",
+                    channel
+                );
+                assert!(
+                    channel.freq.is_finite() && channel.freq > 0.,
+                    "{:?}",
+                    channel
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn phase_shifts_rotate_every_channel_together() {
+        let phases = |palette: &SineRGB| {
+            let (r, g, b) = &palette.channels;
+            [r.phase, g.phase, b.phase]
+        };
+        let original = SineRGB::default();
+
+        for delta in [0.5, -0.5, 10., -25.] {
+            let mut rotated = original.clone();
+            rotated.shift_phase(delta);
+
+            for (before, after) in phases(&original).iter().zip(phases(&rotated)) {
+                assert!((0. ..std::f64::consts::TAU).contains(&after), "{}", after);
+                let advanced = (after - before - delta).rem_euclid(std::f64::consts::TAU);
+                assert!(
+                    advanced < 1e-9 || std::f64::consts::TAU - advanced < 1e-9,
+                    "{} shifted by {} to {}",
+                    before,
+                    delta,
+                    after
+                );
+            }
+
+            // Only the phases change, so the structure of the coloring is kept.
+            let (r, g, b) = &rotated.channels;
+            let (r0, g0, b0) = &original.channels;
+            for (channel, channel0) in [(r, r0), (g, g0), (b, b0)] {
+                assert_eq!(channel.coef, channel0.coef);
+                assert_eq!(channel.freq, channel0.freq);
+                assert_eq!(channel.offset, channel0.offset);
+            }
+        }
+    }
+
+    #[test]
+    fn rainbow_phases_are_a_third_of_a_turn_apart() {
+        let third = std::f64::consts::TAU / 3.;
+        let (r, g, b) = SineRGB::rgb_true().channels;
+        for (from, to) in [(&r, &g), (&g, &b), (&b, &r)] {
+            let apart = (to.phase - from.phase).rem_euclid(std::f64::consts::TAU);
+            assert!((apart - third).abs() < 1e-12, "{} apart", apart);
+        }
+
+        let rainbow: SineRGB = serde_json::from_str(r#""rainbow""#).unwrap();
+        assert_eq!(rainbow, SineRGB::rgb_true());
+        let sunset: SineRGB = serde_json::from_str(r#""sunset""#).unwrap();
+        assert_eq!(sunset, SineRGB::default());
+        assert_ne!(SineRGB::default(), SineRGB::rgb_true());
+    }
+
+    #[test]
+    fn colors_repeat_every_period() {
+        let cyclic = SineRGB::default().with_period(10.);
+        for i in 0..40 {
+            let escape = f64::from(i) * 0.25;
+            let color = cyclic.rgb(Some(escape));
+            assert_eq!(cyclic.rgb(Some(escape + 10.)), color, "at {}", escape);
+            assert_eq!(cyclic.rgb(Some(escape + 30.)), color, "at {}", escape);
+        }
+        assert_ne!(
+            SineRGB::default().rgb(Some(1.)),
+            SineRGB::default().rgb(Some(11.))
+        );
+
+        let spec = serde_json::to_string(&cyclic).unwrap();
+        let loaded: SineRGB = serde_json::from_str(&spec).unwrap();
+        assert_eq!(loaded.period, Some(10.));
+        assert_eq!(loaded, cyclic);
+    }
+
+    #[test]
+    fn gamma_above_1_lightens_midtones() {
+        assert!(gamma_correct(128, 2.2) > gamma_correct(128, 1.));
+        for level in [0, 255] {
+            assert_eq!(gamma_correct(level, 2.2), level);
+        }
+
+        let (plain, corrected) = (SineRGB::default(), SineRGB::default().with_gamma(2.2));
+        let mut lightened = false;
+        for i in 0..100 {
+            let (a, b) = (
+                plain.rgb(Some(f64::from(i))),
+                corrected.rgb(Some(f64::from(i))),
+            );
+            assert!(
+                b.0 >= a.0 && b.1 >= a.1 && b.2 >= a.2,
+                "{:?} and {:?}",
+                a,
+                b
+            );
+            lightened |= a != b;
+        }
+        assert!(lightened);
+
+        let spec = serde_json::to_string(&corrected).unwrap();
+        let loaded: SineRGB = serde_json::from_str(&spec).unwrap();
+        assert_eq!(loaded.channels.1.gamma, 2.2);
+    }
+}
@@ -5,7 +5,11 @@ use num::complex::Complex64;
 use serde::{Deserialize, Serialize};
 
 /// A location, scalar, and rendering context for a position in the complex plane.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+///
+/// Deep zoom locations depend on every bit of the coordinates, so serialized locations must parse
+/// back to the exact same values. `serde_json` writes the shortest representation that round
+/// trips, and its `float_roundtrip` feature is required to parse that representation exactly.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Loc {
     /// The imaginary axis origin.
     pub im0: f64,
@@ -38,7 +42,7 @@ impl Loc {
         Self {
             im0: 0.,
             re0: -0.,
-            scalar: scalar,
+            scalar,
             max_iter: 100,
         }
     }
@@ -48,22 +52,14 @@ impl Loc {
     ///
     /// This acts to downscale/upscale a location.
     pub fn scale(&self, old: &Bounds, new: &Bounds, scale_by: ScaleMethod) -> Self {
-        let scalar: f64;
-
         let re_scalar = f64::from(new.width) / f64::from(old.width);
         let im_scalar = f64::from(new.height) / f64::from(old.height);
 
-        match scale_by {
-            ScaleMethod::Min => {
-                scalar = self.scalar / re_scalar.min(im_scalar);
-            }
-            ScaleMethod::Avg => {
-                scalar = self.scalar / ((re_scalar + im_scalar) / 2.);
-            }
-            ScaleMethod::Max => {
-                scalar = self.scalar / re_scalar.max(im_scalar);
-            }
-        }
+        let scalar = match scale_by {
+            ScaleMethod::Min => self.scalar / re_scalar.min(im_scalar),
+            ScaleMethod::Avg => self.scalar / ((re_scalar + im_scalar) / 2.),
+            ScaleMethod::Max => self.scalar / re_scalar.max(im_scalar),
+        };
 
         Self { scalar, ..*self }
     }
@@ -80,6 +76,88 @@ impl Loc {
         self.im0 = c.im;
         self.re0 = c.re;
     }
+
+    /// Interpolate a fraction `t` of the way from this location toward a target.
+    ///
+    /// The origin and iteration limit are interpolated linearly, and the scalar geometrically so
+    /// equal steps of `t` zoom by equal factors. `t` is clamped to `[0, 1]`, and the ends land
+    /// exactly on either location.
+    pub fn toward(&self, target: &Loc, t: f64) -> Self {
+        let t = t.clamp(0., 1.);
+        if t == 1. {
+            return target.clone();
+        }
+
+        let linear = |from: f64, to: f64| from * (1. - t) + to * t;
+        Self {
+            im0: linear(self.im0, target.im0),
+            re0: linear(self.re0, target.re0),
+            scalar: self.scalar.powf(1. - t) * target.scalar.powf(t),
+            max_iter: linear(f64::from(self.max_iter), f64::from(target.max_iter)).round() as u32,
+        }
+    }
+
+    /// Interpolate a fraction `t` of the way from this location toward a target, moving the
+    /// origin along with the zoom.
+    ///
+    /// The scalar and iteration limit are interpolated as with `toward`, but the origin moves so
+    /// the target drifts toward the center of the screen at a constant rate in pixels. On deep
+    /// zooms this keeps the target in view, where a linear origin would leave it off screen until
+    /// the last few frames.
+    pub fn toward_tracking(&self, target: &Loc, t: f64) -> Self {
+        let mut loc = self.toward(target, t);
+        let remaining = (1. - t.clamp(0., 1.)) * loc.scalar / self.scalar;
+        loc.move_to(target.origin() + (self.origin() - target.origin()) * remaining);
+        loc
+    }
+
+    /// The locations of `frames` evenly spaced steps from this location to a target, including
+    /// both ends.
+    pub fn zoom_frames(&self, target: &Loc, frames: usize, path: CenterPath) -> Vec<Loc> {
+        let steps = frames.saturating_sub(1).max(1) as f64;
+        (0..frames)
+            .map(|i| {
+                let t = i as f64 / steps;
+                match path {
+                    CenterPath::Linear => self.toward(target, t),
+                    CenterPath::Log => self.toward_tracking(target, t),
+                }
+            })
+            .collect()
+    }
+}
+
+/// How the origin moves during a zoom between two locations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CenterPath {
+    /// The origin moves at a constant rate, see `Loc::toward`.
+    Linear,
+
+    /// The origin moves with the zoom, see `Loc::toward_tracking`.
+    Log,
+}
+
+#[derive(Debug)]
+pub struct CenterPathParseError(String);
+
+impl std::fmt::Display for CenterPathParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to center path", self.0)
+    }
+}
+
+impl std::error::Error for CenterPathParseError {}
+
+impl std::str::FromStr for CenterPath {
+    type Err = CenterPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(CenterPath::Linear),
+            "log" => Ok(CenterPath::Log),
+            _ => Err(CenterPathParseError(s.to_string())),
+        }
+    }
 }
 
 /// Generate a default location with scaling set for a terminal.
@@ -93,3 +171,170 @@ impl Default for Loc {
         }
     }
 }
+
+/// A well known location within the quadratic Mandelbrot set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LocPreset {
+    /// Seahorse valley, between the main cardioid and the period 2 bulb.
+    Seahorse,
+
+    /// Elephant valley, right of the main cardioid cusp.
+    Elephant,
+
+    /// A spiral around a Misiurewicz point in seahorse valley.
+    Spiral,
+
+    /// The three way branch point at the tip of the period 3 bulb antenna.
+    TripleBranch,
+
+    /// The Feigenbaum point, where the chain of period doubling bulbs accumulates.
+    Feigenbaum,
+
+    /// The dendrite filaments around `c = i`.
+    Dendrite,
+
+    /// The period 3 minibrot on the real axis.
+    Minibrot,
+}
+
+impl LocPreset {
+    /// Every preset.
+    pub const ALL: [LocPreset; 7] = [
+        LocPreset::Seahorse,
+        LocPreset::Elephant,
+        LocPreset::Spiral,
+        LocPreset::TripleBranch,
+        LocPreset::Feigenbaum,
+        LocPreset::Dendrite,
+        LocPreset::Minibrot,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LocPreset::Seahorse => "seahorse",
+            LocPreset::Elephant => "elephant",
+            LocPreset::Spiral => "spiral",
+            LocPreset::TripleBranch => "triple-branch",
+            LocPreset::Feigenbaum => "feigenbaum",
+            LocPreset::Dendrite => "dendrite",
+            LocPreset::Minibrot => "minibrot",
+        }
+    }
+
+    /// The preset location, authored for the given bounds.
+    pub fn loc(self, bounds: Bounds) -> Loc {
+        // The center, the width of the view along the real axis, and the iteration limit.
+        let (re0, im0, width, max_iter) = match self {
+            LocPreset::Seahorse => (-0.743_643_887_037_158_7, 0.131_825_904_205_312, 1e-4, 1000),
+            LocPreset::Elephant => (0.2925, 0.0149, 1e-2, 500),
+            LocPreset::Spiral => (-0.775_683_77, 0.136_467_37, 1e-3, 1000),
+            LocPreset::TripleBranch => (-0.101_096_363_845_62, 0.956_286_510_809_14, 2e-2, 500),
+            LocPreset::Feigenbaum => (-1.401_155_189, 0., 1e-2, 1000),
+            LocPreset::Dendrite => (0., 1., 5e-2, 500),
+            LocPreset::Minibrot => (-1.754_877_666, 0., 5e-2, 300),
+        };
+
+        Loc {
+            im0,
+            re0,
+            scalar: width / f64::from(bounds.width),
+            max_iter,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocPresetParseError(String);
+
+impl std::fmt::Display for LocPresetParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let names: Vec<&str> = LocPreset::ALL.iter().map(|preset| preset.name()).collect();
+        write!(
+            f,
+            "Cannot parse {} to location preset, expected one of: {}",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for LocPresetParseError {}
+
+impl std::str::FromStr for LocPreset {
+    type Err = LocPresetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LocPreset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name() == s)
+            .ok_or_else(|| LocPresetParseError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialized_locs_keep_every_bit() {
+        // Seahorse valley at the limit of f64 precision, along with its neighboring values.
+        let re0: f64 = -0.743_643_887_037_158_7;
+        let im0: f64 = 0.131_825_904_205_312;
+        for (re0, im0) in [
+            (re0, im0),
+            (
+                f64::from_bits(re0.to_bits() + 1),
+                f64::from_bits(im0.to_bits() - 1),
+            ),
+            (
+                f64::from_bits(re0.to_bits() - 1),
+                f64::from_bits(im0.to_bits() + 1),
+            ),
+        ] {
+            let loc = Loc {
+                im0,
+                re0,
+                scalar: 3.3e-17,
+                max_iter: 50_000,
+            };
+
+            for json in [
+                serde_json::to_string(&loc).unwrap(),
+                serde_json::to_string_pretty(&loc).unwrap(),
+            ] {
+                let parsed: Loc = serde_json::from_str(&json).unwrap();
+                assert_eq!(parsed.re0.to_bits(), loc.re0.to_bits(), "{}", json);
+                assert_eq!(parsed.im0.to_bits(), loc.im0.to_bits(), "{}", json);
+                assert_eq!(parsed.scalar.to_bits(), loc.scalar.to_bits(), "{}", json);
+            }
+        }
+    }
+
+    #[test]
+    fn presets_are_finite_and_deeply_zoomed() {
+        let bounds = Bounds {
+            width: 80,
+            height: 40,
+        };
+        let default = Loc::default();
+        for preset in LocPreset::ALL {
+            assert_eq!(preset.name().parse::<LocPreset>().unwrap(), preset);
+
+            let loc = preset.loc(bounds);
+            assert!(loc.re0.is_finite() && loc.im0.is_finite(), "{:?}", preset);
+            assert!(loc.scalar > 0., "{:?}", preset);
+            assert!(loc.scalar * 100. < default.scalar, "{:?}", preset);
+            assert!(loc.max_iter >= default.max_iter, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn unknown_presets_list_the_valid_names() {
+        let message = "mandelbrot".parse::<LocPreset>().unwrap_err().to_string();
+        assert!(message.contains("mandelbrot"), "{}", message);
+        for preset in LocPreset::ALL {
+            assert!(message.contains(preset.name()), "{}", message);
+        }
+    }
+}
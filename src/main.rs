@@ -6,11 +6,27 @@ extern crate termion;
 extern crate tui;
 
 use indicatif::ProgressBar;
+use mandelbrot::coloring::{ColorMode, ToneMap};
+use mandelbrot::compare;
+use mandelbrot::contact;
+use mandelbrot::doubledouble::Precision;
+use mandelbrot::ematrix::{EMatrix, PpmFormat};
 use mandelbrot::frontend;
-use mandelbrot::rctx::Rctx;
-use mandelbrot::{loc::Loc, Bounds, Error};
+use mandelbrot::histogram::Histogram;
+use mandelbrot::layer::LayeredSpec;
+use mandelbrot::loc::{CenterPath, Loc, LocPreset};
+use mandelbrot::manifest::{ArtifactKind, Manifest};
+use mandelbrot::morph;
+use mandelbrot::order::PixelOrder;
+use mandelbrot::output;
+use mandelbrot::palette::{self, Palette};
+use mandelbrot::postprocess;
+use mandelbrot::progress::{self, Progress};
+use mandelbrot::rctx::{Rctx, SPEC_BOUNDS};
+use mandelbrot::{Bounds, ComplexFn, Error, FunctionKind};
 use std::fs::File;
 use std::io::Read;
+use std::sync::Arc;
 use std::time::Instant;
 use structopt::StructOpt;
 
@@ -45,52 +61,403 @@ impl std::str::FromStr for FrontendType {
 
 fn read_rctx(path: &std::path::PathBuf) -> std::result::Result<Rctx, crate::Error> {
     let mut buf = String::new();
-    File::open(&path)
-        .map_err(|e| crate::Error::from(e))
+    File::open(path)
+        .map_err(crate::Error::from)
         .and_then(|mut fh| {
             // Read the given file into a string and handle errors
-            fh.read_to_string(&mut buf)
-                .map_err(|e| crate::Error::from(e))
+            fh.read_to_string(&mut buf).map_err(crate::Error::from)
         })
         .and_then(|_| {
             // Deserialize the rctx and handle decode errors
-            serde_json::from_str(&buf).map_err(|e| crate::Error::from(e))
+            Rctx::from_spec_string(&buf)
         })
 }
 
+/// Configuration for the `render` subcommand
 #[derive(Debug, StructOpt)]
-#[structopt(name = "mandelbrot")]
-struct AppOptions {
+struct RenderOptions {
+    spec: std::path::PathBuf,
+
+    /// The output image path. Use `-` to write a PNG to stdout.
+    #[structopt(long = "dest")]
+    dest: Option<std::path::PathBuf>,
+
+    #[structopt(long = "height", default_value = "4000")]
+    height: u16,
+
+    #[structopt(long = "width", default_value = "4000")]
+    width: u16,
+
+    /// Override the spec aspect compensation with vertical and horizontal scaling factors.
+    #[structopt(long = "comp", number_of_values = 2, allow_hyphen_values = true)]
+    comp: Vec<f64>,
+
+    /// Compress high dynamic range escapes before coloring: none, reinhard, filmic, or equalize.
+    #[structopt(long = "tone-map", default_value = "none")]
+    tone_map: ToneMap,
+
+    /// Override the spec color mode: escape, distance, or velocity.
+    #[structopt(long = "color-mode")]
+    color_mode: Option<ColorMode>,
+
+    /// Override the spec orbit precision: auto, double, or double-double. Auto switches to
+    /// double-double once f64 can no longer resolve the pixels.
+    #[structopt(long = "precision")]
+    precision: Option<Precision>,
+
+    /// Bail out early on orbits that appear trapped, at the cost of boundary accuracy.
+    #[structopt(long = "early-bailout")]
+    early_bailout: bool,
+
+    /// Color with a built in palette instead of the spec colorer: sunset, rainbow, cividis,
+    /// grayscale, or debug.
+    #[structopt(long = "palette")]
+    palette: Option<palette::Preset>,
+
+    /// Color with a random palette generated from a seed instead of the spec colorer.
+    #[structopt(long = "palette-seed")]
+    palette_seed: Option<u64>,
+
+    /// Color with a GIMP gradient (.ggr) file instead of the spec colorer.
+    #[structopt(long = "palette-ggr")]
+    palette_ggr: Option<std::path::PathBuf>,
+
+    /// Override the spec color period, the number of escape iterations per palette cycle.
+    #[structopt(long = "color-period")]
+    color_period: Option<f64>,
+
+    /// Color interior points with a built in palette instead of black, shaded by their distance
+    /// from the boundary.
+    #[structopt(long = "interior-palette")]
+    interior_palette: Option<palette::Preset>,
+
+    /// Scale the saturation of the colored image; 0 is grayscale.
+    #[structopt(long = "saturation", default_value = "1.0")]
+    saturation: f64,
+
+    /// Scale the contrast of the colored image.
+    #[structopt(long = "contrast", default_value = "1.0")]
+    contrast: f64,
+
+    /// Tint the colored image with light of this color temperature in Kelvin. Values below 6500
+    /// warm the image and values above cool it.
+    #[structopt(long = "temperature")]
+    temperature: Option<f64>,
+
+    /// Make the image symmetric: horizontal, vertical, quad, or kaleidoscope.
+    #[structopt(long = "mirror")]
+    mirror: Option<postprocess::Mirror>,
+
+    /// Crop the rendered image to the pixel rectangle x,y,w,h. The location isn't changed, so
+    /// the crop shows exactly that part of the full frame.
+    #[structopt(long = "crop")]
+    crop: Option<postprocess::Crop>,
+
+    /// Encode and tag the output image with a color space: srgb, linear, or p3. Images are
+    /// untagged by default.
+    #[structopt(long = "color-space")]
+    color_space: Option<output::ColorSpace>,
+
+    /// Make interior points transparent, for compositing the image over others.
+    #[structopt(long = "transparent")]
+    transparent: bool,
+
+    /// Render and encode the PNG a band of rows at a time instead of holding the whole image in
+    /// memory, for very large renders. Options that need the complete frame can't be combined
+    /// with it.
+    #[structopt(long = "streaming")]
+    streaming: bool,
+
+    /// Evaluate escapes on the GPU. Specs the GPU can't evaluate are rendered on the CPU.
+    #[cfg(feature = "gpu")]
+    #[structopt(long = "gpu")]
+    gpu: bool,
+
+    /// The order pixels are evaluated in: column, scanline, boustrophedon, or hilbert.
+    #[structopt(long = "scanline-order")]
+    scanline_order: Option<PixelOrder>,
+
+    /// Also write a downscaled preview with this many pixels along the longest side.
+    #[structopt(long = "proxy-size")]
+    proxy_size: Option<u32>,
+
+    /// Average this many jittered samples per pixel to anti-alias the image.
+    #[structopt(long = "sample-density", default_value = "1")]
+    sample_density: u32,
+
+    /// Anti-alias by sampling each pixel until the variance of its escapes settles.
+    #[structopt(long = "adaptive-aa")]
+    adaptive_aa: bool,
+
+    /// The escape variance below which adaptive anti-aliasing stops sampling a pixel.
+    #[structopt(long = "aa-threshold", default_value = "0.1")]
+    aa_threshold: f64,
+
+    /// The most samples adaptive anti-aliasing takes for a pixel.
+    #[structopt(long = "aa-max-samples", default_value = "64")]
+    aa_max_samples: u32,
+
+    /// Write a CSV histogram of the escape values across the frame.
+    #[structopt(long = "iteration-histogram")]
+    iteration_histogram: Option<std::path::PathBuf>,
+
+    /// The number of bins in the iteration histogram.
+    #[structopt(long = "histogram-bins", default_value = "32")]
+    histogram_bins: usize,
+
+    /// Write the escape values as a single channel 32-bit float TIFF, with interior points as
+    /// NaN.
+    #[structopt(long = "escape-tiff")]
+    escape_tiff: Option<std::path::PathBuf>,
+
+    /// Write the colored escapes as an ASCII (P3) PPM, bypassing the PNG encoder and any
+    /// post-processing.
+    #[structopt(long = "ppm-ascii")]
+    ppm_ascii: Option<std::path::PathBuf>,
+
+    /// Write the colored escapes as a binary (P6) PPM, bypassing the PNG encoder and any
+    /// post-processing.
+    #[structopt(long = "ppm-binary")]
+    ppm_binary: Option<std::path::PathBuf>,
+
+    /// Write a heatmap PNG of the iterations spent on each pixel, and report the total.
+    #[structopt(long = "iteration-profile")]
+    iteration_profile: Option<std::path::PathBuf>,
+
+    /// Write a JSON manifest listing the produced files and their checksums. Only `render`
+    /// writes manifests.
+    #[structopt(long = "manifest")]
+    manifest: Option<std::path::PathBuf>,
+}
+
+/// Configuration for the `contact-sheet` subcommand
+#[derive(Debug, StructOpt)]
+struct ContactSheetOptions {
+    spec: std::path::PathBuf,
+
+    /// The output image path. Use `-` to write a PNG to stdout.
+    #[structopt(long = "dest")]
+    dest: Option<std::path::PathBuf>,
+
+    /// The exponents to render as `start,end,step`.
+    #[structopt(long = "exp-range", default_value = "2,6,1")]
+    exp_range: contact::ExpRange,
+
+    /// The number of cells in each row of the grid.
+    #[structopt(long = "cols", default_value = "3")]
+    cols: u32,
+
+    #[structopt(long = "cell-height", default_value = "400")]
+    cell_height: u16,
+
+    #[structopt(long = "cell-width", default_value = "400")]
+    cell_width: u16,
+
+    /// Advance the palette phase by this many radians for each cell, so the sheet has a color
+    /// progression.
+    #[structopt(
+        long = "loop-palette-offset",
+        default_value = "0",
+        allow_hyphen_values = true
+    )]
+    loop_palette_offset: f64,
+}
+
+/// Configuration for the `julia-path` subcommand
+#[derive(Debug, StructOpt)]
+struct JuliaPathOptions {
+    spec: std::path::PathBuf,
+
+    /// The path of Julia offsets: line:re0,im0,re1,im1,samples, circle:re,im,radius,samples, or
+    /// points:re,im;re,im;...
+    #[structopt(long = "path", allow_hyphen_values = true)]
+    path: morph::JuliaPath,
+
+    /// The directory frames are written to, as julia-0000.png and so on.
+    #[structopt(long = "dest-dir", default_value = ".")]
+    dest_dir: std::path::PathBuf,
+
+    #[structopt(long = "height", default_value = "400")]
+    height: u16,
+
+    #[structopt(long = "width", default_value = "400")]
+    width: u16,
+}
+
+/// Configuration for the `render-zoom` subcommand
+#[derive(Debug, StructOpt)]
+struct ZoomOptions {
+    /// The spec of the first frame.
+    spec: std::path::PathBuf,
+
+    /// The real part of the center of the last frame.
+    #[structopt(long = "re", allow_hyphen_values = true)]
+    re: f64,
+
+    /// The imaginary part of the center of the last frame.
+    #[structopt(long = "im", allow_hyphen_values = true)]
+    im: f64,
+
+    /// The scalar of the last frame, the distance between pixels as with the spec scalar.
+    #[structopt(long = "scalar")]
+    scalar: f64,
+
+    /// The iteration limit of the last frame. Defaults to the spec iteration limit.
+    #[structopt(long = "max-iter")]
+    max_iter: Option<u32>,
+
+    /// The number of frames, including the first and last.
+    #[structopt(long = "frames", default_value = "60")]
+    frames: usize,
+
+    /// How the center moves toward the target: linear, or log to move with the zoom.
+    #[structopt(long = "center-path", default_value = "linear")]
+    center_path: CenterPath,
+
+    /// The directory frames are written to, as frame-0001.png and so on.
+    #[structopt(long = "dest-dir", default_value = ".")]
+    dest_dir: std::path::PathBuf,
+
+    /// Write the frames as a looping animated GIF at this path instead of numbered PNGs.
+    #[structopt(long = "gif")]
+    gif: Option<std::path::PathBuf>,
+
+    /// The frame rate of the animated GIF.
+    #[structopt(long = "fps", default_value = "24")]
+    fps: u32,
+
+    #[structopt(long = "height", default_value = "400")]
+    height: u16,
+
+    #[structopt(long = "width", default_value = "400")]
+    width: u16,
+}
+
+/// Configuration for the `render-layered` subcommand
+#[derive(Debug, StructOpt)]
+struct LayeredOptions {
+    /// A layered spec: a JSON object with a list of `layers` from the bottom up, each with an
+    /// `rctx` spec, an `opacity`, and a `blend` mode of normal, multiply, screen, or add.
+    spec: std::path::PathBuf,
+
+    /// The output image path. Use `-` to write a PNG to stdout.
+    #[structopt(long = "dest")]
+    dest: Option<std::path::PathBuf>,
+
+    #[structopt(long = "height", default_value = "4000")]
+    height: u16,
+
+    #[structopt(long = "width", default_value = "4000")]
+    width: u16,
+}
+
+/// Configuration for the `run` subcommand
+#[derive(Debug, StructOpt)]
+struct ExplorerOptions {
+    #[structopt(long = "frontend")]
+    frontend_type: Option<FrontendType>,
+
     #[structopt(long = "spec")]
     spec: Option<std::path::PathBuf>,
+
+    #[structopt(long = "img-dir")]
+    img_dir: Option<std::path::PathBuf>,
+
+    /// The scalar multiplier for each zoom step, e.g. 1.2 for finer zooming.
+    #[structopt(long = "zoom-factor")]
+    zoom_factor: Option<f64>,
+
+    /// The phase in radians that `.` and `,` rotate the palette by, e.g. 0.05 for finer color
+    /// registration.
+    #[structopt(long = "palette-rotate")]
+    palette_rotate: Option<f64>,
+
+    /// Render screenshots at N times their resolution and downsample them for anti-aliasing.
+    #[structopt(long = "screenshot-supersample", default_value = "1")]
+    screenshot_supersample: u32,
+
+    /// Draw two samples per terminal cell with half block glyphs (termion frontend only).
+    #[structopt(long = "half-block")]
+    half_block: bool,
+
+    /// Draw overlay labels in the terminal's colors, or over a veil of the fractal with
+    /// contrasting text: plain or contrast (termion frontend only).
+    #[structopt(long = "label-style", default_value = "contrast")]
+    label_style: frontend::LabelStyle,
+
+    /// Show a scale bar this many cells long, labeled with the width of the complex plane it
+    /// spans.
+    #[structopt(long = "scale-bar")]
+    scale_bar: Option<u16>,
+
+    /// Draw frames that take longer than this to render at reduced resolution, refining them
+    /// while idle (termion frontend only).
+    #[structopt(long = "frame-budget-ms")]
+    frame_budget_ms: Option<u64>,
+
+    /// Limit how many frames are drawn per second while input is held down; 0 disables the limit.
+    #[structopt(long = "max-fps", default_value = "30")]
+    max_fps: u32,
+
+    /// Start at a well known location of the Mandelbrot set: seahorse, elephant, spiral,
+    /// triple-branch, feigenbaum, dendrite, or minibrot.
+    #[structopt(long = "goto-preset")]
+    goto_preset: Option<LocPreset>,
+
+    /// Start centered and zoomed on the slowest escaping point of the initial view.
+    #[structopt(long = "center-on-max-escape")]
+    center_on_max_escape: bool,
+
+    /// Start with the given function instead of the spec function: mandelbrot, julia,
+    /// burning-ship, tricorn, or newton.
+    #[structopt(long = "function")]
+    function: Option<FunctionKind>,
+
+    /// Start with the given function exponent.
+    #[structopt(long = "exp")]
+    exp: Option<f64>,
+
+    /// Constrain the exponent to whole numbers, rounding the starting exponent and stepping it by
+    /// one at a time.
+    #[structopt(long = "exp-int")]
+    exp_int: bool,
 }
 
 #[derive(Debug, StructOpt)]
 enum Subcommand {
     #[structopt(name = "run")]
-    Run {
-        #[structopt(long = "frontend")]
-        frontend_type: Option<FrontendType>,
+    Run(ExplorerOptions),
 
-        #[structopt(long = "spec")]
-        spec: Option<std::path::PathBuf>,
+    #[structopt(name = "render")]
+    Render(RenderOptions),
 
-        #[structopt(long = "img-dir")]
-        img_dir: Option<std::path::PathBuf>,
-    },
+    #[structopt(name = "contact-sheet")]
+    ContactSheet(ContactSheetOptions),
 
-    #[structopt(name = "render")]
-    Render {
-        spec: std::path::PathBuf,
+    /// Render the Julia set of each point along a path through the Mandelbrot set.
+    #[structopt(name = "julia-path")]
+    JuliaPath(JuliaPathOptions),
+
+    /// Render a sequence of frames zooming from a spec toward a target location.
+    #[structopt(name = "render-zoom")]
+    RenderZoom(ZoomOptions),
+
+    /// Render the layers of a layered spec and composite them into one image.
+    #[structopt(name = "render-layered")]
+    RenderLayered(LayeredOptions),
 
-        #[structopt(long = "dest")]
-        dest: Option<std::path::PathBuf>,
+    /// Print the spec, with defaults and overrides applied, that render would use.
+    #[structopt(name = "explain")]
+    Explain(RenderOptions),
 
-        #[structopt(long = "height", default_value = "4000")]
-        height: u16,
+    #[structopt(name = "image-compare")]
+    ImageCompare {
+        a: std::path::PathBuf,
 
-        #[structopt(long = "width", default_value = "4000")]
-        width: u16,
+        b: std::path::PathBuf,
+
+        #[structopt(long = "heatmap")]
+        heatmap: Option<std::path::PathBuf>,
     },
 }
 
@@ -100,26 +467,71 @@ struct Command {
     subcommand: Subcommand,
 }
 
-/// Run an interactive mandelbrot explorer
-fn run(
-    frontend_type: Option<FrontendType>,
-    spec: Option<std::path::PathBuf>,
-    img_dir: Option<std::path::PathBuf>,
-) -> std::result::Result<(), crate::Error> {
+/// Build the initial rendering context for the explorer from a spec and command line overrides.
+fn initial_rctx(opts: &ExplorerOptions, bounds: Bounds) -> std::result::Result<Rctx, crate::Error> {
     let mut rctx: Rctx;
-    if let Some(ref path) = spec {
-        rctx = read_rctx(&path)?;
-        rctx.comp = (2.3, 1.0);
+    if let Some(ref path) = opts.spec {
+        rctx = read_rctx(path)?;
+        rctx.fit_to_image(&SPEC_BOUNDS, &bounds);
+        rctx.comp = rctx.terminal_comp();
+        rctx.early_bailout = true;
     } else {
-        rctx = Rctx::for_terminal(Some(Loc::for_bounds(termion::terminal_size()?.into())));
+        rctx = Rctx::for_terminal(Some(Loc::for_bounds(bounds)));
+    }
+
+    if let Some(preset) = opts.goto_preset {
+        rctx.complexfn = FunctionKind::Mandelbrot.complexfn(2.);
+        rctx.loc = preset.loc(bounds);
     }
+    if let Some(function) = opts.function {
+        rctx.complexfn = function.complexfn(rctx.complexfn.exp());
+    }
+    if let Some(exp) = opts.exp {
+        *rctx.complexfn.exp_mut() = exp;
+    }
+    if opts.exp_int {
+        rctx.integer_exp = true;
+        let exp = rctx.complexfn.exp_mut();
+        *exp = exp.round().max(2.);
+    }
+    if let Some(zoom_factor) = opts.zoom_factor {
+        rctx.zoom_factor = zoom_factor;
+    }
+    if let Some(phase_step) = opts.palette_rotate {
+        rctx.phase_step = phase_step;
+    }
+    if opts.center_on_max_escape {
+        rctx.center_on_max_escape(bounds);
+    }
+
+    Ok(rctx)
+}
 
-    let mut runtime: Box<dyn mandelbrot::frontend::Frontend> = match frontend_type {
-        None | Some(FrontendType::Termion) => Box::new(mandelbrot::frontend::Termion::build()?),
-        Some(FrontendType::Tui) => Box::new(mandelbrot::frontend::Tui::build()?),
+/// Run an interactive mandelbrot explorer
+fn run(opts: ExplorerOptions) -> std::result::Result<(), crate::Error> {
+    let rctx = initial_rctx(&opts, termion::terminal_size()?.into())?;
+
+    let mut runtime: Box<dyn mandelbrot::frontend::Frontend> = match opts.frontend_type {
+        None | Some(FrontendType::Termion) => {
+            let frame_budget = opts.frame_budget_ms.map(std::time::Duration::from_millis);
+            Box::new(
+                mandelbrot::frontend::Termion::build()?
+                    .half_block(opts.half_block)
+                    .label_style(opts.label_style)
+                    .scale_bar(opts.scale_bar)
+                    .frame_budget(frame_budget),
+            )
+        }
+        Some(FrontendType::Tui) => {
+            Box::new(mandelbrot::frontend::Tui::build()?.scale_bar(opts.scale_bar))
+        }
     };
 
-    frontend::run_with_altscreen(move || runtime.run(rctx, frontend::RunOptions::new(img_dir)))
+    let mut run_options = frontend::RunOptions::new(opts.img_dir);
+    run_options.screenshot_supersample = opts.screenshot_supersample.max(1);
+    run_options.min_frame_interval = frontend::RunOptions::frame_interval(opts.max_fps);
+
+    runtime.run(rctx, run_options)
 }
 
 #[allow(unused)]
@@ -129,24 +541,136 @@ where
 {
     let start = Instant::now();
     let result: U = f();
-    println!("{} elapsed: {:?}", desc, start.elapsed());
+    eprintln!("{} elapsed: {:?}", desc, start.elapsed());
     result
 }
 
+/// Evaluate the escape matrix on the GPU when requested and supported by the spec.
+#[cfg(feature = "gpu")]
+fn gpu_ematrix(
+    enabled: bool,
+    rctx: &Rctx,
+    bounds: Bounds,
+) -> Result<Option<mandelbrot::ematrix::EMatrix>, Error> {
+    use mandelbrot::gpu::Gpu;
+
+    if !enabled {
+        return Ok(None);
+    }
+    if !Gpu::supports(rctx) {
+        eprintln!(
+            "The GPU only evaluates quadratic escapes within f32 precision, rendering on the CPU"
+        );
+        return Ok(None);
+    }
+
+    let gpu = Gpu::new()?;
+    time_fn("ematrix", || gpu.to_ematrix(rctx, bounds)).map(Some)
+}
+
+/// Build the rendering context for a render from a spec and command line overrides.
+fn render_rctx(opts: &RenderOptions) -> std::result::Result<Rctx, crate::Error> {
+    let mut rctx = read_rctx(&opts.spec)?;
+    if let [y, x] = opts.comp[..] {
+        rctx.comp = (y, x);
+    }
+    rctx.early_bailout |= opts.early_bailout;
+    if let Some(color_period) = opts.color_period {
+        rctx.color_period = color_period;
+    }
+    if let Some(color_mode) = opts.color_mode {
+        rctx.color_mode = color_mode;
+    }
+    if let Some(precision) = opts.precision {
+        rctx.precision = precision;
+    }
+
+    Ok(rctx)
+}
+
+/// Print the fully resolved configuration a render would use, without rendering.
+fn explain(opts: RenderOptions) -> std::result::Result<(), crate::Error> {
+    let rctx = render_rctx(&opts)?;
+    let palette = match (opts.palette, opts.palette_seed, &opts.palette_ggr) {
+        (Some(preset), _, _) => format!("preset {:?}", preset),
+        (None, Some(seed), _) => format!("seed {}", seed),
+        (None, None, Some(path)) => format!("gradient {}", path.display()),
+        (None, None, None) => String::from("spec colorer"),
+    };
+    let dest = opts
+        .dest
+        .clone()
+        .unwrap_or_else(|| opts.spec.with_extension("png"));
+
+    println!("{}", rctx.to_spec_string()?);
+    println!("escape:  {}", rctx.complexfn.escape_value());
+    println!("bounds:  {}x{}", opts.width, opts.height);
+    println!("palette: {}", palette);
+    println!("dest:    {}", dest.display());
+    Ok(())
+}
+
+/// Find a render option that needs the complete frame and so can't be used with `--streaming`.
+fn streaming_conflict(opts: &RenderOptions, rctx: &Rctx) -> Option<&'static str> {
+    #[cfg(feature = "gpu")]
+    let gpu = opts.gpu;
+    #[cfg(not(feature = "gpu"))]
+    let gpu = false;
+
+    let conflicts = [
+        (opts.sample_density > 1, "--sample-density"),
+        (opts.adaptive_aa, "--adaptive-aa"),
+        (opts.tone_map != ToneMap::None, "--tone-map"),
+        (
+            rctx.color_mode == ColorMode::Velocity,
+            "the velocity color mode",
+        ),
+        (opts.saturation != 1., "--saturation"),
+        (opts.contrast != 1., "--contrast"),
+        (opts.temperature.is_some(), "--temperature"),
+        (opts.mirror.is_some(), "--mirror"),
+        (opts.crop.is_some(), "--crop"),
+        (opts.color_space.is_some(), "--color-space"),
+        (opts.transparent, "--transparent"),
+        (gpu, "--gpu"),
+        (opts.scanline_order.is_some(), "--scanline-order"),
+        (opts.proxy_size.is_some(), "--proxy-size"),
+        (opts.iteration_histogram.is_some(), "--iteration-histogram"),
+        (opts.escape_tiff.is_some(), "--escape-tiff"),
+        (opts.ppm_ascii.is_some(), "--ppm-ascii"),
+        (opts.ppm_binary.is_some(), "--ppm-binary"),
+        (opts.iteration_profile.is_some(), "--iteration-profile"),
+        (opts.interior_palette.is_some(), "--interior-palette"),
+    ];
+    conflicts
+        .iter()
+        .find(|(conflict, _)| *conflict)
+        .map(|(_, option)| *option)
+}
+
 /// Render a fractal from the given spec/rctx
-fn render(
-    spec: std::path::PathBuf,
-    height: u16,
-    width: u16,
-    dest: Option<std::path::PathBuf>,
-) -> std::result::Result<(), crate::Error> {
-    let mut rctx = read_rctx(&spec)?;
-    rctx.comp = (1., 1.);
+fn render(opts: RenderOptions) -> std::result::Result<(), crate::Error> {
+    let rctx = render_rctx(&opts)?;
+    if opts.streaming {
+        if let Some(conflict) = streaming_conflict(&opts, &rctx) {
+            return Err(Error::from(format!(
+                "--streaming never holds a complete frame and can't be combined with {}",
+                conflict
+            )));
+        }
+    } else {
+        EMatrix::check_capacity(usize::from(opts.height), usize::from(opts.width))?;
+    }
     let bound_rctx = rctx.bind(Bounds {
-        height: height,
-        width: width,
+        height: opts.height,
+        width: opts.width,
     });
 
+    // Every path reports on signals, though only the plain CPU render counts cells as it goes.
+    let cells = u64::from(opts.width) * u64::from(opts.height);
+    let progress = Arc::new(Progress::new(cells));
+    progress::watch_signals(Arc::clone(&progress))?;
+
     // XXX bad conversion
     let bar = ProgressBar::new(0);
 
@@ -156,28 +680,597 @@ fn render(
     );
     bar.set_draw_delta(10000);
 
-    let output_path = dest.unwrap_or(spec.with_extension("png"));
+    let output_path = opts.dest.unwrap_or(opts.spec.with_extension("png"));
+
+    let palette: Box<dyn Palette> = match (opts.palette, opts.palette_seed, &opts.palette_ggr) {
+        (Some(preset), None, None) => preset.palette(),
+        (None, Some(seed), None) => Box::new(mandelbrot::SineRGB::from_seed(seed)),
+        (None, None, Some(path)) => Box::new(palette::GradientPalette::from_ggr(path)?),
+        (None, None, None) => Box::new(rctx.colorer.clone()),
+        _ => {
+            return Err(Error::from(String::from(
+                "--palette, --palette-seed, and --palette-ggr each choose the palette and can't \
+                 be combined",
+            )));
+        }
+    };
+    let palette = rctx.with_color_period(palette);
+
+    let sample_density = opts.sample_density;
+    let sampled = sample_density > 1 || opts.adaptive_aa;
+    let tone_map = opts.tone_map;
+    if sample_density > 1 && opts.adaptive_aa {
+        return Err(Error::from(String::from(
+            "--sample-density and --adaptive-aa are alternative anti-aliasing methods and can't \
+             be combined",
+        )));
+    }
+    if sampled && tone_map != ToneMap::None {
+        return Err(Error::from(String::from(
+            "--tone-map needs complete frames of escapes and can't be combined with \
+             --sample-density or --adaptive-aa",
+        )));
+    }
+    if sampled && opts.iteration_histogram.is_some() {
+        return Err(Error::from(String::from(
+            "--iteration-histogram needs complete frames of escapes and can't be combined with \
+             --sample-density or --adaptive-aa",
+        )));
+    }
+    if sampled && opts.interior_palette.is_some() {
+        return Err(Error::from(String::from(
+            "--interior-palette shades by the distance across a complete frame of escapes and \
+             can't be combined with --sample-density or --adaptive-aa",
+        )));
+    }
+    if sampled && opts.escape_tiff.is_some() {
+        return Err(Error::from(String::from(
+            "--escape-tiff needs complete frames of escapes and can't be combined with \
+             --sample-density or --adaptive-aa",
+        )));
+    }
+    let ppms: Vec<(PpmFormat, std::path::PathBuf)> = vec![
+        (PpmFormat::Ascii, opts.ppm_ascii.clone()),
+        (PpmFormat::Binary, opts.ppm_binary.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(format, path)| path.map(|path| (format, path)))
+    .collect();
+    if sampled && !ppms.is_empty() {
+        return Err(Error::from(String::from(
+            "--ppm-ascii and --ppm-binary color complete frames of escapes and can't be combined \
+             with --sample-density or --adaptive-aa",
+        )));
+    }
+    if opts.transparent {
+        let conflict = if sampled {
+            Some("--sample-density or --adaptive-aa")
+        } else if opts.mirror.is_some() {
+            Some("--mirror")
+        } else if opts.crop.is_some() {
+            Some("--crop")
+        } else if opts.color_space.is_some() {
+            Some("--color-space")
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(Error::from(format!(
+                "--transparent needs the interior of a complete frame of escapes and can't be \
+                 combined with {}",
+                conflict
+            )));
+        }
+    }
+    if opts.streaming {
+        time_fn("streaming", || {
+            output::save_png_streaming(&bound_rctx, palette.as_ref(), &output_path)
+        })?;
+        progress.update(cells);
+        if let Some(manifest_path) = opts.manifest {
+            let mut manifest = Manifest::default();
+            if !output::is_stdout(&output_path) {
+                manifest.push(ArtifactKind::Image, &output_path)?;
+            }
+            manifest.save(&manifest_path)?;
+        }
+        return Ok(());
+    }
+
+    let mut histogram = None;
+    let mut transparent = None;
+
+    let mut img = if opts.adaptive_aa {
+        let (threshold, max_samples) = (opts.aa_threshold, opts.aa_max_samples);
+        let img = time_fn("sampling", || {
+            bound_rctx.to_img_adaptive(palette.as_ref(), threshold, max_samples)
+        });
+        progress.update(cells);
+        img
+    } else if sample_density > 1 {
+        let img = time_fn("sampling", || {
+            bound_rctx.to_img_jittered(palette.as_ref(), sample_density)
+        });
+        progress.update(cells);
+        img
+    } else {
+        //let ematrix = time_fn("ematrix", || bound_rctx.to_ematrix_with_bar(bar));
+        #[cfg(feature = "gpu")]
+        let gpu_ematrix = gpu_ematrix(opts.gpu, &rctx, bound_rctx.bounds)?;
+        #[cfg(not(feature = "gpu"))]
+        let gpu_ematrix = None;
+
+        let ematrix = match (gpu_ematrix, opts.scanline_order) {
+            (Some(ematrix), _) => ematrix,
+            (None, Some(order)) => time_fn("ematrix", || bound_rctx.to_ematrix_ordered(order)),
+            (None, None) => time_fn("ematrix", || bound_rctx.to_ematrix_with_progress(&progress)),
+        };
+        progress.update(cells);
+        if opts.iteration_histogram.is_some() {
+            histogram = Some(Histogram::from_ematrix(&ematrix, opts.histogram_bins));
+        }
+        if let Some(ref path) = opts.escape_tiff {
+            output::save_escape_tiff(&ematrix, path)?;
+        }
+        let ematrix = tone_map.apply(&ematrix, rctx.color_period / 2.);
+        // The interior palette shades by the distance from the boundary of this frame.
+        let dual;
+        let palette = match opts.interior_palette {
+            Some(interior) => {
+                dual = palette::DualPalette::new(palette, interior.palette(), &ematrix);
+                &dual as &dyn Palette
+            }
+            None => palette.as_ref(),
+        };
+        for (format, path) in &ppms {
+            output::save_ppm(&ematrix, palette, *format, path)?;
+        }
+        if opts.transparent {
+            let rgba = time_fn("coloring", || ematrix.to_rgba_img(palette));
+            let img = image::DynamicImage::ImageRgba8(rgba.clone()).to_rgb8();
+            transparent = Some(rgba);
+            img
+        } else {
+            time_fn("coloring", || ematrix.to_img(palette))
+        }
+    };
+
+    if opts.saturation != 1. {
+        postprocess::saturate(&mut img, opts.saturation);
+    }
+    if opts.contrast != 1. {
+        postprocess::contrast(&mut img, opts.contrast);
+    }
+    if let Some(kelvin) = opts.temperature {
+        postprocess::temperature(&mut img, kelvin);
+    }
+    if let Some(mirror) = opts.mirror {
+        postprocess::mirror(&mut img, mirror);
+    }
+    if let Some(rect) = opts.crop {
+        img = postprocess::crop(&img, rect)?;
+    }
+    let color_space = opts.color_space;
+    if let Some(color_space) = color_space {
+        color_space.encode(&mut img);
+    }
+    let save = |img: &image::RgbImage, path: &std::path::Path| match color_space {
+        Some(color_space) => output::save_tagged(img, path, color_space),
+        None => output::save(img, path),
+    };
+
+    match transparent {
+        Some(mut rgba) => {
+            // Carry the adjusted colors over, keeping the interior transparent.
+            for (dst, src) in rgba.pixels_mut().zip(img.pixels()) {
+                dst.0[..3].copy_from_slice(&src.0);
+            }
+            output::save_rgba(&rgba, &output_path)?;
+        }
+        None => save(&img, &output_path)?,
+    }
+
+    // Images streamed to stdout have no file to record.
+    let mut manifest = Manifest::default();
+    if opts.manifest.is_some() && !output::is_stdout(&output_path) {
+        manifest.push(ArtifactKind::Image, &output_path)?;
+    }
+
+    if let Some(size) = opts.proxy_size {
+        // When streaming to stdout the proxy is written alongside the spec instead.
+        let proxy_path = if output::is_stdout(&output_path) {
+            output::proxy_path(&opts.spec.with_extension("png"))
+        } else {
+            output::proxy_path(&output_path)
+        };
+        save(&output::proxy(&img, size), &proxy_path)?;
+        if opts.manifest.is_some() {
+            manifest.push(ArtifactKind::Proxy, &proxy_path)?;
+        }
+    }
+
+    if let (Some(path), Some(histogram)) = (opts.iteration_histogram, histogram) {
+        std::fs::write(&path, histogram.to_csv())?;
+        if opts.manifest.is_some() {
+            manifest.push(ArtifactKind::Histogram, &path)?;
+        }
+    }
+
+    if let Some(ref path) = opts.escape_tiff {
+        if opts.manifest.is_some() {
+            manifest.push(ArtifactKind::Escapes, path)?;
+        }
+    }
+
+    if opts.manifest.is_some() {
+        for (_, path) in ppms.iter().filter(|(_, path)| !output::is_stdout(path)) {
+            manifest.push(ArtifactKind::Ppm, path)?;
+        }
+    }
+
+    if let Some(path) = opts.iteration_profile {
+        let profile = time_fn("profile", || bound_rctx.to_iteration_ematrix());
+        let total: f64 = profile.iter().flatten().sum();
+        eprintln!(
+            "profile: {} iterations, {:.1} per pixel",
+            total,
+            total / profile.len().max(1) as f64
+        );
+        output::save(
+            &profile.to_heatmap(&palette::GradientPalette::heat()),
+            &path,
+        )?;
+        if opts.manifest.is_some() {
+            manifest.push(ArtifactKind::Profile, &path)?;
+        }
+    }
+
+    if let Some(manifest_path) = opts.manifest {
+        manifest.save(&manifest_path)?;
+    }
+
+    Ok(())
+}
+
+/// Render a spec at a range of exponents into a grid
+fn contact_sheet(opts: ContactSheetOptions) -> std::result::Result<(), crate::Error> {
+    let rctx = read_rctx(&opts.spec)?;
+    let cell = Bounds {
+        height: opts.cell_height,
+        width: opts.cell_width,
+    };
+    let exps = opts.exp_range.values();
+    let palettes: Vec<Box<dyn Palette>> =
+        contact::phase_offsets(opts.loop_palette_offset, exps.len())
+            .into_iter()
+            .map(|offset| {
+                let mut rctx = rctx.clone();
+                rctx.colorer.shift_phase(offset);
+                rctx.palette()
+            })
+            .collect();
+
+    let sheet = time_fn("contact sheet", || {
+        contact::contact_sheet(&rctx, &SPEC_BOUNDS, &exps, opts.cols, cell, &palettes)
+    });
+
+    let output_path = opts.dest.unwrap_or(opts.spec.with_extension("sheet.png"));
+    output::save(&sheet, &output_path)
+}
+
+/// Render a Julia frame for each point along a path
+fn julia_path(opts: JuliaPathOptions) -> std::result::Result<(), crate::Error> {
+    let mut rctx = read_rctx(&opts.spec)?;
+    let bounds = Bounds {
+        height: opts.height,
+        width: opts.width,
+    };
+    EMatrix::check_capacity(usize::from(opts.height), usize::from(opts.width))?;
+    rctx.loc = morph::frame_loc(bounds, rctx.loc.max_iter);
+    let palette = rctx.palette();
+
+    let frames = morph::frames(&rctx, &opts.path.points());
+    time_fn("julia path", || -> std::result::Result<(), crate::Error> {
+        for (i, frame) in frames.iter().enumerate() {
+            let img = frame.bind(bounds).to_ematrix().to_img(palette.as_ref());
+            output::save(&img, &opts.dest_dir.join(format!("julia-{:04}.png", i)))?;
+        }
+        Ok(())
+    })
+}
+
+/// Render the frames of a zoom from a spec toward a target location
+fn render_zoom(opts: ZoomOptions) -> std::result::Result<(), crate::Error> {
+    let rctx = read_rctx(&opts.spec)?;
+    let bounds = Bounds {
+        height: opts.height,
+        width: opts.width,
+    };
+    EMatrix::check_capacity(usize::from(opts.height), usize::from(opts.width))?;
+    if opts.frames == 0 || opts.scalar.is_nan() || opts.scalar <= 0. {
+        return Err(Error::from(String::from(
+            "A zoom needs at least one frame and a positive --scalar",
+        )));
+    }
+    let target = Loc {
+        im0: opts.im,
+        re0: opts.re,
+        scalar: opts.scalar,
+        max_iter: opts.max_iter.unwrap_or(rctx.loc.max_iter),
+    };
+    let palette = rctx.palette();
+
+    let mut gif = match opts.gif {
+        Some(ref path) => Some(output::GifAnimation::create(path, opts.fps)?),
+        None => None,
+    };
+
+    let locs = rctx.loc.zoom_frames(&target, opts.frames, opts.center_path);
+    time_fn("zoom", || -> std::result::Result<(), crate::Error> {
+        for (i, loc) in locs.into_iter().enumerate() {
+            let frame = Rctx {
+                loc,
+                ..rctx.clone()
+            };
+            let img = frame.bind(bounds).to_ematrix().to_img(palette.as_ref());
+            match gif {
+                Some(ref mut gif) => gif.push(&img)?,
+                None => output::save(&img, &opts.dest_dir.join(format!("frame-{:04}.png", i + 1)))?,
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Render a layered spec into a single composited image
+fn render_layered(opts: LayeredOptions) -> std::result::Result<(), crate::Error> {
+    let spec = LayeredSpec::from_spec_string(&std::fs::read_to_string(&opts.spec)?)?;
+    let bounds = Bounds {
+        height: opts.height,
+        width: opts.width,
+    };
+    EMatrix::check_capacity(usize::from(opts.height), usize::from(opts.width))?;
 
-    //let ematrix = time_fn("ematrix", || bound_rctx.to_ematrix_with_bar(bar));
-    let ematrix = time_fn("ematrix", || bound_rctx.to_ematrix());
-    let img = time_fn("coloring", || ematrix.to_img(&rctx.colorer));
-    img.save(&output_path).map_err(|e| Error::from(e))
+    let img = time_fn("layers", || spec.render(bounds));
+    let output_path = opts.dest.unwrap_or(opts.spec.with_extension("png"));
+    output::save(&img, &output_path)
+}
+
+/// Compare two rendered images and report how much they differ
+fn image_compare(
+    a: std::path::PathBuf,
+    b: std::path::PathBuf,
+    heatmap: Option<std::path::PathBuf>,
+) -> std::result::Result<(), crate::Error> {
+    let img_a = image::open(&a)?.to_rgb8();
+    let img_b = image::open(&b)?.to_rgb8();
+
+    let diff = compare::ImageDiff::between(&img_a, &img_b)?;
+    println!("max  = {}", diff.max);
+    println!("mean = {:.4}", diff.mean);
+    println!("psnr = {:.2}dB", diff.psnr);
+
+    if let Some(path) = heatmap {
+        compare::heatmap(&img_a, &img_b)?
+            .save_with_format(&path, output::image_format(&path)?)
+            .map_err(Error::from)?;
+    }
+
+    Ok(())
 }
 
 fn main() -> std::result::Result<(), crate::Error> {
     let cmd = Command::from_args();
 
     match cmd.subcommand {
-        Subcommand::Run {
-            frontend_type,
-            spec,
-            img_dir,
-        } => run(frontend_type, spec, img_dir),
-        Subcommand::Render {
-            spec,
-            height,
-            width,
-            dest,
-        } => render(spec, height, width, dest),
+        Subcommand::Run(opts) => run(opts),
+        Subcommand::Render(opts) => render(opts),
+        Subcommand::ContactSheet(opts) => contact_sheet(opts),
+        Subcommand::JuliaPath(opts) => julia_path(opts),
+        Subcommand::RenderZoom(opts) => render_zoom(opts),
+        Subcommand::RenderLayered(opts) => render_layered(opts),
+        Subcommand::Explain(opts) => explain(opts),
+        Subcommand::ImageCompare { a, b, heatmap } => image_compare(a, b, heatmap),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mandelbrot::{BurningShip, Julia, PolyComplexFn};
+
+    const BOUNDS: Bounds = Bounds {
+        width: 80,
+        height: 24,
+    };
+
+    fn explorer_options(args: &[&str]) -> ExplorerOptions {
+        ExplorerOptions::from_iter(std::iter::once("run").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn initial_rctx_starts_with_the_requested_function_and_exponent() {
+        let rctx = initial_rctx(
+            &explorer_options(&["--function", "julia", "--exp", "3"]),
+            BOUNDS,
+        )
+        .unwrap();
+        assert_eq!(
+            rctx.complexfn,
+            PolyComplexFn::Julia(Julia {
+                exp: 3.,
+                ..Julia::default()
+            })
+        );
+
+        // The function keeps the default exponent unless one is given.
+        let rctx =
+            initial_rctx(&explorer_options(&["--function", "burning-ship"]), BOUNDS).unwrap();
+        assert_eq!(
+            rctx.complexfn,
+            PolyComplexFn::BurningShip(BurningShip::default())
+        );
+
+        let rctx = initial_rctx(&explorer_options(&[]), BOUNDS).unwrap();
+        assert_eq!(rctx, Rctx::for_terminal(Some(Loc::for_bounds(BOUNDS))));
+    }
+
+    #[test]
+    fn explained_specs_fill_in_every_defaulted_field() {
+        // A spec written before the optional fields were added.
+        let mut old = serde_json::to_value(Rctx::default()).unwrap();
+        let defaulted = [
+            "early_bailout",
+            "color_mode",
+            "color_period",
+            "zoom_factor",
+            "phase_step",
+            "integer_exp",
+            "precision",
+        ];
+        for field in defaulted {
+            old.as_object_mut().unwrap().remove(field);
+        }
+        let path = std::env::temp_dir().join(format!("mandelbrot-old-{}.json", std::process::id()));
+        std::fs::write(&path, old.to_string()).unwrap();
+
+        let opts = RenderOptions::from_iter([
+            "explain",
+            path.to_str().unwrap(),
+            "--color-mode",
+            "distance",
+        ]);
+        let rctx = render_rctx(&opts).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            rctx,
+            Rctx {
+                color_mode: ColorMode::Distance,
+                ..Rctx::default()
+            }
+        );
+        let explained: serde_json::Value =
+            serde_json::from_str(&rctx.to_spec_string().unwrap()).unwrap();
+        for field in defaulted {
+            assert!(explained.get(field).is_some(), "{} is missing", field);
+        }
+    }
+
+    #[test]
+    fn zoom_frames_step_the_scalar_geometrically() {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-zoom-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec = dir.join("spec.json");
+        let rctx = Rctx::default();
+        std::fs::write(&spec, rctx.to_spec_string().unwrap()).unwrap();
+
+        render_zoom(ZoomOptions::from_iter([
+            "render-zoom",
+            spec.to_str().unwrap(),
+            "--re",
+            "-0.75",
+            "--im",
+            "0.1",
+            "--scalar",
+            "0.001",
+            "--frames",
+            "3",
+            "--dest-dir",
+            dir.to_str().unwrap(),
+            "--width",
+            "16",
+            "--height",
+            "12",
+        ]))
+        .unwrap();
+
+        let target = Loc {
+            re0: -0.75,
+            im0: 0.1,
+            scalar: 0.001,
+            ..rctx.loc.clone()
+        };
+        let locs = rctx.loc.zoom_frames(&target, 3, CenterPath::Linear);
+        let ratios = [
+            locs[1].scalar / locs[0].scalar,
+            locs[2].scalar / locs[1].scalar,
+        ];
+        assert!((ratios[0] - 0.1).abs() < 1e-12, "{:?}", ratios);
+        assert!((ratios[1] - 0.1).abs() < 1e-12, "{:?}", ratios);
+
+        let bounds = Bounds {
+            width: 16,
+            height: 12,
+        };
+        for (i, loc) in locs.into_iter().enumerate() {
+            let frame = image::open(dir.join(format!("frame-{:04}.png", i + 1)))
+                .unwrap()
+                .to_rgb8();
+            let expected = Rctx {
+                loc,
+                ..rctx.clone()
+            }
+            .bind(bounds)
+            .to_ematrix()
+            .to_img(rctx.palette().as_ref());
+            assert_eq!(frame, expected, "frame {}", i + 1);
+        }
+        assert!(!dir.join("frame-0004.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn render_manifests_list_the_image_proxy_and_histogram() {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-render-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec = dir.join("spec.json");
+        std::fs::write(&spec, Rctx::default().to_spec_string().unwrap()).unwrap();
+        let (image, histogram, manifest) = (
+            dir.join("out.png"),
+            dir.join("histogram.csv"),
+            dir.join("manifest.json"),
+        );
+
+        render(RenderOptions::from_iter([
+            "render",
+            spec.to_str().unwrap(),
+            "--dest",
+            image.to_str().unwrap(),
+            "--width",
+            "16",
+            "--height",
+            "12",
+            "--proxy-size",
+            "4",
+            "--iteration-histogram",
+            histogram.to_str().unwrap(),
+            "--manifest",
+            manifest.to_str().unwrap(),
+        ]))
+        .unwrap();
+
+        let loaded: Manifest =
+            serde_json::from_str(&std::fs::read_to_string(&manifest).unwrap()).unwrap();
+        let listed: Vec<_> = loaded
+            .artifacts
+            .iter()
+            .map(|artifact| (artifact.kind, artifact.path.clone()))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                (ArtifactKind::Image, image),
+                (ArtifactKind::Proxy, dir.join("out.proxy.png")),
+                (ArtifactKind::Histogram, histogram),
+            ]
+        );
+        for artifact in &loaded.artifacts {
+            let buf = std::fs::read(&artifact.path).unwrap();
+            assert_eq!(artifact.crc32, format!("{:08x}", crc32fast::hash(&buf)));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,113 @@
+//! Manifests record the files produced by a command, so pipelines can consume renders without
+//! reconstructing output paths.
+//!
+//! Only `render` writes manifests. The frames of `julia-path` and `render-zoom`, and the
+//! `contact-sheet` grid, follow fixed naming schemes in their destination.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The role of a produced file.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    /// The full resolution image.
+    Image,
+
+    /// A downscaled preview of the image.
+    Proxy,
+
+    /// A CSV histogram of escape values.
+    Histogram,
+
+    /// A heatmap of the iterations spent on each pixel.
+    Profile,
+
+    /// A 32-bit float TIFF of escape values.
+    Escapes,
+
+    /// The full resolution image written directly as a PPM.
+    Ppm,
+}
+
+/// A file produced by a command.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: PathBuf,
+
+    /// The CRC-32 checksum of the file contents, as hex.
+    pub crc32: String,
+}
+
+impl Artifact {
+    /// Record a file that has been written, checksumming its contents.
+    pub fn from_file(kind: ArtifactKind, path: &Path) -> Result<Self, crate::Error> {
+        let buf = std::fs::read(path)?;
+        Ok(Self {
+            kind,
+            path: path.to_path_buf(),
+            crc32: format!("{:08x}", crc32fast::hash(&buf)),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Manifest {
+    pub artifacts: Vec<Artifact>,
+}
+
+impl Manifest {
+    /// Record a written file in the manifest.
+    pub fn push(&mut self, kind: ArtifactKind, path: &Path) -> Result<(), crate::Error> {
+        self.artifacts.push(Artifact::from_file(kind, path)?);
+        Ok(())
+    }
+
+    /// Write the manifest as pretty printed JSON.
+    pub fn save(&self, path: &Path) -> Result<(), crate::Error> {
+        let buf = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, buf)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifests_list_every_pushed_artifact() {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-manifest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // An image with a proxy thumbnail and histogram stats beside it.
+        let files = [
+            (ArtifactKind::Image, dir.join("out.png"), "image"),
+            (ArtifactKind::Proxy, dir.join("out.proxy.png"), "proxy"),
+            (ArtifactKind::Histogram, dir.join("out.csv"), "histogram"),
+        ];
+        let mut manifest = Manifest::default();
+        for (kind, path, contents) in &files {
+            std::fs::write(path, contents).unwrap();
+            manifest.push(*kind, path).unwrap();
+        }
+        let manifest_path = dir.join("manifest.json");
+        manifest.save(&manifest_path).unwrap();
+
+        let loaded: Manifest =
+            serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        assert_eq!(loaded.artifacts.len(), 3);
+        for (artifact, (kind, path, contents)) in loaded.artifacts.iter().zip(&files) {
+            assert_eq!(artifact.kind, *kind);
+            assert_eq!(&artifact.path, path);
+            assert_eq!(
+                artifact.crc32,
+                format!("{:08x}", crc32fast::hash(contents.as_bytes()))
+            );
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
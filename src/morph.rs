@@ -0,0 +1,199 @@
+//! Julia morphs render the Julia set of each point along a path through the Mandelbrot set, so
+//! the frames animate how Julia sets change with their offset.
+
+use crate::loc::Loc;
+use crate::polycomplex::{Julia, PolyComplexFn};
+use crate::rctx::Rctx;
+use crate::Bounds;
+use num::complex::Complex64;
+
+/// A path through the complex plane, sampled at a fixed number of points.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JuliaPath {
+    /// Evenly spaced points from one end of a line segment to the other, inclusive.
+    Line {
+        from: Complex64,
+        to: Complex64,
+        samples: usize,
+    },
+
+    /// Evenly spaced points counterclockwise around a circle, starting on its real-most point.
+    ///
+    /// The start isn't repeated at the end, so the frames loop seamlessly.
+    Circle {
+        center: Complex64,
+        radius: f64,
+        samples: usize,
+    },
+
+    /// An explicit list of points.
+    Points(Vec<Complex64>),
+}
+
+impl JuliaPath {
+    /// Sample the points along the path.
+    pub fn points(&self) -> Vec<Complex64> {
+        match *self {
+            JuliaPath::Line { from, to, samples } => {
+                let steps = samples.saturating_sub(1).max(1) as f64;
+                (0..samples)
+                    .map(|i| from + (to - from) * (i as f64 / steps))
+                    .collect()
+            }
+            JuliaPath::Circle {
+                center,
+                radius,
+                samples,
+            } => (0..samples)
+                .map(|i| {
+                    let theta = std::f64::consts::TAU * i as f64 / samples as f64;
+                    center + Complex64::from_polar(&radius, &theta)
+                })
+                .collect(),
+            JuliaPath::Points(ref points) => points.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct JuliaPathParseError(String);
+
+impl std::fmt::Display for JuliaPathParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot parse {} to a path; expected line:re0,im0,re1,im1,samples, \
+             circle:re,im,radius,samples, or points:re,im;re,im;...",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for JuliaPathParseError {}
+
+impl std::str::FromStr for JuliaPath {
+    type Err = JuliaPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || JuliaPathParseError(s.to_string());
+        let floats = |list: &str| -> Result<Vec<f64>, JuliaPathParseError> {
+            list.split(',')
+                .map(|part| part.trim().parse::<f64>().map_err(|_| err()))
+                .collect()
+        };
+        let samples = |value: f64| {
+            if value >= 1. && value.fract() == 0. {
+                Ok(value as usize)
+            } else {
+                Err(err())
+            }
+        };
+
+        let (kind, args) = s.split_once(':').ok_or_else(err)?;
+        match kind {
+            "line" => match floats(args)?[..] {
+                [re0, im0, re1, im1, n] => Ok(JuliaPath::Line {
+                    from: Complex64::new(re0, im0),
+                    to: Complex64::new(re1, im1),
+                    samples: samples(n)?,
+                }),
+                _ => Err(err()),
+            },
+            "circle" => match floats(args)?[..] {
+                [re, im, radius, n] => Ok(JuliaPath::Circle {
+                    center: Complex64::new(re, im),
+                    radius,
+                    samples: samples(n)?,
+                }),
+                _ => Err(err()),
+            },
+            "points" => args
+                .split(';')
+                .map(|point| match floats(point)?[..] {
+                    [re, im] => Ok(Complex64::new(re, im)),
+                    _ => Err(err()),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(JuliaPath::Points),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Frame the whole of a typical Julia set, which lies within a radius of 2 of the origin.
+pub fn frame_loc(bounds: Bounds, max_iter: u32) -> Loc {
+    let shortest = bounds.width.min(bounds.height).max(1);
+    Loc {
+        im0: 0.,
+        re0: 0.,
+        scalar: 3.5 / f64::from(shortest),
+        max_iter,
+    }
+}
+
+/// Create the rendering context of each frame, with the Julia set offset by each point.
+///
+/// Mandelbrot, Burning Ship, tricorn, and Newton functions are converted to the Julia function with
+/// the same exponent, and Julia functions keep their settings apart from the offset.
+pub fn frames(rctx: &Rctx, points: &[Complex64]) -> Vec<Rctx> {
+    points
+        .iter()
+        .map(|c| {
+            let julia = match rctx.complexfn {
+                PolyComplexFn::Mandelbrot(ref m) => Julia::from_c(m, *c),
+                PolyComplexFn::Julia(ref j) => Julia {
+                    c_offset: *c,
+                    ..j.clone()
+                },
+                PolyComplexFn::BurningShip(ref b) => Julia::with_exp(b.exp, *c),
+                PolyComplexFn::Tricorn(ref t) => Julia::with_exp(t.exp, *c),
+                PolyComplexFn::Newton(ref n) => Julia::with_exp(n.exp, *c),
+            };
+            Rctx {
+                complexfn: PolyComplexFn::Julia(julia),
+                ..rctx.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_follow_each_point_of_the_path() {
+        let rctx = Rctx {
+            complexfn: PolyComplexFn::multibrot(3.),
+            ..Rctx::default()
+        };
+
+        for (spec, samples) in [
+            ("line:-0.5,0,0.5,0.5,5", 5),
+            ("circle:0,0,0.7885,8", 8),
+            ("points:-0.4,0.6; 0.285,0.01; -0.8,0.156", 3),
+        ] {
+            let points = spec.parse::<JuliaPath>().unwrap().points();
+            assert_eq!(points.len(), samples, "{}", spec);
+            let frames = frames(&rctx, &points);
+            assert_eq!(frames.len(), samples, "{}", spec);
+
+            for (frame, point) in frames.iter().zip(&points) {
+                match &frame.complexfn {
+                    PolyComplexFn::Julia(julia) => {
+                        assert_eq!(julia.c_offset, *point);
+                        assert_eq!(julia.exp, 3.);
+                    }
+                    f => panic!("{:?} isn't a Julia set", f),
+                }
+                assert_eq!(frame.loc, rctx.loc);
+            }
+        }
+
+        let line: JuliaPath = "line:-0.5,0,0.5,0.5,5".parse().unwrap();
+        let points = line.points();
+        assert_eq!(points.first(), Some(&Complex64::new(-0.5, 0.)));
+        assert_eq!(points.last(), Some(&Complex64::new(0.5, 0.5)));
+        assert!("line:0,0,1,1,2.5".parse::<JuliaPath>().is_err());
+    }
+}
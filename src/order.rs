@@ -0,0 +1,160 @@
+//! Orders in which the pixels of a frame are evaluated.
+//!
+//! The evaluation order doesn't change the rendered escape matrix, but it does change how a frame
+//! fills in when displayed progressively and how neighboring pixels share cache lines.
+
+use crate::{Bounds, Pos};
+
+#[derive(Clone, Copy, Debug)]
+pub enum PixelOrder {
+    /// Column by column, matching the column major layout of an `EMatrix`.
+    Column,
+
+    /// Row by row, left to right.
+    Scanline,
+
+    /// Row by row, alternating between left to right and right to left.
+    Boustrophedon,
+
+    /// Along a Hilbert curve, which keeps consecutive pixels spatially close.
+    Hilbert,
+}
+
+impl PixelOrder {
+    /// Generate every position within the given bounds in this order.
+    pub fn positions(self, bounds: Bounds) -> Vec<Pos> {
+        let (width, height) = (bounds.width, bounds.height);
+
+        match self {
+            PixelOrder::Column => (0..width)
+                .flat_map(|x| (0..height).map(move |y| Pos { x, y }))
+                .collect(),
+            PixelOrder::Scanline => (0..height)
+                .flat_map(|y| (0..width).map(move |x| Pos { x, y }))
+                .collect(),
+            PixelOrder::Boustrophedon => (0..height)
+                .flat_map(|y| {
+                    (0..width).map(move |xi| Pos {
+                        x: if y % 2 == 0 { xi } else { width - 1 - xi },
+                        y,
+                    })
+                })
+                .collect(),
+            PixelOrder::Hilbert => {
+                // Walk a square curve that covers the bounds and skip positions outside of them.
+                let side = u64::from(width.max(height)).next_power_of_two();
+                (0..side * side)
+                    .map(|d| hilbert_d2xy(side, d))
+                    .filter(|(x, y)| *x < u64::from(width) && *y < u64::from(height))
+                    .map(|(x, y)| Pos {
+                        x: x as u16,
+                        y: y as u16,
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Convert a distance along a Hilbert curve filling a `side` by `side` square to a position.
+fn hilbert_d2xy(side: u64, d: u64) -> (u64, u64) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+
+        // Rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+#[derive(Debug)]
+pub struct PixelOrderParseError(String);
+
+impl std::fmt::Display for PixelOrderParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to pixel order", self.0)
+    }
+}
+
+impl std::error::Error for PixelOrderParseError {}
+
+impl std::str::FromStr for PixelOrder {
+    type Err = PixelOrderParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "column" => Ok(PixelOrder::Column),
+            "scanline" => Ok(PixelOrder::Scanline),
+            "boustrophedon" => Ok(PixelOrder::Boustrophedon),
+            "hilbert" => Ok(PixelOrder::Hilbert),
+            _ => Err(PixelOrderParseError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [PixelOrder; 4] = [
+        PixelOrder::Column,
+        PixelOrder::Scanline,
+        PixelOrder::Boustrophedon,
+        PixelOrder::Hilbert,
+    ];
+
+    #[test]
+    fn every_order_visits_each_pixel_once() {
+        // Square, wide, tall, and degenerate bounds, none a power of two.
+        for (width, height) in [(7, 7), (13, 5), (3, 11), (1, 9), (0, 4)] {
+            let bounds = Bounds { width, height };
+            for order in ORDERS {
+                let mut visited: Vec<(u16, u16)> = order
+                    .positions(bounds)
+                    .iter()
+                    .map(|pos| (pos.x, pos.y))
+                    .collect();
+                visited.sort_unstable();
+
+                let expected: Vec<(u16, u16)> = (0..width)
+                    .flat_map(|x| (0..height).map(move |y| (x, y)))
+                    .collect();
+                assert_eq!(visited, expected, "{:?} over {}x{}", order, width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn every_order_renders_the_same_matrix() {
+        let rctx = crate::rctx::Rctx::default();
+        let bound = rctx.bind(Bounds {
+            width: 21,
+            height: 13,
+        });
+        let expected = bound.to_ematrix();
+
+        for order in ORDERS {
+            assert_eq!(
+                bound.to_ematrix_ordered(order).inner(),
+                expected.inner(),
+                "{:?}",
+                order
+            );
+        }
+    }
+}
@@ -0,0 +1,782 @@
+//! Encoding and writing rendered images.
+
+use crate::ematrix::{EMatrix, PpmFormat};
+use crate::palette::Palette;
+use crate::rctx::{BoundRctx, Tile};
+use crate::{Bounds, Pos};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::ImageEncoder;
+use rayon::prelude::*;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// The destination path that indicates output should be written to stdout.
+pub const STDOUT: &str = "-";
+
+/// Determine if a destination path refers to stdout.
+pub fn is_stdout(path: &Path) -> bool {
+    path == Path::new(STDOUT)
+}
+
+/// Encode an image as a PNG into the given writer.
+///
+/// Unlike `RgbImage::save` this doesn't require a seekable destination, so images can be
+/// streamed to stdout or a socket.
+pub fn write_png<W: Write>(img: &image::RgbImage, writer: W) -> Result<(), crate::Error> {
+    PngEncoder::new(writer)
+        .write_image(
+            img.as_raw(),
+            img.width(),
+            img.height(),
+            image::ColorType::Rgb8,
+        )
+        .map_err(crate::Error::from)
+}
+
+/// The image formats output can be written in, by path extension.
+const FORMATS: &[(&str, image::ImageFormat)] = &[
+    ("png", image::ImageFormat::Png),
+    ("jpg", image::ImageFormat::Jpeg),
+    ("jpeg", image::ImageFormat::Jpeg),
+    ("bmp", image::ImageFormat::Bmp),
+    ("tif", image::ImageFormat::Tiff),
+    ("tiff", image::ImageFormat::Tiff),
+    ("ppm", image::ImageFormat::Pnm),
+    ("tga", image::ImageFormat::Tga),
+    ("gif", image::ImageFormat::Gif),
+];
+
+/// Determine the image format to write a path in from its extension.
+pub fn image_format(path: &Path) -> Result<image::ImageFormat, crate::Error> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    FORMATS
+        .iter()
+        .find(|(name, _)| extension.as_deref() == Some(*name))
+        .map(|(_, format)| *format)
+        .ok_or_else(|| {
+            let names: Vec<&str> = FORMATS.iter().map(|(name, _)| *name).collect();
+            crate::Error::from(format!(
+                "Cannot write {}: the extension must be one of {}",
+                path.display(),
+                names.join(", ")
+            ))
+        })
+}
+
+/// Encode an image with an alpha channel as a PNG into the given writer.
+pub fn write_rgba_png<W: Write>(img: &image::RgbaImage, writer: W) -> Result<(), crate::Error> {
+    PngEncoder::new(writer)
+        .write_image(
+            img.as_raw(),
+            img.width(),
+            img.height(),
+            image::ColorType::Rgba8,
+        )
+        .map_err(crate::Error::from)
+}
+
+/// Save an image with an alpha channel to a path, or stream it to stdout as a PNG when the path
+/// is `-`.
+pub fn save_rgba(img: &image::RgbaImage, path: &Path) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        write_rgba_png(img, &mut handle)?;
+        handle.flush().map_err(crate::Error::from)
+    } else {
+        img.save_with_format(path, image_format(path)?)
+            .map_err(crate::Error::from)
+    }
+}
+
+/// Save an image to a path in the format given by its extension, or stream it to stdout as a PNG
+/// when the path is `-`.
+pub fn save(img: &image::RgbImage, path: &Path) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        write_png(img, &mut handle)?;
+        handle.flush().map_err(crate::Error::from)
+    } else {
+        img.save_with_format(path, image_format(path)?)
+            .map_err(crate::Error::from)
+    }
+}
+
+/// The color space output images are encoded in and tagged with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSpace {
+    /// sRGB encoded values, tagged with the `sRGB` chunk.
+    Srgb,
+
+    /// Linear light values with sRGB primaries, tagged with a gamma of 1.
+    Linear,
+
+    /// Values converted to the wider Display P3 primaries and encoded with the sRGB transfer
+    /// function, tagged with the `cICP` chunk and matching gamma and chromaticities for older
+    /// decoders.
+    DisplayP3,
+}
+
+impl ColorSpace {
+    /// Convert rendered values, which are sRGB encoded, to the encoding of the color space.
+    ///
+    /// Colors look the same in every color space; Display P3 only has room for more saturated
+    /// colors than the renderer produces.
+    pub fn encode(self, img: &mut image::RgbImage) {
+        match self {
+            ColorSpace::Srgb => {}
+            ColorSpace::Linear => {
+                for channel in img.iter_mut() {
+                    *channel = srgb_to_linear(*channel);
+                }
+            }
+            ColorSpace::DisplayP3 => {
+                for pixel in img.pixels_mut() {
+                    pixel.0 = srgb_to_p3(pixel.0);
+                }
+            }
+        }
+    }
+}
+
+/// Convert linear light with sRGB primaries to linear light with Display P3 primaries.
+///
+/// Both share the D65 white point, so each row sums to 1 and grays are unchanged.
+const SRGB_TO_P3: [[f64; 3]; 3] = [
+    [0.822_462_1, 0.177_538_0, 0.],
+    [0.033_194_2, 0.966_805_8, 0.],
+    [0.017_082_7, 0.072_397_4, 0.910_519_9],
+];
+
+/// Decode an sRGB encoded value in `[0, 1]` to linear light.
+fn srgb_decode(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode linear light in `[0, 1]` with the sRGB transfer function.
+fn srgb_encode(v: f64) -> f64 {
+    if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Decode an sRGB encoded channel to linear light.
+fn srgb_to_linear(level: u8) -> u8 {
+    (srgb_decode(f64::from(level) / 255.) * 255.).round() as u8
+}
+
+/// Convert an sRGB encoded pixel to Display P3, keeping the sRGB transfer function.
+fn srgb_to_p3(rgb: [u8; 3]) -> [u8; 3] {
+    let linear = rgb.map(|level| srgb_decode(f64::from(level) / 255.));
+    SRGB_TO_P3.map(|row| {
+        let v: f64 = row.iter().zip(linear.iter()).map(|(m, c)| m * c).sum();
+        (srgb_encode(v.clamp(0., 1.)) * 255.).round() as u8
+    })
+}
+
+#[derive(Debug)]
+pub struct ColorSpaceParseError(String);
+
+impl std::fmt::Display for ColorSpaceParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to color space", self.0)
+    }
+}
+
+impl std::error::Error for ColorSpaceParseError {}
+
+impl std::str::FromStr for ColorSpace {
+    type Err = ColorSpaceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "srgb" => Ok(ColorSpace::Srgb),
+            "linear" => Ok(ColorSpace::Linear),
+            "p3" => Ok(ColorSpace::DisplayP3),
+            _ => Err(ColorSpaceParseError(s.to_string())),
+        }
+    }
+}
+
+/// Encode an image as a PNG tagged with a color space into the given writer.
+///
+/// The image values must already be encoded for the color space.
+pub fn write_tagged_png<W: Write>(
+    img: &image::RgbImage,
+    writer: W,
+    color_space: ColorSpace,
+) -> Result<(), crate::Error> {
+    let to_error = |e: png::EncodingError| crate::Error::from(format!("Cannot encode PNG: {}", e));
+
+    let mut encoder = png::Encoder::new(writer, img.width(), img.height());
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    match color_space {
+        ColorSpace::Srgb => encoder.set_srgb(png::SrgbRenderingIntent::Perceptual),
+        ColorSpace::Linear => {
+            encoder.set_source_gamma(png::ScaledFloat::new(1.));
+            encoder.set_source_chromaticities(png::SourceChromaticities::new(
+                (0.3127, 0.3290),
+                (0.64, 0.33),
+                (0.30, 0.60),
+                (0.15, 0.06),
+            ));
+        }
+        ColorSpace::DisplayP3 => {
+            encoder.set_source_gamma(png::ScaledFloat::new(1. / 2.2));
+            encoder.set_source_chromaticities(png::SourceChromaticities::new(
+                (0.3127, 0.3290),
+                (0.680, 0.320),
+                (0.265, 0.690),
+                (0.150, 0.060),
+            ));
+        }
+    }
+
+    let mut writer = encoder.write_header().map_err(to_error)?;
+    if color_space == ColorSpace::DisplayP3 {
+        // P3 D65 primaries, the sRGB transfer function, RGB values, and full range.
+        writer
+            .write_chunk(png::chunk::ChunkType(*b"cICP"), &[12, 13, 0, 1])
+            .map_err(to_error)?;
+    }
+    writer.write_image_data(img.as_raw()).map_err(to_error)?;
+    writer.finish().map_err(to_error)
+}
+
+/// Save an image to a path as a PNG tagged with a color space, or stream it to stdout when the
+/// path is `-`.
+pub fn save_tagged(
+    img: &image::RgbImage,
+    path: &Path,
+    color_space: ColorSpace,
+) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        write_tagged_png(img, &mut handle, color_space)?;
+        handle.flush().map_err(crate::Error::from)
+    } else {
+        let is_png = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        if !is_png {
+            return Err(crate::Error::from(format!(
+                "Cannot tag {} with a color space, only PNG images are supported",
+                path.display()
+            )));
+        }
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write_tagged_png(img, file, color_space)
+    }
+}
+
+/// An endlessly looping animated GIF that frames are encoded into as they're rendered.
+pub struct GifAnimation<W: Write> {
+    encoder: GifEncoder<W>,
+    delay: image::Delay,
+}
+
+impl<W: Write> GifAnimation<W> {
+    /// Start an animation in the given writer, played at `fps` frames per second.
+    ///
+    /// GIF delays are counted in hundredths of a second, so rates above 50 fps play back slower
+    /// than requested in most viewers.
+    pub fn new(writer: W, fps: u32) -> Result<Self, crate::Error> {
+        let delay = gif_delay(fps)?;
+        let mut encoder = GifEncoder::new_with_speed(writer, GIF_QUANTIZER_SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        Ok(Self { encoder, delay })
+    }
+
+    /// Quantize a frame to the GIF palette and append it to the animation.
+    pub fn push(&mut self, img: &image::RgbImage) -> Result<(), crate::Error> {
+        let rgba = image::DynamicImage::ImageRgb8(img.clone()).to_rgba8();
+        self.encoder
+            .encode_frame(image::Frame::from_parts(rgba, 0, 0, self.delay))
+            .map_err(crate::Error::from)
+    }
+}
+
+impl GifAnimation<std::io::BufWriter<std::fs::File>> {
+    /// Start an animation written to a file.
+    pub fn create(path: &Path, fps: u32) -> Result<Self, crate::Error> {
+        gif_delay(fps)?;
+        Self::new(std::io::BufWriter::new(std::fs::File::create(path)?), fps)
+    }
+}
+
+/// The delay between frames of an animation played at `fps` frames per second.
+fn gif_delay(fps: u32) -> Result<image::Delay, crate::Error> {
+    if fps == 0 {
+        return Err(crate::Error::from(String::from(
+            "Cannot play an animation at 0 frames per second",
+        )));
+    }
+    Ok(image::Delay::from_numer_denom_ms(1000, fps))
+}
+
+/// The NeuQuant sampling factor used to build GIF palettes, from 1 (best) to 30 (fastest).
+const GIF_QUANTIZER_SPEED: i32 = 10;
+
+/// The number of rows rendered and encoded at a time by streaming PNG output.
+pub const STREAMING_ROWS: u16 = 64;
+
+/// Render a frame and encode it as a PNG into the given writer one band of `rows` rows at a
+/// time.
+///
+/// Bands are full width tiles from `BoundRctx::tiles`, so only a single band of escapes and
+/// pixels is held in memory and frames far larger than an `RgbImage` could hold can be written.
+/// Bands are colored at their position in the full frame, so the image is identical to coloring
+/// `to_ematrix`, except that frame normalization by the color mode is not applied.
+pub fn write_png_streaming<W: Write, P: Palette + ?Sized>(
+    bound_rctx: &BoundRctx,
+    colorer: &P,
+    rows: u16,
+    writer: W,
+) -> Result<(), crate::Error> {
+    let to_error = |e: png::EncodingError| crate::Error::from(format!("Cannot encode PNG: {}", e));
+    let bounds = bound_rctx.bounds;
+
+    let mut encoder = png::Encoder::new(writer, u32::from(bounds.width), u32::from(bounds.height));
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(to_error)?;
+    let mut stream = writer.stream_writer().map_err(to_error)?;
+
+    let band = Bounds {
+        width: bounds.width,
+        height: rows,
+    };
+    for Tile { origin, ematrix } in bound_rctx.tiles(band) {
+        // Tiles are column major, while PNG rows are written left to right.
+        let ncols = ematrix.ncols();
+        let pixels: Vec<[u8; 3]> = (0..ematrix.len())
+            .into_par_iter()
+            .map(|index| {
+                let (row, col) = (index / ncols, index % ncols);
+                let pos = Pos {
+                    x: origin.x + col as u16,
+                    y: origin.y + row as u16,
+                };
+                let rgb = colorer.rgb_at(pos, ematrix[(row, col)]);
+                [rgb.0, rgb.1, rgb.2]
+            })
+            .collect();
+
+        stream.write_all(&pixels.concat())?;
+    }
+
+    stream.finish().map_err(to_error)
+}
+
+/// Render a frame and save it to a path as a PNG one band at a time, or stream it to stdout when
+/// the path is `-`.
+pub fn save_png_streaming<P: Palette + ?Sized>(
+    bound_rctx: &BoundRctx,
+    colorer: &P,
+    path: &Path,
+) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        write_png_streaming(bound_rctx, colorer, STREAMING_ROWS, &mut handle)?;
+        handle.flush().map_err(crate::Error::from)
+    } else {
+        if image_format(path)? != image::ImageFormat::Png {
+            return Err(crate::Error::from(format!(
+                "Cannot stream {}, only PNG images can be written a band at a time",
+                path.display()
+            )));
+        }
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write_png_streaming(bound_rctx, colorer, STREAMING_ROWS, file)
+    }
+}
+
+/// Encode the escapes of an ematrix as a single channel 32-bit float TIFF into the given writer.
+///
+/// Escapes keep their smoothed values for external analysis rather than being colored, and
+/// interior cells are written as NaN.
+pub fn write_escape_tiff<W: Write + Seek>(
+    ematrix: &EMatrix,
+    writer: W,
+) -> Result<(), crate::Error> {
+    let to_error = |e: tiff::TiffError| crate::Error::from(format!("Cannot encode TIFF: {}", e));
+
+    // TIFF rows are stored top to bottom, while the ematrix is stored column major.
+    let (nrows, ncols) = (ematrix.nrows(), ematrix.ncols());
+    let mut values = Vec::with_capacity(nrows * ncols);
+    for row in 0..nrows {
+        for col in 0..ncols {
+            values.push(ematrix[(row, col)].map_or(f32::NAN, |escape| escape as f32));
+        }
+    }
+
+    tiff::encoder::TiffEncoder::new(writer)
+        .map_err(to_error)?
+        .write_image::<tiff::encoder::colortype::Gray32Float>(ncols as u32, nrows as u32, &values)
+        .map_err(to_error)
+}
+
+/// Save the escapes of an ematrix as a 32-bit float TIFF.
+///
+/// TIFF encoding needs a seekable destination, so escapes can't be streamed to stdout.
+pub fn save_escape_tiff(ematrix: &EMatrix, path: &Path) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        return Err(crate::Error::from(String::from(
+            "Escape TIFFs can't be written to stdout",
+        )));
+    }
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_escape_tiff(ematrix, file)
+}
+
+/// Color the escapes of an ematrix and save them as a PPM image.
+pub fn save_ppm<P: Palette + ?Sized>(
+    ematrix: &EMatrix,
+    colorer: &P,
+    format: PpmFormat,
+    path: &Path,
+) -> Result<(), crate::Error> {
+    if is_stdout(path) {
+        return ematrix.write_ppm(colorer, format, std::io::stdout().lock());
+    }
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    ematrix.write_ppm(colorer, format, file)
+}
+
+/// Downscale an image so its longest side is at most `size` pixels, preserving the aspect ratio.
+///
+/// Images that already fit are returned unchanged.
+pub fn proxy(img: &image::RgbImage, size: u32) -> image::RgbImage {
+    let longest = img.width().max(img.height());
+    if longest <= size {
+        return img.clone();
+    }
+
+    let scale = f64::from(size) / f64::from(longest);
+    let width = ((f64::from(img.width()) * scale).round() as u32).max(1);
+    let height = ((f64::from(img.height()) * scale).round() as u32).max(1);
+    image::imageops::resize(img, width, height, image::imageops::FilterType::Triangle)
+}
+
+/// Downsample a supersampled image by averaging each `factor` by `factor` block of pixels.
+///
+/// Trailing pixels that don't fill a complete block are dropped.
+pub fn downsample(img: &image::RgbImage, factor: u32) -> image::RgbImage {
+    let factor = factor.max(1);
+    let area = factor * factor;
+    image::RgbImage::from_fn(img.width() / factor, img.height() / factor, |x, y| {
+        let mut acc = [0u32; 3];
+        for dy in 0..factor {
+            for dx in 0..factor {
+                let pixel = img.get_pixel(x * factor + dx, y * factor + dy);
+                for (sum, channel) in acc.iter_mut().zip(pixel.0.iter()) {
+                    *sum += u32::from(*channel);
+                }
+            }
+        }
+        image::Rgb([
+            ((acc[0] + area / 2) / area) as u8,
+            ((acc[1] + area / 2) / area) as u8,
+            ((acc[2] + area / 2) / area) as u8,
+        ])
+    })
+}
+
+/// Determine the path of the proxy image for a given output path.
+///
+/// `out.png` has the proxy `out.proxy.png`.
+pub fn proxy_path(path: &Path) -> PathBuf {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png");
+    path.with_extension(format!("proxy.{}", ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rctx::Rctx;
+
+    #[test]
+    fn streamed_pngs_match_to_img() {
+        let rctx = Rctx::default();
+        let bound = rctx.bind(Bounds {
+            width: 37,
+            height: 29,
+        });
+        let palette = rctx.palette();
+
+        // Band heights that divide the frame, leave a short last band, and exceed the frame.
+        for rows in [1, 8, 29, 64] {
+            let mut png = Vec::new();
+            write_png_streaming(&bound, palette.as_ref(), rows, &mut png).unwrap();
+
+            let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+                .unwrap()
+                .to_rgb8();
+            assert_eq!(
+                decoded,
+                bound.to_ematrix().to_img(palette.as_ref()),
+                "{} rows",
+                rows
+            );
+        }
+    }
+
+    #[test]
+    fn written_pngs_decode_to_the_same_image() {
+        let img = image::RgbImage::from_fn(13, 7, |x, y| image::Rgb([x as u8, y as u8, 200]));
+        let mut png = Vec::new();
+        write_png(&img, &mut png).unwrap();
+
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(decoded.dimensions(), (13, 7));
+        assert_eq!(decoded, img);
+    }
+
+    #[test]
+    fn only_a_dash_refers_to_stdout() {
+        assert!(is_stdout(Path::new("-")));
+        assert!(!is_stdout(Path::new("./-")));
+        assert!(!is_stdout(Path::new("out.png")));
+    }
+
+    /// Create an empty scratch directory for a test's output files.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mandelbrot-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn proxies_are_saved_beside_the_full_image() {
+        // Red on the left half and blue on the right.
+        let img = image::RgbImage::from_fn(200, 100, |x, _| {
+            if x < 100 {
+                image::Rgb([255, 0, 0])
+            } else {
+                image::Rgb([0, 0, 255])
+            }
+        });
+        let dir = scratch_dir("proxy");
+        let path = dir.join("out.png");
+        save(&img, &path).unwrap();
+        save(&proxy(&img, 50), &proxy_path(&path)).unwrap();
+
+        let full = image::open(&path).unwrap().to_rgb8();
+        let small = image::open(dir.join("out.proxy.png")).unwrap().to_rgb8();
+        assert_eq!(full, img);
+        assert_eq!(small.dimensions(), (50, 25));
+        assert_eq!(small.get_pixel(10, 12).0, [255, 0, 0]);
+        assert_eq!(small.get_pixel(40, 12).0, [0, 0, 255]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn images_that_fit_are_their_own_proxy() {
+        let img = image::RgbImage::from_fn(30, 40, |x, y| image::Rgb([x as u8, y as u8, 0]));
+        assert_eq!(proxy(&img, 40), img);
+        assert_eq!(
+            proxy_path(Path::new("a/out.jpg")),
+            Path::new("a/out.proxy.jpg")
+        );
+    }
+
+    /// Determine if an encoded PNG contains a chunk of the given type.
+    fn has_chunk(png: &[u8], chunk: &[u8; 4]) -> bool {
+        png.windows(4).any(|window| window == chunk)
+    }
+
+    #[test]
+    fn color_spaces_are_tagged_and_encoded() {
+        let img = image::RgbImage::from_fn(8, 4, |x, _| {
+            let level = [0, 128, 255][x as usize % 3];
+            image::Rgb([level, level, level])
+        });
+
+        let mut srgb = img.clone();
+        ColorSpace::Srgb.encode(&mut srgb);
+        assert_eq!(srgb, img);
+        let mut png = Vec::new();
+        write_tagged_png(&srgb, &mut png, ColorSpace::Srgb).unwrap();
+        assert!(has_chunk(&png, b"sRGB"));
+
+        // Linear light drops the sRGB gamma encoding, darkening the midtones.
+        let mut linear = img.clone();
+        "linear".parse::<ColorSpace>().unwrap().encode(&mut linear);
+        assert_eq!(linear.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(linear.get_pixel(1, 0).0, [55, 55, 55]);
+        assert_eq!(linear.get_pixel(2, 0).0, [255, 255, 255]);
+        let mut png = Vec::new();
+        write_tagged_png(&linear, &mut png, ColorSpace::Linear).unwrap();
+        assert!(!has_chunk(&png, b"sRGB"));
+        assert!(has_chunk(&png, b"gAMA"));
+
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(decoded, linear);
+    }
+
+    #[test]
+    fn display_p3_converts_to_the_wider_primaries() {
+        let img = image::RgbImage::from_fn(5, 1, |x, _| {
+            [
+                image::Rgb([255, 0, 0]),
+                image::Rgb([0, 255, 0]),
+                image::Rgb([0, 0, 255]),
+                image::Rgb([128, 128, 128]),
+                image::Rgb([255, 255, 255]),
+            ][x as usize]
+        });
+
+        let mut p3 = img.clone();
+        ColorSpace::DisplayP3.encode(&mut p3);
+
+        // Saturated sRGB primaries sit inside the P3 gamut, so they're less saturated in P3.
+        assert_eq!(p3.get_pixel(0, 0).0, [234, 51, 35]);
+        assert_ne!(p3.get_pixel(1, 0).0, [0, 255, 0]);
+        assert_ne!(p3.get_pixel(2, 0).0, [0, 0, 255]);
+
+        // Both share the D65 white point, so grays are unchanged.
+        assert_eq!(p3.get_pixel(3, 0), img.get_pixel(3, 0));
+        assert_eq!(p3.get_pixel(4, 0), img.get_pixel(4, 0));
+
+        let mut png = Vec::new();
+        write_tagged_png(&p3, &mut png, ColorSpace::DisplayP3).unwrap();
+        assert!(has_chunk(&png, b"cICP"));
+    }
+
+    #[test]
+    fn escape_tiffs_round_trip_escapes_with_nan_interiors() {
+        let ematrix = Rctx::default()
+            .bind(Bounds {
+                width: 23,
+                height: 17,
+            })
+            .to_ematrix();
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        write_escape_tiff(&ematrix, &mut tiff).unwrap();
+
+        tiff.set_position(0);
+        let mut decoder = tiff::decoder::Decoder::new(tiff).unwrap();
+        assert_eq!(decoder.dimensions().unwrap(), (23, 17));
+        let values = match decoder.read_image().unwrap() {
+            tiff::decoder::DecodingResult::F32(values) => values,
+            _ => panic!("escape TIFFs hold 32-bit floats"),
+        };
+
+        let mut interior = 0;
+        for (index, value) in values.iter().enumerate() {
+            let (row, col) = (index / 23, index % 23);
+            match ematrix[(row, col)] {
+                Some(escape) => {
+                    assert!(
+                        (f64::from(*value) - escape).abs() <= escape.abs() * 1e-6,
+                        "{} at {:?}",
+                        value,
+                        (row, col)
+                    );
+                }
+                None => {
+                    interior += 1;
+                    assert!(value.is_nan(), "{} at {:?}", value, (row, col));
+                }
+            }
+        }
+        assert!(interior > 0);
+    }
+
+    #[test]
+    fn saved_images_decode_in_the_format_of_their_extension() {
+        let rctx = Rctx::default();
+        let img = rctx
+            .bind(Bounds {
+                width: 19,
+                height: 11,
+            })
+            .to_ematrix()
+            .to_img(rctx.palette().as_ref());
+        let dir = scratch_dir("formats");
+
+        for extension in ["bmp", "ppm", "PPM"] {
+            let path = dir.join(format!("out.{}", extension));
+            save(&img, &path).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0, "{}", extension);
+            assert_eq!(image::open(&path).unwrap().to_rgb8(), img, "{}", extension);
+        }
+
+        for name in ["out.webp", "out"] {
+            let err = save(&img, &dir.join(name)).unwrap_err().to_string();
+            assert!(err.contains("bmp, tif"), "{}", err);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gif_animations_decode_to_their_frames() {
+        use image::AnimationDecoder;
+
+        let frames = [
+            image::RgbImage::from_pixel(8, 6, image::Rgb([255, 0, 0])),
+            image::RgbImage::from_pixel(8, 6, image::Rgb([0, 0, 255])),
+        ];
+        let dir = scratch_dir("gif");
+        let path = dir.join("zoom.gif");
+        let mut gif = GifAnimation::create(&path, 10).unwrap();
+        for frame in &frames {
+            gif.push(frame).unwrap();
+        }
+        drop(gif);
+
+        let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let decoded = image::codecs::gif::GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 2);
+        for (frame, expected) in decoded.iter().zip(&frames) {
+            assert_eq!(
+                std::time::Duration::from(frame.delay()),
+                std::time::Duration::from_millis(100)
+            );
+            let rgb = image::DynamicImage::ImageRgba8(frame.buffer().clone()).to_rgb8();
+            assert_eq!(rgb.dimensions(), (8, 6));
+
+            // Frames are quantized to the GIF palette, so colors are only close.
+            let (got, want) = (rgb.get_pixel(4, 3), expected.get_pixel(4, 3));
+            for channel in 0..3 {
+                assert!(
+                    got[channel].abs_diff(want[channel]) <= 8,
+                    "{:?}",
+                    (got, want)
+                );
+            }
+        }
+
+        assert!(GifAnimation::create(&dir.join("still.gif"), 0).is_err());
+        assert!(!dir.join("still.gif").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,721 @@
+//! Palettes map escapes to colors.
+
+use crate::{ematrix::EMatrix, Escape, Pos, SineRGB};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A mapping from escape values to RGB colors.
+///
+/// Palettes are shared across the threads that color a frame, so they must be `Sync`.
+pub trait Palette: Sync {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8);
+
+    /// Color an escape at a given position within the frame.
+    ///
+    /// Most palettes only depend on the escape, but diagnostic palettes can use the position.
+    fn rgb_at(&self, _pos: Pos, escape: Escape) -> (u8, u8, u8) {
+        self.rgb(escape)
+    }
+}
+
+impl Palette for SineRGB {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        self.rgb(escape)
+    }
+}
+
+/// A palette that linearly interpolates between a series of color stops.
+///
+/// Escape values sweep forward and back across the gradient once per period, so the coloring
+/// stays continuous for arbitrarily large escape values. Interior points are black.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GradientPalette {
+    /// Color stops as `(position, rgb)` pairs, with positions ascending within `[0, 1]`.
+    pub stops: Vec<(f64, (u8, u8, u8))>,
+
+    /// The number of escape iterations needed to sweep across the gradient and back.
+    pub period: f64,
+}
+
+impl GradientPalette {
+    const PERIOD: f64 = 64.;
+
+    pub fn new(stops: Vec<(f64, (u8, u8, u8))>, period: f64) -> Self {
+        Self { stops, period }
+    }
+
+    /// A perceptually uniform palette designed to be legible with color vision deficiency.
+    ///
+    /// Control points are taken from the cividis colormap (Nuñez, Anderton, and Renslow, 2018).
+    pub fn cividis() -> Self {
+        Self::new(
+            vec![
+                (0.00, (0x00, 0x20, 0x4d)),
+                (0.25, (0x41, 0x4d, 0x6b)),
+                (0.50, (0x7c, 0x7b, 0x78)),
+                (0.75, (0xbc, 0xaf, 0x6f)),
+                (1.00, (0xff, 0xea, 0x46)),
+            ],
+            Self::PERIOD,
+        )
+    }
+
+    /// A black body palette running from black through red and yellow to white, for heatmaps.
+    pub fn heat() -> Self {
+        Self::new(
+            vec![
+                (0.00, (0x00, 0x00, 0x00)),
+                (0.35, (0xc0, 0x10, 0x00)),
+                (0.70, (0xff, 0xc0, 0x00)),
+                (1.00, (0xff, 0xff, 0xff)),
+            ],
+            Self::PERIOD,
+        )
+    }
+
+    /// Sample the gradient at a position within `[0, 1]`.
+    pub fn sample(&self, t: f64) -> (u8, u8, u8) {
+        let first = match self.stops.first() {
+            Some(stop) => stop,
+            None => return (0, 0, 0),
+        };
+
+        if t <= first.0 {
+            return first.1;
+        }
+
+        for pair in self.stops.windows(2) {
+            let (lo, hi) = (&pair[0], &pair[1]);
+            if t <= hi.0 {
+                let span = hi.0 - lo.0;
+                let frac = if span > 0. { (t - lo.0) / span } else { 1. };
+                return (
+                    lerp(lo.1 .0, hi.1 .0, frac),
+                    lerp(lo.1 .1, hi.1 .1, frac),
+                    lerp(lo.1 .2, hi.1 .2, frac),
+                );
+            }
+        }
+
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+impl GradientPalette {
+    /// The number of stops a GIMP gradient is sampled into.
+    const GGR_SAMPLES: usize = 256;
+
+    /// Load a GIMP gradient (`.ggr`) file.
+    pub fn from_ggr(path: &Path) -> Result<Self, crate::Error> {
+        Self::parse_ggr(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the contents of a GIMP gradient into a palette.
+    ///
+    /// The gradient is sampled into a lookup table of evenly spaced stops, so its blending
+    /// functions and midpoints are followed closely while coloring stays a simple interpolation.
+    /// Colors are interpolated in RGB even for segments GIMP would blend in HSV, and alpha is
+    /// ignored.
+    pub fn parse_ggr(ggr: &str) -> Result<Self, crate::Error> {
+        let mut lines = ggr.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some("GIMP Gradient") {
+            return Err(ggr_error("missing the GIMP Gradient header"));
+        }
+
+        let mut line = lines.next();
+        if line.is_some_and(|line| line.starts_with("Name:")) {
+            line = lines.next();
+        }
+        let count: usize = line
+            .and_then(|line| line.parse().ok())
+            .ok_or_else(|| ggr_error("missing the segment count"))?;
+
+        if count == 0 {
+            return Err(ggr_error("no segments"));
+        }
+
+        let segments = lines
+            .take(count)
+            .map(GgrSegment::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if segments.len() != count {
+            return Err(ggr_error(&format!(
+                "expected {} segments, found {}",
+                count,
+                segments.len()
+            )));
+        }
+
+        let stops = (0..Self::GGR_SAMPLES)
+            .map(|i| {
+                let t = i as f64 / (Self::GGR_SAMPLES - 1) as f64;
+                let segment = segments
+                    .iter()
+                    .find(|segment| t <= segment.right)
+                    .unwrap_or(&segments[segments.len() - 1]);
+                let [r, g, b] = segment.color(t);
+                (t, (r, g, b))
+            })
+            .collect();
+
+        Ok(Self::new(stops, Self::PERIOD))
+    }
+}
+
+fn ggr_error(reason: &str) -> crate::Error {
+    crate::Error::from(format!("Malformed GIMP gradient: {}", reason))
+}
+
+/// A segment of a GIMP gradient, blending between two colors across `[left, right]`.
+struct GgrSegment {
+    left: f64,
+    middle: f64,
+    right: f64,
+    left_color: [f64; 3],
+    right_color: [f64; 3],
+
+    /// The blending function: linear, curved, sine, sphere increasing, sphere decreasing, or
+    /// step, numbered from 0.
+    blend: u8,
+}
+
+impl GgrSegment {
+    /// Parse a segment line: the left, middle, and right positions, the left and right RGBA
+    /// colors, the blending function, and the coloring type, optionally followed by endpoint
+    /// color types.
+    fn parse(line: &str) -> Result<Self, crate::Error> {
+        let fields = line
+            .split_whitespace()
+            .map(|field| field.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ggr_error(&format!("non-numeric segment {:?}", line)))?;
+        if fields.len() < 13 {
+            return Err(ggr_error(&format!("short segment {:?}", line)));
+        }
+
+        let blend = fields[11];
+        if !(0. ..=5.).contains(&blend) || blend.fract() != 0. {
+            return Err(ggr_error(&format!("unknown blending function {}", blend)));
+        }
+
+        Ok(Self {
+            left: fields[0],
+            middle: fields[1],
+            right: fields[2],
+            left_color: [fields[3], fields[4], fields[5]],
+            right_color: [fields[7], fields[8], fields[9]],
+            blend: blend as u8,
+        })
+    }
+
+    /// Determine the color at a position within the segment.
+    fn color(&self, t: f64) -> [u8; 3] {
+        let factor = self.factor(t);
+        let mut color = [0; 3];
+        for (channel, (left, right)) in self.left_color.iter().zip(&self.right_color).enumerate() {
+            let level = left + (right - left) * factor;
+            color[channel] = (level.clamp(0., 1.) * 255.).round() as u8;
+        }
+        color
+    }
+
+    /// Determine how far a position has blended from the left color to the right, following
+    /// GIMP's blending functions.
+    fn factor(&self, t: f64) -> f64 {
+        const EPSILON: f64 = 1e-10;
+
+        let span = self.right - self.left;
+        let (pos, middle) = if span < EPSILON {
+            (0.5, 0.5)
+        } else {
+            ((t - self.left) / span, (self.middle - self.left) / span)
+        };
+        let pos = pos.clamp(0., 1.);
+
+        let linear = if pos <= middle {
+            if middle < EPSILON {
+                0.
+            } else {
+                0.5 * pos / middle
+            }
+        } else if 1. - middle < EPSILON {
+            1.
+        } else {
+            0.5 + 0.5 * (pos - middle) / (1. - middle)
+        };
+
+        match self.blend {
+            1 => pos.powf(0.5f64.ln() / middle.max(EPSILON).ln()),
+            2 => ((std::f64::consts::PI * linear - std::f64::consts::FRAC_PI_2).sin() + 1.) / 2.,
+            3 => (1. - (linear - 1.).powi(2)).sqrt(),
+            4 => 1. - (1. - linear.powi(2)).sqrt(),
+            5 => {
+                if pos >= middle {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            _ => linear,
+        }
+    }
+}
+
+fn lerp(a: u8, b: u8, frac: f64) -> u8 {
+    (f64::from(a) + (f64::from(b) - f64::from(a)) * frac).round() as u8
+}
+
+impl Palette for GradientPalette {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        match escape {
+            None => (0, 0, 0),
+            Some(i) => {
+                // Map the escape onto a triangle wave so the gradient reverses rather than
+                // wrapping around at each period.
+                let phase = (i / self.period).rem_euclid(1.) * 2.;
+                let t = if phase > 1. { 2. - phase } else { phase };
+                self.sample(t)
+            }
+        }
+    }
+}
+
+/// A palette mapping escapes to gray levels, for printing and plain scientific output.
+///
+/// Escapes ramp linearly from black at 0 to white at `period` and stay white beyond it, so
+/// unlike the cyclic palettes the gray level orders points by escape. Interior points are black.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GrayscalePalette {
+    /// Gamma correction applied to the ramp; values above 1 lighten midtones.
+    pub gamma: f64,
+
+    /// The escape at which the ramp reaches white.
+    pub period: f64,
+}
+
+impl GrayscalePalette {
+    const PERIOD: f64 = 64.;
+
+    pub fn new(gamma: f64) -> Self {
+        Self {
+            gamma,
+            period: Self::PERIOD,
+        }
+    }
+}
+
+impl Default for GrayscalePalette {
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+impl Palette for GrayscalePalette {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        match escape {
+            None => (0, 0, 0),
+            Some(i) => {
+                let t = (i / self.period).clamp(0., 1.).powf(1. / self.gamma);
+                let level = (t * 255.).round() as u8;
+                (level, level, level)
+            }
+        }
+    }
+}
+
+/// The colorer a spec renders with.
+///
+/// Specs written before there was a choice of colorer give a bare `SineRGB`, and still load as
+/// one; colorers are always written back out tagged with their kind.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "ColorerSpec")]
+pub enum Colorer {
+    SineRGB(SineRGB),
+    Grayscale(GrayscalePalette),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorerSpec {
+    Tagged(TaggedColorer),
+    SineRGB(SineRGB),
+}
+
+#[derive(Deserialize)]
+enum TaggedColorer {
+    SineRGB(SineRGB),
+    Grayscale(GrayscalePalette),
+}
+
+impl From<ColorerSpec> for Colorer {
+    fn from(spec: ColorerSpec) -> Self {
+        match spec {
+            ColorerSpec::Tagged(TaggedColorer::SineRGB(sine)) | ColorerSpec::SineRGB(sine) => {
+                Colorer::SineRGB(sine)
+            }
+            ColorerSpec::Tagged(TaggedColorer::Grayscale(gray)) => Colorer::Grayscale(gray),
+        }
+    }
+}
+
+impl Default for Colorer {
+    fn default() -> Self {
+        Colorer::SineRGB(SineRGB::default())
+    }
+}
+
+impl Colorer {
+    /// Shift the phase of the colorer, cycling its colors along the escape gradient.
+    ///
+    /// Grayscale ramps order points by escape and have no phase, so they're left unchanged.
+    pub fn shift_phase(&mut self, delta: f64) {
+        match self {
+            Colorer::SineRGB(sine) => sine.shift_phase(delta),
+            Colorer::Grayscale(_) => {}
+        }
+    }
+}
+
+impl Palette for Colorer {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        match self {
+            Colorer::SineRGB(sine) => sine.rgb(escape),
+            Colorer::Grayscale(gray) => gray.rgb(escape),
+        }
+    }
+}
+
+/// A palette that colors exterior and interior points of a frame with separate palettes.
+///
+/// Escaped points are colored by the exterior palette. Interior points carry no escape value, so
+/// they are colored by the interior palette with their distance in cells from the nearest escaped
+/// point instead, shading the interior away from the boundary. Within `BLEND_CELLS` of the
+/// boundary, interior colors blend into the exterior color of that nearest point.
+///
+/// Distances are measured across the frame the palette was created for and looked up by position.
+/// Interior points colored without a position, or in frames where nothing escaped, get the
+/// interior palette's base color, i.e. its color for an escape of 0.
+pub struct DualPalette {
+    pub exterior: Box<dyn Palette>,
+    pub interior: Box<dyn Palette>,
+
+    /// The distance of each cell from the nearest escaped cell and its escape, as given by
+    /// `EMatrix::boundary_distances`.
+    boundary: Vec<Option<(f64, f64)>>,
+    nrows: usize,
+}
+
+impl DualPalette {
+    /// The distance in cells over which interior colors blend into the exterior colors.
+    pub const BLEND_CELLS: f64 = 3.;
+
+    /// Create a palette for coloring the escapes of `ematrix`.
+    pub fn new(exterior: Box<dyn Palette>, interior: Box<dyn Palette>, ematrix: &EMatrix) -> Self {
+        Self {
+            exterior,
+            interior,
+            boundary: ematrix.boundary_distances(),
+            nrows: ematrix.nrows(),
+        }
+    }
+
+    fn nearest_escape(&self, pos: Pos) -> Option<(f64, f64)> {
+        let index = usize::from(pos.x) * self.nrows + usize::from(pos.y);
+        self.boundary.get(index).copied().flatten()
+    }
+}
+
+impl Palette for DualPalette {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        match escape {
+            Some(_) => self.exterior.rgb(escape),
+            None => self.interior.rgb(Some(0.)),
+        }
+    }
+
+    fn rgb_at(&self, pos: Pos, escape: Escape) -> (u8, u8, u8) {
+        match (escape, self.nearest_escape(pos)) {
+            (Some(_), _) => self.exterior.rgb_at(pos, escape),
+            (None, Some((distance, nearest))) => {
+                let outside = self.exterior.rgb_at(pos, Some(nearest));
+                let inside = self.interior.rgb_at(pos, Some(distance));
+                let frac = (distance / Self::BLEND_CELLS).min(1.);
+                (
+                    lerp(outside.0, inside.0, frac),
+                    lerp(outside.1, inside.1, frac),
+                    lerp(outside.2, inside.2, frac),
+                )
+            }
+            (None, None) => self.interior.rgb_at(pos, Some(0.)),
+        }
+    }
+}
+
+/// A palette that rescales escape values before coloring them with another palette.
+///
+/// A scale above 1 packs more color cycles into the same range of escapes.
+pub struct ScaledPalette {
+    pub inner: Box<dyn Palette>,
+    pub scale: f64,
+}
+
+impl ScaledPalette {
+    pub fn new(inner: Box<dyn Palette>, scale: f64) -> Self {
+        Self { inner, scale }
+    }
+}
+
+impl Palette for ScaledPalette {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        self.inner.rgb(escape.map(|i| i * self.scale))
+    }
+
+    fn rgb_at(&self, pos: Pos, escape: Escape) -> (u8, u8, u8) {
+        self.inner.rgb_at(pos, escape.map(|i| i * self.scale))
+    }
+}
+
+/// A diagnostic palette that colors cells with a checkerboard of their positions.
+///
+/// Cells alternate between light and dark squares, with interior points drawn darker than escaped
+/// points. The square at the origin is red, so transposed or flipped frames are easy to spot.
+#[derive(Clone, Debug)]
+pub struct CheckerboardPalette {
+    /// The width and height of each square in cells.
+    pub cell: u16,
+}
+
+impl CheckerboardPalette {
+    const CELL: u16 = 8;
+
+    pub fn new(cell: u16) -> Self {
+        Self { cell: cell.max(1) }
+    }
+}
+
+impl Default for CheckerboardPalette {
+    fn default() -> Self {
+        Self::new(Self::CELL)
+    }
+}
+
+impl Palette for CheckerboardPalette {
+    fn rgb(&self, escape: Escape) -> (u8, u8, u8) {
+        self.rgb_at(Pos { x: 0, y: 0 }, escape)
+    }
+
+    fn rgb_at(&self, pos: Pos, escape: Escape) -> (u8, u8, u8) {
+        let (col, row) = (pos.x / self.cell, pos.y / self.cell);
+        if col == 0 && row == 0 {
+            return (0xff, 0x00, 0x00);
+        }
+
+        let light = (col + row) % 2 == 0;
+        match (escape, light) {
+            (Some(_), true) => (0xe0, 0xe0, 0xe0),
+            (Some(_), false) => (0x80, 0x80, 0x80),
+            (None, true) => (0x40, 0x40, 0x40),
+            (None, false) => (0x00, 0x00, 0x00),
+        }
+    }
+}
+
+/// A named, built in palette.
+#[derive(Clone, Copy, Debug)]
+pub enum Preset {
+    Sunset,
+    Rainbow,
+    Cividis,
+    Grayscale,
+    Debug,
+}
+
+impl Preset {
+    pub fn palette(self) -> Box<dyn Palette> {
+        match self {
+            Preset::Sunset => Box::new(SineRGB::default()),
+            Preset::Rainbow => Box::new(SineRGB::rgb_true()),
+            Preset::Cividis => Box::new(GradientPalette::cividis()),
+            Preset::Grayscale => Box::new(GrayscalePalette::default()),
+            Preset::Debug => Box::new(CheckerboardPalette::default()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PresetParseError(String);
+
+impl std::fmt::Display for PresetParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to palette", self.0)
+    }
+}
+
+impl std::error::Error for PresetParseError {}
+
+impl std::str::FromStr for Preset {
+    type Err = PresetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sunset" => Ok(Preset::Sunset),
+            "rainbow" => Ok(Preset::Rainbow),
+            "cividis" => Ok(Preset::Cividis),
+            "grayscale" => Ok(Preset::Grayscale),
+            "debug" => Ok(Preset::Debug),
+            _ => Err(PresetParseError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cividis_matches_its_reference_control_points() {
+        let cividis: Preset = "cividis".parse().unwrap();
+        let palette = cividis.palette();
+        let period = GradientPalette::PERIOD;
+
+        assert_eq!(palette.rgb(Some(0.)), (0x00, 0x20, 0x4d));
+        assert_eq!(palette.rgb(Some(period / 4.)), (0x7c, 0x7b, 0x78));
+        assert_eq!(palette.rgb(Some(period / 2.)), (0xff, 0xea, 0x46));
+        assert_eq!(palette.rgb(None), (0, 0, 0));
+    }
+
+    #[test]
+    fn dual_palettes_color_interior_and_exterior_separately() {
+        // A frame escaping along its left column, with interior cells to the right.
+        let (nrows, ncols) = (4, 12);
+        let escapes = (0..nrows * ncols)
+            .map(|i| if i < nrows { Some(3.) } else { None })
+            .collect();
+        let ematrix = EMatrix::from_vec(nrows, ncols, escapes).unwrap();
+        let interior = GradientPalette::new(
+            vec![(0., (0, 0, 0)), (1., (0, 0, 250))],
+            GradientPalette::PERIOD,
+        );
+        let dual = DualPalette::new(
+            Box::new(SineRGB::default()),
+            Box::new(interior.clone()),
+            &ematrix,
+        );
+
+        for i in 0..50 {
+            let escape = Some(f64::from(i) * 0.7);
+            let pos = Pos { x: 0, y: 1 };
+            assert_eq!(dual.rgb(escape), SineRGB::default().rgb(escape));
+            assert_eq!(dual.rgb_at(pos, escape), SineRGB::default().rgb(escape));
+        }
+
+        // Interior cells shade with their distance from the boundary, blending into the exterior
+        // color next to it.
+        let colors: Vec<_> = (1..ncols as u16)
+            .map(|x| dual.rgb_at(Pos { x, y: 1 }, None))
+            .collect();
+        let exterior = SineRGB::default().rgb(Some(3.));
+        let blended = dual.rgb_at(Pos { x: 1, y: 1 }, None);
+        assert_ne!(blended, exterior);
+        assert_ne!(blended, interior.rgb(Some(1.)));
+        for x in 3..ncols as u16 {
+            let distance = f64::from(x);
+            assert_eq!(
+                dual.rgb_at(Pos { x, y: 1 }, None),
+                interior.rgb(Some(distance))
+            );
+        }
+        assert!(
+            colors.windows(2).all(|pair| pair[0] != pair[1]),
+            "{:?}",
+            colors
+        );
+
+        // Without a position there's no distance to shade by.
+        assert_eq!(dual.rgb(None), interior.rgb(Some(0.)));
+    }
+
+    #[test]
+    fn checkerboards_alternate_between_adjacent_squares() {
+        let checkerboard = CheckerboardPalette::new(1);
+        for escape in [Some(3.), None] {
+            for x in 1..12 {
+                for y in 1..12 {
+                    let rgb = checkerboard.rgb_at(Pos { x, y }, escape);
+                    assert_ne!(rgb, checkerboard.rgb_at(Pos { x: x + 1, y }, escape));
+                    assert_ne!(rgb, checkerboard.rgb_at(Pos { x, y: y + 1 }, escape));
+                    assert_eq!(rgb, checkerboard.rgb_at(Pos { x: x + 1, y: y + 1 }, escape));
+                }
+            }
+        }
+
+        // Cells within a square share its color, and only the origin square is red.
+        let checkerboard = CheckerboardPalette::default();
+        let at = |x, y| checkerboard.rgb_at(Pos { x, y }, Some(3.));
+        assert_eq!(at(0, 0), (0xff, 0x00, 0x00));
+        assert_eq!(at(8, 0), at(15, 7));
+        assert_ne!(at(8, 0), at(16, 0));
+        assert_ne!(at(8, 0), at(8, 8));
+    }
+
+    #[test]
+    fn grayscale_ramps_from_black_to_white() {
+        let grayscale = GrayscalePalette::default();
+        assert_eq!(grayscale.rgb(None), (0, 0, 0));
+        assert_eq!(grayscale.rgb(Some(0.)), (0, 0, 0));
+        assert_eq!(grayscale.rgb(Some(grayscale.period)), (255, 255, 255));
+        assert_eq!(grayscale.rgb(Some(grayscale.period * 2.)), (255, 255, 255));
+
+        let mut previous = 0;
+        for i in 0..=80 {
+            let (r, g, b) = grayscale.rgb(Some(f64::from(i)));
+            assert!(r == g && g == b, "{:?} at {}", (r, g, b), i);
+            assert!(r >= previous, "{} after {} at {}", r, previous, i);
+            previous = r;
+        }
+
+        let midtone = Some(grayscale.period / 2.);
+        assert_eq!(grayscale.rgb(midtone), (128, 128, 128));
+        assert!(GrayscalePalette::new(2.2).rgb(midtone).0 > 128);
+    }
+
+    const TWO_SEGMENT_GGR: &str = "GIMP Gradient
+Name: Red to blue to white
+2
+0.000000 0.250000 0.500000 1 0 0 1 0 0 1 1 0 0
+0.500000 0.750000 1.000000 0 0 1 1 1 1 1 1 2 0
+";
+
+    #[test]
+    fn ggr_segments_blend_between_their_colors() {
+        let gradient = GradientPalette::parse_ggr(TWO_SEGMENT_GGR).unwrap();
+        assert_eq!(gradient.sample(0.), (255, 0, 0));
+        assert_eq!(gradient.sample(1.), (255, 255, 255));
+
+        // Halfway through the linear red to blue segment is purple.
+        let (r, g, b) = gradient.sample(0.25);
+        assert!(r.abs_diff(128) <= 1 && g == 0 && b.abs_diff(128) <= 1);
+
+        // The sine blend eases out of blue, lagging behind a linear blend's 64.
+        assert_eq!(gradient.sample(0.625), (38, 38, 255));
+
+        assert_eq!(gradient.rgb(None), (0, 0, 0));
+        assert_eq!(gradient.rgb(Some(0.)), (255, 0, 0));
+        assert_eq!(gradient.rgb(Some(gradient.period / 2.)), (255, 255, 255));
+    }
+
+    #[test]
+    fn malformed_ggrs_are_errors() {
+        for ggr in [
+            "",
+            "Not a gradient\n1\n0 0.5 1 0 0 0 1 1 1 1 1 0 0",
+            "GIMP Gradient\nName: Empty\n0",
+            "GIMP Gradient\n2\n0 0.25 0.5 1 0 0 1 0 0 1 1 0 0",
+            "GIMP Gradient\n1\n0 0.5 1 0 0 0 1",
+            "GIMP Gradient\n1\n0 0.5 1 0 0 0 1 1 1 1 1 9 0",
+            "GIMP Gradient\n1\n0 0.5 1 red 0 0 1 1 1 1 1 0 0",
+        ] {
+            assert!(GradientPalette::parse_ggr(ggr).is_err(), "{:?}", ggr);
+        }
+    }
+}
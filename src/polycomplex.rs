@@ -4,13 +4,80 @@
 //!
 //! - https://www.math.univ-toulouse.fr/~cheritat/wiki-draw/index.php/Mandelbrot_set
 
+use crate::doubledouble::DoubleDoubleComplex;
 use crate::Escape;
-use num::complex::Complex64;
+use num::complex::{Complex, Complex64};
+use num::Float;
 use serde::{Deserialize, Serialize};
 
 /// A complex polynomial function with a variable exponent.
 pub trait ComplexFn {
     fn escape(&self, c: Complex64, limit: u32) -> Escape;
+
+    /// Evaluate an escape, bailing out early on orbits that appear trapped by an attracting cycle.
+    ///
+    /// This trades correctness near the set boundary for speed; functions that don't implement a
+    /// bailout heuristic evaluate the full escape.
+    fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape(c, limit)
+    }
+
+    /// The squared norm past which a point is considered escaped.
+    ///
+    /// Functions with an `escape_value` use it when set, and otherwise derive the threshold from
+    /// the exponent.
+    fn escape_value(&self) -> f64;
+
+    /// Evaluate an escape with the orbit iterated in the float type of `c`.
+    ///
+    /// `f32` orbits are faster but lose precision at deep zooms; `escape` iterates in `f64`.
+    fn escape_in<T: Float>(&self, c: Complex<T>, limit: u32) -> Escape
+    where
+        Self: Sized,
+    {
+        self.traced_orbit(c, limit, false).0
+    }
+
+    /// Evaluate an escape with the orbit iterated in double-double precision, for zooms too deep
+    /// for `f64`.
+    ///
+    /// Only integer exponents have an exact double-double power, so other exponents fall back to
+    /// an `f64` orbit.
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape;
+
+    /// Count the iterations evaluated for `c`, for profiling where a render spends its time.
+    ///
+    /// Escaping points count the iterations up to and including the one they escaped on, points
+    /// ruled out by the interior, periodicity, or trapped orbit checks count the iterations done
+    /// before that, and other interior points count the full `limit`.
+    fn iterations(&self, c: Complex64, limit: u32, bailout: bool) -> u32
+    where
+        Self: Sized,
+    {
+        self.traced_orbit(c, limit, bailout).1
+    }
+
+    /// Iterate the orbit of `c`, returning its escape and the number of iterations evaluated.
+    ///
+    /// `bailout` enables the trapped orbit check of `escape_bailout`, and is ignored by functions
+    /// without one.
+    ///
+    /// The generic orbit methods require `Self: Sized`, which keeps `ComplexFn` usable as a trait
+    /// object.
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> (Escape, u32)
+    where
+        Self: Sized;
+
+    /// Estimate the distance from `c` to the boundary of the set.
+    ///
+    /// Interior points have no distance estimate.
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64>;
+
+    /// Determine the magnitude of the orbit derivative when `c` escapes.
+    ///
+    /// Interior points have no escape velocity.
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64>;
+
     fn exp(&self) -> f64;
     fn exp_mut(&mut self) -> &mut f64;
 }
@@ -25,43 +92,295 @@ pub trait ComplexFn {
 /// - `iters`: the number of iterations needed to exceed the escape threshold.
 /// - `escape_value`: the normal escape value.
 /// - `exp`: The exponent in use.
-fn smoothed_escape(z: Complex64, iters: u32, escape_value: f64, exp: f64) -> f64 {
+///
+/// The smoothing term assumes escaping orbits grow like `|z|^exp`, which doesn't hold for
+/// exponents of 1 or less, so those fall back to the raw iteration count. So do orbits that
+/// overflow, where the term isn't finite.
+fn smoothed_escape<T: Float>(z: Complex<T>, iters: u32, escape_value: T, exp: T) -> f64 {
+    if exp <= T::one() {
+        return f64::from(iters);
+    }
     let fract = (z.norm_sqr().ln() / escape_value.ln()).ln() / exp.ln();
-    f64::from(iters) - fract
+    match fract.to_f64() {
+        Some(fract) if fract.is_finite() => f64::from(iters) - fract,
+        _ => f64::from(iters),
+    }
+}
+
+/// Convert a parameter to the float type an orbit is evaluated with.
+///
+/// Values beyond the range of the float type, such as large escape thresholds, saturate to its
+/// largest value.
+fn cast<T: Float>(value: f64) -> T {
+    T::from(value)
+        .filter(|v| v.is_finite())
+        .unwrap_or_else(T::max_value)
+}
+
+/// The largest exponent evaluated by repeated multiplication rather than `powf`.
+const MAX_INTEGER_EXP: f64 = 64.;
+
+/// How far an exponent may drift from a whole number and still be evaluated as one.
+///
+/// Stepping the exponent by fractional increments accumulates rounding error, so an exponent
+/// stepped back to 2 is rarely exactly 2.
+const INTEGER_EXP_TOLERANCE: f64 = 1e-9;
+
+/// Determine if an exponent is within `INTEGER_EXP_TOLERANCE` of a whole number that can be
+/// evaluated by exact repeated multiplication.
+pub fn integer_exp(exp: f64) -> Option<u32> {
+    let rounded = exp.round();
+    if (exp - rounded).abs() < INTEGER_EXP_TOLERANCE && (1. ..=MAX_INTEGER_EXP).contains(&rounded) {
+        Some(rounded as u32)
+    } else {
+        None
+    }
+}
+
+/// Raise `z` to a power, multiplying exactly for integer exponents.
+///
+/// `powf` goes through the polar form, which costs a logarithm and trig functions per call and
+/// loses precision that repeated multiplication keeps.
+fn pow<T: Float>(z: Complex<T>, exp: T, int_exp: Option<u32>) -> Complex<T> {
+    match int_exp {
+        Some(n) => z.powu(n),
+        None => z.powf(exp),
+    }
+}
+
+/// Estimate the distance to the set boundary from an escaped value and its derivative.
+///
+/// This is the exterior distance estimate `|z| ln |z| / |z'|`; see [distance-estimation].
+///
+/// [distance-estimation]: https://iquilezles.org/articles/distancefractals/
+///
+/// Orbits whose derivative vanishes or blows up, such as those starting on a critical point with
+/// an exponent below 1, have no finite estimate. They still escaped, so they fall back to their
+/// smoothed escape, taking the same `iters`, `escape_value`, and `exp` as `smoothed_escape`.
+fn distance_estimate(z: Complex64, dz: Complex64, iters: u32, escape_value: f64, exp: f64) -> f64 {
+    let norm = z.norm();
+    let distance = norm * norm.ln() / dz.norm();
+    if distance.is_finite() {
+        distance
+    } else {
+        smoothed_escape(z, iters, escape_value, exp)
+    }
+}
+
+/// Derive an escape threshold that keeps smoothed escapes continuous for the given exponent.
+///
+/// With higher exponents an orbit can jump far past a fixed threshold in a single iteration,
+/// which makes the smoothing term discontinuous. Scaling the threshold with the exponent keeps
+/// escaping orbits in the range where the smoothing approximation holds.
+fn auto_escape_value(base: f64, exp: f64) -> f64 {
+    base.powf((exp / 2.).max(1.)).min(1e100)
+}
+
+/// The orbit derivative magnitude below which an orbit is assumed to be trapped.
+const TRAPPED_DERIVATIVE: f64 = 1e-9;
+
+/// Accumulate the orbit derivative magnitude `|f'(z)|` and determine if the orbit is trapped.
+///
+/// An orbit whose derivative collapses toward zero is being drawn into an attracting cycle and
+/// will never escape. Orbits passing very close to the critical point also collapse the
+/// derivative, so this heuristic can misclassify exterior points near the boundary as interior.
+///
+/// The derivative vanishes on the critical point itself whatever the orbit's fate, so a point
+/// exactly at the origin, such as the start of every Mandelbrot orbit or the origin pixel of a
+/// Julia set, isn't accumulated.
+fn trapped<T: Float>(dz: &mut T, z: Complex<T>, exp: T) -> bool {
+    if z.re == T::zero() && z.im == T::zero() {
+        return false;
+    }
+    *dz = *dz * exp.abs() * z.norm().powf(exp - T::one());
+    *dz < cast(TRAPPED_DERIVATIVE)
+}
+
+/// Determine if `c` lies within the main cardioid or the period-2 bulb of the quadratic
+/// Mandelbrot set. Points within either region never escape.
+fn in_main_bulbs<T: Float>(c: Complex<T>) -> bool {
+    let quarter: T = cast(0.25);
+    let x = c.re - quarter;
+    let y2 = c.im * c.im;
+    let q = x * x + y2;
+
+    let in_cardioid = q * (q + x) <= quarter * y2;
+    let in_bulb = (c.re + T::one()) * (c.re + T::one()) + y2 <= cast(1. / 16.);
+
+    in_cardioid || in_bulb
+}
+
+/// The distance within which an orbit returning to its reference point is assumed to be cycling.
+const PERIODICITY_EPSILON: f64 = 1e-13;
+
+/// The iterations before the periodicity reference point first moves along the orbit.
+const PERIODICITY_INTERVAL: u32 = 8;
+
+/// Detects orbits that have settled into a cycle, which will never escape.
+///
+/// The orbit is compared against a reference point that moves along the orbit at doubling
+/// intervals, so cycles of any period are caught once the interval grows past the period.
+struct Periodicity<T> {
+    reference: Complex<T>,
+    interval: u32,
+    next: u32,
+}
+
+impl<T: Float> Periodicity<T> {
+    fn new(z: Complex<T>) -> Self {
+        Periodicity {
+            reference: z,
+            interval: PERIODICITY_INTERVAL,
+            next: PERIODICITY_INTERVAL,
+        }
+    }
+
+    /// Determine if the orbit value at iteration `i` has returned to the reference point, moving
+    /// the reference point along the orbit when its interval is up.
+    fn cycled(&mut self, z: Complex<T>, i: u32) -> bool {
+        if (z - self.reference).norm_sqr() < cast(PERIODICITY_EPSILON * PERIODICITY_EPSILON) {
+            return true;
+        }
+        if i == self.next {
+            self.reference = z;
+            self.interval = self.interval.saturating_mul(2);
+            self.next = i.saturating_add(self.interval);
+        }
+        false
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Mandelbrot {
     pub exp: f64,
+
+    /// Skip iterating points within the main cardioid and period-2 bulb.
+    ///
+    /// The interior check is only valid for the quadratic set and is ignored for other exponents.
+    #[serde(default = "default_true")]
+    pub check_interior: bool,
+
+    /// Stop iterating orbits that have settled into a cycle.
+    #[serde(default = "default_true")]
+    pub check_periodicity: bool,
+
+    /// The starting value of `z` for each orbit.
+    ///
+    /// The classic Mandelbrot set starts each orbit on the critical point `z = 0`; other seeds
+    /// produce perturbed variants of the set. Julia sets have no equivalent since their orbits
+    /// always start at the evaluated point.
+    #[serde(default)]
+    pub z0: Complex64,
+
+    /// Overrides the threshold derived by `ComplexFn::escape_value`.
+    #[serde(default)]
+    pub escape_value: Option<f64>,
 }
 
 impl Default for Mandelbrot {
     fn default() -> Self {
-        Mandelbrot { exp: 2. }
+        Mandelbrot {
+            exp: 2.,
+            check_interior: true,
+            check_periodicity: true,
+            z0: Complex64 { re: 0.0, im: 0.0 },
+            escape_value: None,
+        }
     }
 }
 
 impl From<&Julia> for Mandelbrot {
     fn from(j: &Julia) -> Self {
-        Mandelbrot { exp: j.exp }
+        Mandelbrot {
+            exp: j.exp,
+            check_periodicity: j.check_periodicity,
+            ..Mandelbrot::default()
+        }
     }
 }
 
 impl Mandelbrot {
-    /// The threshold at which a point is considered escaped
+    /// The threshold at which a point is considered escaped with a quadratic function.
     const ESCAPE_VALUE: f64 = 1000.;
 
+    /// Create the multibrot set with the given exponent, which is the Mandelbrot set for 2.
+    pub fn with_exp(exp: f64) -> Self {
+        Mandelbrot {
+            exp,
+            ..Mandelbrot::default()
+        }
+    }
+
     pub fn escape(&self, c: Complex64, limit: u32) -> Escape {
-        let mut z = Complex64 { re: 0.0, im: 0.0 };
+        self.orbit(c, limit, false)
+    }
+
+    pub fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.orbit(c, limit, true)
+    }
+
+    /// The interior regions are only known for the classic quadratic set.
+    ///
+    /// Exponents that have drifted from 2 by rounding error are still treated as quadratic, as
+    /// they are by `integer_exp`.
+    fn interior_check_applies(&self) -> bool {
+        self.check_interior && integer_exp(self.exp) == Some(2) && self.z0 == Complex64::default()
+    }
+
+    fn orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> Escape {
+        self.traced_orbit(c, limit, bailout).0
+    }
+
+    /// Estimate the distance to the set boundary.
+    ///
+    /// This is the distance half of `escape_distance`.
+    pub fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.escape_distance(c, limit).map(|(_, distance)| distance)
+    }
+
+    /// Determine the magnitude of the orbit derivative with respect to `c` when `c` escapes.
+    pub fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.derivative_orbit(c, limit).map(|(_, dc, _)| dc.norm())
+    }
+
+    /// Evaluate both the smoothed escape and the estimated distance to the set boundary from a
+    /// single orbit, returning `None` for interior points.
+    pub fn escape_distance(&self, c: Complex64, limit: u32) -> Option<(f64, f64)> {
+        let escape_value = self.escape_value();
+        self.derivative_orbit(c, limit).map(|(z, dc, i)| {
+            (
+                smoothed_escape(z, i, escape_value, self.exp),
+                distance_estimate(z, dc, i, escape_value, self.exp),
+            )
+        })
+    }
+
+    /// Iterate the orbit while tracking its derivative with respect to `c`, returning both and
+    /// the iteration they escaped on once the orbit escapes.
+    fn derivative_orbit(&self, c: Complex64, limit: u32) -> Option<(Complex64, Complex64, u32)> {
+        if self.interior_check_applies() && in_main_bulbs(c) {
+            return None;
+        }
+
+        let mut z = self.z0;
+        let mut dc = Complex64::new(0., 0.);
+        let escape_value = self.escape_value();
+        let int_exp = integer_exp(self.exp);
+        let deriv_exp = integer_exp(self.exp - 1.);
         for i in 0..limit {
-            z = z.powf(self.exp);
+            dc = self.exp * pow(z, self.exp - 1., deriv_exp) * dc + 1.;
+            z = pow(z, self.exp, int_exp);
             z += c;
-            if z.norm_sqr() > Self::ESCAPE_VALUE {
-                return Some(smoothed_escape(z, i, Self::ESCAPE_VALUE, self.exp));
+            if z.norm_sqr() > escape_value {
+                return Some((z, dc, i));
             }
         }
 
-        return None;
+        None
     }
 }
 
@@ -86,6 +405,71 @@ impl ComplexFn for Mandelbrot {
         self.escape(c, limit)
     }
 
+    fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape_bailout(c, limit)
+    }
+
+    fn escape_value(&self) -> f64 {
+        self.escape_value
+            .unwrap_or_else(|| auto_escape_value(Self::ESCAPE_VALUE, self.exp))
+    }
+
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        let int_exp = match integer_exp(self.exp) {
+            Some(int_exp) => int_exp,
+            None => return self.escape(c.to_complex64(), limit),
+        };
+        if self.interior_check_applies() && in_main_bulbs(c.to_complex64()) {
+            return None;
+        }
+
+        let mut z = DoubleDoubleComplex::from(self.z0);
+        let escape_value = self.escape_value();
+        for i in 0..limit {
+            z = z.powu(int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return Some(smoothed_escape(z.to_complex64(), i, escape_value, self.exp));
+            }
+        }
+
+        None
+    }
+
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> (Escape, u32) {
+        if self.interior_check_applies() && in_main_bulbs(c) {
+            return (None, 0);
+        }
+
+        let mut z = Complex::new(cast(self.z0.re), cast(self.z0.im));
+        let mut dz = T::one();
+        let mut periodicity = Periodicity::new(z);
+        let exp = cast(self.exp);
+        let int_exp = integer_exp(self.exp);
+        let escape_value = cast(self.escape_value());
+        for i in 0..limit {
+            if bailout && trapped(&mut dz, z, exp) {
+                return (None, i);
+            }
+            z = pow(z, exp, int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return (Some(smoothed_escape(z, i, escape_value, exp)), i + 1);
+            }
+            if self.check_periodicity && periodicity.cycled(z, i) {
+                return (None, i + 1);
+            }
+        }
+
+        (None, limit)
+    }
+
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.distance(c, limit)
+    }
+
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.velocity(c, limit)
+    }
+
     fn exp(&self) -> f64 {
         self.exp
     }
@@ -95,10 +479,18 @@ impl ComplexFn for Mandelbrot {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Julia {
     pub exp: f64,
     pub c_offset: Complex64,
+
+    /// Stop iterating orbits that have settled into a cycle.
+    #[serde(default = "default_true")]
+    pub check_periodicity: bool,
+
+    /// Overrides the threshold derived by `ComplexFn::escape_value`.
+    #[serde(default)]
+    pub escape_value: Option<f64>,
 }
 
 impl Default for Julia {
@@ -106,34 +498,87 @@ impl Default for Julia {
         Julia {
             exp: 2.,
             c_offset: Complex64 { re: 0.6, im: 0.4 },
+            check_periodicity: true,
+            escape_value: None,
+        }
+    }
+}
+
+impl From<&Mandelbrot> for Julia {
+    fn from(m: &Mandelbrot) -> Self {
+        Julia {
+            exp: m.exp,
+            check_periodicity: m.check_periodicity,
+            ..Julia::default()
         }
     }
 }
 
 impl Julia {
-    /// The threshold at which a point is considered escaped
+    /// The threshold at which a point is considered escaped with a quadratic function.
     const ESCAPE_VALUE: f64 = 1000.;
 
+    /// Create the Julia set of the multibrot with the given exponent at `c_offset`.
+    pub fn with_exp(exp: f64, c_offset: Complex64) -> Self {
+        Julia {
+            exp,
+            c_offset,
+            ..Julia::default()
+        }
+    }
+
     /// Create a Julia set with a given mandelbrot algorithm and
     /// re/im coordinates.
     pub fn from_c(m: &Mandelbrot, c_offset: Complex64) -> Self {
         Julia {
-            exp: m.exp,
-            c_offset: c_offset,
+            c_offset,
+            ..Julia::from(m)
         }
     }
 
     fn escape(&self, c: Complex64, limit: u32) -> Escape {
-        let mut z = c.clone();
+        self.orbit(c, limit, false)
+    }
+
+    fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.orbit(c, limit, true)
+    }
+
+    fn orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> Escape {
+        self.traced_orbit(c, limit, bailout).0
+    }
+
+    /// Estimate the distance to the set boundary.
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        let escape_value = self.escape_value();
+        self.derivative_orbit(c, limit)
+            .map(|(z, dz, i)| distance_estimate(z, dz, i, escape_value, self.exp))
+    }
+
+    /// Determine the magnitude of the orbit derivative with respect to the starting point when
+    /// the orbit escapes.
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.derivative_orbit(c, limit).map(|(_, dz, _)| dz.norm())
+    }
+
+    /// Iterate the orbit while tracking its derivative with respect to the starting point,
+    /// returning both and the iteration they escaped on once the orbit escapes.
+    fn derivative_orbit(&self, c: Complex64, limit: u32) -> Option<(Complex64, Complex64, u32)> {
+        let mut z = c;
+        let mut dz = Complex64::new(1., 0.);
+        let escape_value = self.escape_value();
+        let int_exp = integer_exp(self.exp);
+        let deriv_exp = integer_exp(self.exp - 1.);
         for i in 0..limit {
-            z = z.powf(self.exp);
+            dz = self.exp * pow(z, self.exp - 1., deriv_exp) * dz;
+            z = pow(z, self.exp, int_exp);
             z += self.c_offset;
-            if z.norm_sqr() > Self::ESCAPE_VALUE {
-                return Some(smoothed_escape(z, i, Self::ESCAPE_VALUE, self.exp));
+            if z.norm_sqr() > escape_value {
+                return Some((z, dz, i));
             }
         }
 
-        return None;
+        None
     }
 }
 
@@ -142,6 +587,403 @@ impl ComplexFn for Julia {
         self.escape(c, limit)
     }
 
+    fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape_bailout(c, limit)
+    }
+
+    fn escape_value(&self) -> f64 {
+        self.escape_value
+            .unwrap_or_else(|| auto_escape_value(Self::ESCAPE_VALUE, self.exp))
+    }
+
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        let int_exp = match integer_exp(self.exp) {
+            Some(int_exp) => int_exp,
+            None => return self.escape(c.to_complex64(), limit),
+        };
+
+        let mut z = c;
+        let c_offset = DoubleDoubleComplex::from(self.c_offset);
+        let escape_value = self.escape_value();
+        for i in 0..limit {
+            z = z.powu(int_exp) + c_offset;
+            if z.norm_sqr() > escape_value {
+                return Some(smoothed_escape(z.to_complex64(), i, escape_value, self.exp));
+            }
+        }
+
+        None
+    }
+
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> (Escape, u32) {
+        let mut z = c;
+        let mut dz = T::one();
+        let mut periodicity = Periodicity::new(z);
+        let exp = cast(self.exp);
+        let c_offset = Complex::new(cast(self.c_offset.re), cast(self.c_offset.im));
+        let int_exp = integer_exp(self.exp);
+        let escape_value = cast(self.escape_value());
+        for i in 0..limit {
+            if bailout && trapped(&mut dz, z, exp) {
+                return (None, i);
+            }
+            z = pow(z, exp, int_exp) + c_offset;
+            if z.norm_sqr() > escape_value {
+                return (Some(smoothed_escape(z, i, escape_value, exp)), i + 1);
+            }
+            if self.check_periodicity && periodicity.cycled(z, i) {
+                return (None, i + 1);
+            }
+        }
+
+        (None, limit)
+    }
+
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.distance(c, limit)
+    }
+
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.velocity(c, limit)
+    }
+
+    fn exp(&self) -> f64 {
+        self.exp
+    }
+
+    fn exp_mut(&mut self) -> &mut f64 {
+        &mut self.exp
+    }
+}
+
+/// The Burning Ship fractal, which folds each orbit value into the first quadrant before raising
+/// it to the exponent.
+///
+/// Folding with the absolute values of the components breaks the symmetry of the Mandelbrot set,
+/// producing the flame-like ship with its hull along the negative real axis.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct BurningShip {
+    pub exp: f64,
+
+    /// Overrides the threshold derived by `ComplexFn::escape_value`.
+    #[serde(default)]
+    pub escape_value: Option<f64>,
+}
+
+impl Default for BurningShip {
+    fn default() -> Self {
+        BurningShip {
+            exp: 2.,
+            escape_value: None,
+        }
+    }
+}
+
+/// Fold a complex value into the first quadrant.
+fn fold<T: Float>(z: Complex<T>) -> Complex<T> {
+    Complex::new(z.re.abs(), z.im.abs())
+}
+
+impl BurningShip {
+    /// The threshold at which a point is considered escaped with a quadratic function.
+    const ESCAPE_VALUE: f64 = 1000.;
+
+    pub fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.traced_orbit(c, limit, false).0
+    }
+
+    /// Iterate the orbit while tracking its derivative with respect to `c`, returning both and
+    /// the iteration they escaped on once the orbit escapes.
+    ///
+    /// The fold isn't complex differentiable, so its derivative is approximated by reflecting the
+    /// derivative along with each folded component. This is accurate enough for coloring.
+    fn derivative_orbit(&self, c: Complex64, limit: u32) -> Option<(Complex64, Complex64, u32)> {
+        let mut z = Complex64::new(0., 0.);
+        let mut dc = Complex64::new(0., 0.);
+        let escape_value = self.escape_value();
+        let int_exp = integer_exp(self.exp);
+        let deriv_exp = integer_exp(self.exp - 1.);
+        for i in 0..limit {
+            let folded = fold(z);
+            let reflected = Complex64::new(dc.re * z.re.signum(), dc.im * z.im.signum());
+            dc = self.exp * pow(folded, self.exp - 1., deriv_exp) * reflected + 1.;
+            z = pow(folded, self.exp, int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return Some((z, dc, i));
+            }
+        }
+
+        None
+    }
+}
+
+impl ComplexFn for BurningShip {
+    fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape(c, limit)
+    }
+
+    fn escape_value(&self) -> f64 {
+        self.escape_value
+            .unwrap_or_else(|| auto_escape_value(Self::ESCAPE_VALUE, self.exp))
+    }
+
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        let int_exp = match integer_exp(self.exp) {
+            Some(int_exp) => int_exp,
+            None => return self.escape(c.to_complex64(), limit),
+        };
+
+        let mut z = DoubleDoubleComplex::default();
+        let escape_value = self.escape_value();
+        for i in 0..limit {
+            z = DoubleDoubleComplex::new(z.re.abs(), z.im.abs()).powu(int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return Some(smoothed_escape(z.to_complex64(), i, escape_value, self.exp));
+            }
+        }
+
+        None
+    }
+
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, _bailout: bool) -> (Escape, u32) {
+        let mut z = Complex::new(T::zero(), T::zero());
+        let exp = cast(self.exp);
+        let int_exp = integer_exp(self.exp);
+        let escape_value = cast(self.escape_value());
+        for i in 0..limit {
+            z = pow(fold(z), exp, int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return (Some(smoothed_escape(z, i, escape_value, exp)), i + 1);
+            }
+        }
+
+        (None, limit)
+    }
+
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        let escape_value = self.escape_value();
+        self.derivative_orbit(c, limit)
+            .map(|(z, dc, i)| distance_estimate(z, dc, i, escape_value, self.exp))
+    }
+
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.derivative_orbit(c, limit).map(|(_, dc, _)| dc.norm())
+    }
+
+    fn exp(&self) -> f64 {
+        self.exp
+    }
+
+    fn exp_mut(&mut self) -> &mut f64 {
+        &mut self.exp
+    }
+}
+
+/// The Tricorn, or Mandelbar set, which conjugates each orbit value before raising it to the
+/// exponent.
+///
+/// Conjugation turns the two-fold symmetry of the quadratic Mandelbrot set into the three-fold
+/// symmetry of the tricorn.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Tricorn {
+    pub exp: f64,
+
+    /// Overrides the threshold derived by `ComplexFn::escape_value`.
+    #[serde(default)]
+    pub escape_value: Option<f64>,
+}
+
+impl Default for Tricorn {
+    fn default() -> Self {
+        Tricorn {
+            exp: 2.,
+            escape_value: None,
+        }
+    }
+}
+
+impl Tricorn {
+    /// The threshold at which a point is considered escaped with a quadratic function.
+    const ESCAPE_VALUE: f64 = 1000.;
+
+    pub fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.traced_orbit(c, limit, false).0
+    }
+
+    /// Iterate the orbit while tracking its derivative with respect to `c`, returning both and
+    /// the iteration they escaped on once the orbit escapes.
+    ///
+    /// Conjugation isn't complex differentiable, so the derivative is conjugated along with the
+    /// orbit value. Its magnitude, which is all coloring uses, is unaffected.
+    fn derivative_orbit(&self, c: Complex64, limit: u32) -> Option<(Complex64, Complex64, u32)> {
+        let mut z = Complex64::new(0., 0.);
+        let mut dc = Complex64::new(0., 0.);
+        let escape_value = self.escape_value();
+        let int_exp = integer_exp(self.exp);
+        let deriv_exp = integer_exp(self.exp - 1.);
+        for i in 0..limit {
+            let conj = z.conj();
+            dc = self.exp * pow(conj, self.exp - 1., deriv_exp) * dc.conj() + 1.;
+            z = pow(conj, self.exp, int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return Some((z, dc, i));
+            }
+        }
+
+        None
+    }
+}
+
+impl ComplexFn for Tricorn {
+    fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape(c, limit)
+    }
+
+    fn escape_value(&self) -> f64 {
+        self.escape_value
+            .unwrap_or_else(|| auto_escape_value(Self::ESCAPE_VALUE, self.exp))
+    }
+
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        let int_exp = match integer_exp(self.exp) {
+            Some(int_exp) => int_exp,
+            None => return self.escape(c.to_complex64(), limit),
+        };
+
+        let mut z = DoubleDoubleComplex::default();
+        let escape_value = self.escape_value();
+        for i in 0..limit {
+            z = z.conj().powu(int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return Some(smoothed_escape(z.to_complex64(), i, escape_value, self.exp));
+            }
+        }
+
+        None
+    }
+
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, _bailout: bool) -> (Escape, u32) {
+        let mut z = Complex::new(T::zero(), T::zero());
+        let exp = cast(self.exp);
+        let int_exp = integer_exp(self.exp);
+        let escape_value = cast(self.escape_value());
+        for i in 0..limit {
+            z = pow(z.conj(), exp, int_exp) + c;
+            if z.norm_sqr() > escape_value {
+                return (Some(smoothed_escape(z, i, escape_value, exp)), i + 1);
+            }
+        }
+
+        (None, limit)
+    }
+
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        let escape_value = self.escape_value();
+        self.derivative_orbit(c, limit)
+            .map(|(z, dc, i)| distance_estimate(z, dc, i, escape_value, self.exp))
+    }
+
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.derivative_orbit(c, limit).map(|(_, dc, _)| dc.norm())
+    }
+
+    fn exp(&self) -> f64 {
+        self.exp
+    }
+
+    fn exp_mut(&mut self) -> &mut f64 {
+        &mut self.exp
+    }
+}
+
+/// The Newton fractal of `z^exp - 1`, which colors each starting point by the root of unity that
+/// Newton's method converges to from it.
+///
+/// Escapes encode both the root and the convergence speed. Each root gets its own band of
+/// `Newton::BASIN_PERIOD / exp` escape values, offset within the band by the number of iterations
+/// taken, so the basins land on distinct colors of a palette and are shaded within them. Points
+/// that don't converge, such as the origin, are interior.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Newton {
+    pub exp: f64,
+}
+
+impl Default for Newton {
+    fn default() -> Self {
+        Newton { exp: 3. }
+    }
+}
+
+impl Newton {
+    /// The squared step size below which an orbit is considered converged.
+    const CONVERGENCE: f64 = 1e-12;
+
+    /// The escape values the root bands are spread across, matching the default color period.
+    pub const BASIN_PERIOD: f64 = 64.;
+
+    pub fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.traced_orbit(c, limit, false).0
+    }
+
+    /// The index of the root of unity nearest to `z`, counting counterclockwise from 1.
+    pub fn root_index<T: Float>(&self, z: Complex<T>) -> u32 {
+        let roots = self.exp.round().max(1.);
+        let turn = z.arg().to_f64().unwrap_or(0.) / std::f64::consts::TAU;
+        (turn * roots).round().rem_euclid(roots) as u32
+    }
+}
+
+impl ComplexFn for Newton {
+    fn escape(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape(c, limit)
+    }
+
+    /// Newton orbits converge rather than escape, so this is the squared step size below which an
+    /// orbit is considered converged.
+    fn escape_value(&self) -> f64 {
+        Self::CONVERGENCE
+    }
+
+    /// Newton orbits converge quadratically to a root, so deep zooms gain nothing from
+    /// double-double orbits and this iterates in `f64`.
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        self.escape(c.to_complex64(), limit)
+    }
+
+    /// Iterate Newton's method from `c`, returning its escape and the number of iterations
+    /// evaluated.
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, _bailout: bool) -> (Escape, u32) {
+        let mut z = c;
+        let exp: T = cast(self.exp);
+        let deriv_exp = integer_exp(self.exp - 1.);
+        let convergence: T = cast(Self::CONVERGENCE);
+        let band = Self::BASIN_PERIOD / self.exp.round().max(1.);
+        for i in 0..limit {
+            let deriv = pow(z, exp - T::one(), deriv_exp);
+            let step = (deriv * z - T::one()) / (deriv * exp);
+            if !(step.re.is_finite() && step.im.is_finite()) {
+                return (None, i + 1);
+            }
+            z = z - step;
+            if step.norm_sqr() < convergence {
+                let shade = f64::from(i).min(band / 2.);
+                return (Some(f64::from(self.root_index(z)) * band + shade), i + 1);
+            }
+        }
+
+        (None, limit)
+    }
+
+    /// Newton basins have no boundary distance estimate.
+    fn distance(&self, _c: Complex64, _limit: u32) -> Option<f64> {
+        None
+    }
+
+    /// Newton orbits converge rather than escape, so there's no escape velocity.
+    fn velocity(&self, _c: Complex64, _limit: u32) -> Option<f64> {
+        None
+    }
+
     fn exp(&self) -> f64 {
         self.exp
     }
@@ -153,19 +995,119 @@ impl ComplexFn for Julia {
 
 /// A polynomial complex-valued function.
 ///
-/// At present this represents either the Mandelbrot set or a Julia set, and provides a common
-/// interface to generating and manipulating the functions generating these sets.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// At present this represents the Mandelbrot set, a Julia set, the Burning Ship, the tricorn, or a
+/// Newton fractal, and provides a common interface to generating and manipulating the functions
+/// generating these sets.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum PolyComplexFn {
     Julia(Julia),
     Mandelbrot(Mandelbrot),
+    BurningShip(BurningShip),
+    Tricorn(Tricorn),
+    Newton(Newton),
 }
 
 impl PolyComplexFn {
+    /// Create the multibrot set with the given exponent.
+    pub fn multibrot(exp: f64) -> Self {
+        PolyComplexFn::Mandelbrot(Mandelbrot::with_exp(exp))
+    }
+
     pub fn escape(&self, c: Complex64, limit: u32) -> Escape {
         match self {
             PolyComplexFn::Julia(j) => j.escape(c, limit),
             PolyComplexFn::Mandelbrot(m) => m.escape(c, limit),
+            PolyComplexFn::BurningShip(b) => b.escape(c, limit),
+            PolyComplexFn::Tricorn(t) => t.escape(c, limit),
+            PolyComplexFn::Newton(n) => n.escape(c, limit),
+        }
+    }
+
+    pub fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        match self {
+            PolyComplexFn::Julia(j) => j.escape_bailout(c, limit),
+            PolyComplexFn::Mandelbrot(m) => m.escape_bailout(c, limit),
+            PolyComplexFn::BurningShip(b) => b.escape(c, limit),
+            PolyComplexFn::Tricorn(t) => t.escape(c, limit),
+            PolyComplexFn::Newton(n) => n.escape(c, limit),
+        }
+    }
+
+    pub fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        match self {
+            PolyComplexFn::Julia(j) => j.distance(c, limit),
+            PolyComplexFn::Mandelbrot(m) => m.distance(c, limit),
+            PolyComplexFn::BurningShip(b) => b.distance(c, limit),
+            PolyComplexFn::Tricorn(t) => t.distance(c, limit),
+            PolyComplexFn::Newton(n) => n.distance(c, limit),
+        }
+    }
+
+    pub fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        match self {
+            PolyComplexFn::Julia(j) => j.velocity(c, limit),
+            PolyComplexFn::Mandelbrot(m) => m.velocity(c, limit),
+            PolyComplexFn::BurningShip(b) => b.velocity(c, limit),
+            PolyComplexFn::Tricorn(t) => t.velocity(c, limit),
+            PolyComplexFn::Newton(n) => n.velocity(c, limit),
+        }
+    }
+}
+
+/// A kind of function, for selecting a function by name.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FunctionKind {
+    Mandelbrot,
+    Julia,
+    BurningShip,
+    Tricorn,
+    Newton,
+}
+
+impl FunctionKind {
+    /// Create the default function of this kind with the given exponent.
+    pub fn complexfn(self, exp: f64) -> PolyComplexFn {
+        match self {
+            FunctionKind::Mandelbrot => PolyComplexFn::multibrot(exp),
+            FunctionKind::Julia => PolyComplexFn::Julia(Julia {
+                exp,
+                ..Julia::default()
+            }),
+            FunctionKind::BurningShip => PolyComplexFn::BurningShip(BurningShip {
+                exp,
+                ..BurningShip::default()
+            }),
+            FunctionKind::Tricorn => PolyComplexFn::Tricorn(Tricorn {
+                exp,
+                ..Tricorn::default()
+            }),
+            FunctionKind::Newton => PolyComplexFn::Newton(Newton { exp }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionKindParseError(String);
+
+impl std::fmt::Display for FunctionKindParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to function", self.0)
+    }
+}
+
+impl std::error::Error for FunctionKindParseError {}
+
+impl std::str::FromStr for FunctionKind {
+    type Err = FunctionKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mandelbrot" => Ok(FunctionKind::Mandelbrot),
+            "julia" => Ok(FunctionKind::Julia),
+            "burning-ship" => Ok(FunctionKind::BurningShip),
+            "tricorn" => Ok(FunctionKind::Tricorn),
+            "newton" => Ok(FunctionKind::Newton),
+            _ => Err(FunctionKindParseError(s.to_string())),
         }
     }
 }
@@ -181,10 +1123,55 @@ impl ComplexFn for PolyComplexFn {
         self.escape(c, limit)
     }
 
+    fn escape_bailout(&self, c: Complex64, limit: u32) -> Escape {
+        self.escape_bailout(c, limit)
+    }
+
+    fn escape_value(&self) -> f64 {
+        match self {
+            PolyComplexFn::Julia(j) => j.escape_value(),
+            PolyComplexFn::Mandelbrot(m) => m.escape_value(),
+            PolyComplexFn::BurningShip(b) => b.escape_value(),
+            PolyComplexFn::Tricorn(t) => t.escape_value(),
+            PolyComplexFn::Newton(n) => n.escape_value(),
+        }
+    }
+
+    fn escape_double_double(&self, c: DoubleDoubleComplex, limit: u32) -> Escape {
+        match self {
+            PolyComplexFn::Julia(j) => j.escape_double_double(c, limit),
+            PolyComplexFn::Mandelbrot(m) => m.escape_double_double(c, limit),
+            PolyComplexFn::BurningShip(b) => b.escape_double_double(c, limit),
+            PolyComplexFn::Tricorn(t) => t.escape_double_double(c, limit),
+            PolyComplexFn::Newton(n) => n.escape_double_double(c, limit),
+        }
+    }
+
+    fn traced_orbit<T: Float>(&self, c: Complex<T>, limit: u32, bailout: bool) -> (Escape, u32) {
+        match self {
+            PolyComplexFn::Julia(j) => j.traced_orbit(c, limit, bailout),
+            PolyComplexFn::Mandelbrot(m) => m.traced_orbit(c, limit, bailout),
+            PolyComplexFn::BurningShip(b) => b.traced_orbit(c, limit, bailout),
+            PolyComplexFn::Tricorn(t) => t.traced_orbit(c, limit, bailout),
+            PolyComplexFn::Newton(n) => n.traced_orbit(c, limit, bailout),
+        }
+    }
+
+    fn distance(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.distance(c, limit)
+    }
+
+    fn velocity(&self, c: Complex64, limit: u32) -> Option<f64> {
+        self.velocity(c, limit)
+    }
+
     fn exp(&self) -> f64 {
         match self {
             PolyComplexFn::Mandelbrot(ref m) => m.exp,
             PolyComplexFn::Julia(ref j) => j.exp,
+            PolyComplexFn::BurningShip(ref b) => b.exp,
+            PolyComplexFn::Tricorn(ref t) => t.exp,
+            PolyComplexFn::Newton(ref n) => n.exp,
         }
     }
 
@@ -192,6 +1179,578 @@ impl ComplexFn for PolyComplexFn {
         match self {
             PolyComplexFn::Mandelbrot(ref mut m) => &mut m.exp,
             PolyComplexFn::Julia(ref mut j) => &mut j.exp,
+            PolyComplexFn::BurningShip(ref mut b) => &mut b.exp,
+            PolyComplexFn::Tricorn(ref mut t) => &mut t.exp,
+            PolyComplexFn::Newton(ref mut n) => &mut n.exp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every function kind with an orbit, set to the given exponent.
+    #[test]
+    fn complex_fns_are_usable_as_trait_objects() {
+        let fns: Vec<Box<dyn ComplexFn>> = vec![
+            Box::new(Mandelbrot::default()),
+            Box::new(Julia::default()),
+            Box::new(BurningShip::default()),
+            Box::new(Tricorn::default()),
+            Box::new(Newton::default()),
+            Box::new(PolyComplexFn::default()),
+        ];
+        let c = Complex64::new(2., 2.);
+        for f in &fns {
+            assert!(f.escape(c, 50).is_some());
+        }
+    }
+
+    fn with_exp(exp: f64) -> Vec<PolyComplexFn> {
+        let mut fns = vec![
+            PolyComplexFn::Mandelbrot(Mandelbrot::default()),
+            PolyComplexFn::Julia(Julia::default()),
+            PolyComplexFn::BurningShip(BurningShip::default()),
+            PolyComplexFn::Tricorn(Tricorn::default()),
+        ];
+        for f in fns.iter_mut() {
+            *f.exp_mut() = exp;
+        }
+        fns
+    }
+
+    /// A grid of points covering the region the default locations render.
+    fn grid() -> impl Iterator<Item = Complex64> {
+        (0..=20).flat_map(|re| {
+            (0..=20)
+                .map(move |im| Complex64::new(-2. + 0.2 * f64::from(re), -2. + 0.2 * f64::from(im)))
+        })
+    }
+
+    #[test]
+    fn non_positive_exponents_produce_no_nan_escapes() {
+        for exp in [0., -2.] {
+            for f in with_exp(exp) {
+                for c in grid() {
+                    let finite = |value: Option<f64>| value.is_none_or(f64::is_finite);
+                    assert!(finite(f.escape(c, 50)), "escape of {} at exp {}", c, exp);
+                    assert!(
+                        finite(f.distance(c, 50)),
+                        "distance of {} at exp {}",
+                        c,
+                        exp
+                    );
+                    assert!(
+                        f.velocity(c, 50).is_none_or(|v| !v.is_nan()),
+                        "velocity of {} at exp {}",
+                        c,
+                        exp
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distance_estimates_shrink_toward_the_boundary() {
+        let mandelbrot = Mandelbrot::default();
+
+        // Just outside the cusp of the main cardioid at 0.25, and well outside the set.
+        let (near_escape, near) = mandelbrot
+            .escape_distance(Complex64::new(0.2501, 0.), 10_000)
+            .unwrap();
+        let (far_escape, far) = mandelbrot
+            .escape_distance(Complex64::new(1., 1.), 10_000)
+            .unwrap();
+        assert!(near < 1e-3, "distance near the boundary {}", near);
+        assert!(far > 0.1, "distance far from the boundary {}", far);
+        assert!(near_escape > far_escape);
+
+        // The center of the main cardioid never escapes, with or without the interior check.
+        let unchecked = Mandelbrot {
+            check_interior: false,
+            ..Mandelbrot::default()
+        };
+        for mandelbrot in [mandelbrot, unchecked] {
+            assert_eq!(
+                mandelbrot.escape_distance(Complex64::new(-0.1, 0.), 10_000),
+                None
+            );
+            assert_eq!(mandelbrot.distance(Complex64::new(-0.1, 0.), 10_000), None);
+        }
+    }
+
+    #[test]
+    fn escaping_points_without_a_distance_estimate_stay_escaped() {
+        // The derivative of z^0.5 at the critical point z0 = 0 isn't finite, so neither is the
+        // distance estimate, but c = 100 still escapes on the first iteration.
+        let mandelbrot = Mandelbrot::with_exp(0.5);
+        let c = Complex64::new(100., 0.);
+        let escape = mandelbrot.escape(c, 50).unwrap();
+
+        assert_eq!(mandelbrot.distance(c, 50), Some(escape));
+        assert_eq!(mandelbrot.escape_distance(c, 50), Some((escape, escape)));
+    }
+
+    /// The quadratic Mandelbrot set without the interior and periodicity checks, so interior
+    /// orbits only stop early through the trapped orbit bailout.
+    fn unchecked() -> Mandelbrot {
+        Mandelbrot {
+            check_interior: false,
+            check_periodicity: false,
+            ..Mandelbrot::default()
+        }
+    }
+
+    #[test]
+    fn early_bailout_stops_interior_orbits_sooner() {
+        let mandelbrot = unchecked();
+        for (re, im) in [(-0.1, 0.), (-1.1, 0.), (0.1, 0.2), (-0.2, 0.5)] {
+            let c = Complex64::new(re, im);
+            assert_eq!(mandelbrot.iterations(c, 10_000, false), 10_000);
+            assert!(mandelbrot.iterations(c, 10_000, true) < 1_000, "{}", c);
+            assert_eq!(mandelbrot.escape_bailout(c, 10_000), None);
+        }
+    }
+
+    #[test]
+    fn early_bailout_matches_exact_escapes_near_the_boundary() {
+        let mandelbrot = unchecked();
+        let points: Vec<Complex64> = (0..40)
+            .flat_map(|re| {
+                (0..40).map(move |im| {
+                    Complex64::new(-0.76 + 0.001 * f64::from(re), 0.05 + 0.0025 * f64::from(im))
+                })
+            })
+            .collect();
+
+        let mut misclassified = 0;
+        for c in &points {
+            match (
+                mandelbrot.escape(*c, 1_000),
+                mandelbrot.escape_bailout(*c, 1_000),
+            ) {
+                (exact, bailout) if exact == bailout => {}
+                (Some(_), None) => misclassified += 1,
+                (exact, bailout) => panic!("{:?} became {:?} at {}", exact, bailout, c),
+            }
+        }
+        assert!(
+            misclassified * 100 <= points.len(),
+            "{} misclassified",
+            misclassified
+        );
+    }
+
+    #[test]
+    fn the_interior_check_matches_brute_force_iteration() {
+        let checked = Mandelbrot {
+            check_periodicity: false,
+            ..Mandelbrot::default()
+        };
+        let brute_force = unchecked();
+
+        for c in grid() {
+            assert_eq!(checked.escape(c, 500), brute_force.escape(c, 500), "{}", c);
+        }
+        for (re, im) in [(-0.1, 0.), (-1.1, 0.), (0.2, 0.3), (-0.5, -0.5)] {
+            assert_eq!(checked.escape(Complex64::new(re, im), 500), None);
+        }
+    }
+
+    #[test]
+    fn the_interior_check_only_applies_to_the_quadratic_set() {
+        // Inside the period 2 bulb of the quadratic set, but outside the cubic set.
+        let c = Complex64::new(-1.1, 0.);
+        assert_eq!(Mandelbrot::default().escape(c, 500), None);
+        assert!(Mandelbrot::with_exp(3.).escape(c, 500).is_some());
+    }
+
+    #[test]
+    fn a_nonzero_z0_perturbs_escapes() {
+        let perturbed = Mandelbrot {
+            z0: Complex64::new(0.3, -0.2),
+            ..Mandelbrot::default()
+        };
+        let c = Complex64::new(-0.75, 0.2);
+        assert_ne!(
+            perturbed.escape(c, 500),
+            Mandelbrot::default().escape(c, 500)
+        );
+    }
+
+    #[test]
+    fn a_zero_z0_reproduces_the_classic_set() {
+        let spec: Mandelbrot = serde_json::from_str(r#"{"exp": 2.0}"#).unwrap();
+        assert_eq!(spec.z0, Complex64::new(0., 0.));
+
+        for c in grid() {
+            // Iterate the classic orbit by hand, counting iterations to escape.
+            let mut z = Complex64::new(0., 0.);
+            let iters = (0..500).find(|_| {
+                z = z * z + c;
+                z.norm_sqr() > Mandelbrot::ESCAPE_VALUE
+            });
+            let escape = spec.escape(c, 500);
+            assert_eq!(escape.map(|e| e.ceil() as u32), iters, "{}", c);
+        }
+    }
+
+    #[test]
+    fn julia_orbits_from_the_origin_are_finite_at_fractional_exponents() {
+        let origin = Complex64::new(0., 0.);
+        for c_offset in [Julia::default().c_offset, Complex64::new(-0.4, 0.6)] {
+            let julia = PolyComplexFn::Julia(Julia {
+                exp: 1.5,
+                c_offset,
+                ..Julia::default()
+            });
+            for c in grid().chain(std::iter::once(origin)) {
+                let finite = |value: Option<f64>| value.is_none_or(f64::is_finite);
+                assert!(finite(julia.escape(c, 200)), "escape of {}", c);
+                assert!(finite(julia.escape_bailout(c, 200)), "bailout of {}", c);
+                assert!(finite(julia.distance(c, 200)), "distance of {}", c);
+                assert!(
+                    julia.velocity(c, 200).is_none_or(|v| !v.is_nan()),
+                    "velocity of {}",
+                    c
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mandelbrot_julia_round_trips_keep_the_exponent() {
+        for exp in [1.5, 2., 3., 7.25] {
+            let mandelbrot = Mandelbrot {
+                check_periodicity: false,
+                ..Mandelbrot::with_exp(exp)
+            };
+            let julia = Julia::from(&mandelbrot);
+            assert_eq!(julia.exp, exp);
+            assert_eq!(julia.c_offset, Julia::default().c_offset);
+            assert_eq!(Mandelbrot::from(&julia), mandelbrot);
+
+            let c_offset = Complex64::new(-0.4, 0.6);
+            let julia = Julia::from_c(&mandelbrot, c_offset);
+            assert_eq!(julia.c_offset, c_offset);
+            assert_eq!(Mandelbrot::from(&julia), mandelbrot);
         }
     }
+
+    /// The largest jump in smoothed escapes where the escape iteration changes along the real
+    /// axis outside of the set.
+    fn largest_band_jump(mandelbrot: &Mandelbrot) -> f64 {
+        let iterations = |re: f64| mandelbrot.iterations(Complex64::new(re, 0.), 200, false);
+        let escape = |re: f64| mandelbrot.escape(Complex64::new(re, 0.), 200).unwrap();
+
+        let mut largest: f64 = 0.;
+        for step in 0..400 {
+            let mut lo = 0.9 + 0.00525 * f64::from(step);
+            let mut hi = lo + 0.00525;
+            if iterations(lo) == iterations(hi) {
+                continue;
+            }
+            // Narrow in on the edge between the two escape iterations.
+            for _ in 0..60 {
+                let mid = (lo + hi) / 2.;
+                if iterations(mid) == iterations(lo) {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            largest = largest.max((escape(lo) - escape(hi)).abs());
+        }
+        largest
+    }
+
+    #[test]
+    fn the_auto_escape_value_smooths_high_exponents() {
+        let auto = Mandelbrot::with_exp(6.);
+        let fixed = Mandelbrot {
+            escape_value: Some(8.),
+            ..Mandelbrot::with_exp(6.)
+        };
+
+        let (auto_jump, fixed_jump) = (largest_band_jump(&auto), largest_band_jump(&fixed));
+        assert!(fixed_jump > 0., "no iteration bands were crossed");
+        assert!(
+            auto_jump * 100. < fixed_jump,
+            "auto jump {} and fixed jump {}",
+            auto_jump,
+            fixed_jump
+        );
+    }
+
+    #[test]
+    fn escape_velocities_grow_with_the_iterations_to_escape() {
+        let mandelbrot = Mandelbrot::default();
+
+        // The derivative compounds every iteration, so slowly escaping points near the boundary
+        // have far larger velocities than points that escape within a few iterations.
+        let quick = mandelbrot.velocity(Complex64::new(1., 1.), 10_000).unwrap();
+        let slow = mandelbrot
+            .velocity(Complex64::new(0.2501, 0.), 10_000)
+            .unwrap();
+        assert!(quick > 1. && quick < 1e3, "quick velocity {}", quick);
+        assert!(slow > 1e5 * quick, "slow velocity {}", slow);
+
+        assert_eq!(mandelbrot.velocity(Complex64::new(-0.1, 0.), 10_000), None);
+    }
+
+    /// Assert that escapes iterated in `f32` agree with those iterated in `f64`.
+    fn assert_f32_agrees(
+        escape: impl Fn(Complex<f32>) -> Escape,
+        exact: impl Fn(Complex64) -> Escape,
+    ) {
+        let points = [
+            (1., 1.),
+            (-2., 1.),
+            (0.5, 0.5),
+            (-1.5, 0.8),
+            (0.3, 0.),
+            (-0.75, 0.2),
+            (0., 0.),
+            (-1.3, 0.),
+        ];
+        for (re, im) in points {
+            match (
+                escape(Complex::new(re as f32, im as f32)),
+                exact(Complex64::new(re, im)),
+            ) {
+                (Some(a), Some(b)) => {
+                    assert!((a - b).abs() < 1e-3, "{} and {} at {}+{}i", a, b, re, im)
+                }
+                (a, b) => assert_eq!(a, b, "at {}+{}i", re, im),
+            }
+        }
+    }
+
+    #[test]
+    fn f32_escapes_agree_with_f64_escapes_at_shallow_zooms() {
+        let mandelbrot = Mandelbrot::default();
+        assert_f32_agrees(
+            |c| mandelbrot.escape_in(c, 500),
+            |c| mandelbrot.escape(c, 500),
+        );
+
+        let julia = Julia::default();
+        assert_f32_agrees(|c| julia.escape_in(c, 500), |c| julia.escape(c, 500));
+    }
+
+    #[test]
+    fn tricorn_interiors_stay_and_exteriors_escape() {
+        let tricorn = Tricorn::default();
+        for (re, im) in [(0., 0.), (0., 0.1), (-0.1, 0.1)] {
+            assert_eq!(tricorn.escape(Complex64::new(re, im), 500), None);
+        }
+
+        // Conjugating c conjugates the whole orbit, and rotating c by a third of a turn rotates
+        // it, so the set is mirrored across the real axis with three-fold symmetry.
+        let third = Complex64::from_polar(&1., &(std::f64::consts::TAU / 3.));
+        for (re, im) in [(1., 1.), (0.5, 1.5), (-1.5, 1.), (2., 0.)] {
+            let c = Complex64::new(re, im);
+            let escape = tricorn.escape(c, 500).expect("exterior points escape");
+            assert_eq!(tricorn.escape(c.conj(), 500), Some(escape));
+            let rotated = tricorn.escape(c * third, 500).unwrap();
+            assert!(
+                (rotated - escape).abs() < 1e-9,
+                "{} and {}",
+                rotated,
+                escape
+            );
+        }
+    }
+
+    #[test]
+    fn whole_exponents_multiply_instead_of_calling_powf() {
+        assert_eq!(integer_exp(2.), Some(2));
+        let stepped: f64 = (0..20).map(|_| 0.1).sum();
+        assert_ne!(stepped, 2.);
+        assert_eq!(integer_exp(stepped), Some(2));
+        assert_eq!(integer_exp(2. + 1e-7), None);
+        assert_eq!(integer_exp(2.5), None);
+
+        // An exponent just far enough from 2 to go through powf.
+        let squared = unchecked();
+        let powf = Mandelbrot {
+            exp: 2. + 1e-7,
+            ..unchecked()
+        };
+        for (re, im) in [(1., 1.), (-2., 1.), (0.5, 0.5), (-1.5, 0.8), (0.3, 0.)] {
+            let c = Complex64::new(re, im);
+            let (a, b) = (
+                squared.escape(c, 200).unwrap(),
+                powf.escape(c, 200).unwrap(),
+            );
+            assert!((a - b).abs() < 1e-4, "{} and {} at {}", a, b, c);
+        }
+    }
+
+    /// The fastest of three evaluations of the escapes over the grid.
+    fn time_grid(mandelbrot: &Mandelbrot, limit: u32) -> std::time::Duration {
+        (0..3)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                for c in grid() {
+                    std::hint::black_box(mandelbrot.escape(c, limit));
+                }
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "depends on wall clock timing; run with --ignored on an idle machine"]
+    fn whole_exponents_escape_faster_than_powf() {
+        let powf = Mandelbrot {
+            exp: 2. + 1e-7,
+            ..unchecked()
+        };
+        let (multiplied, powed) = (time_grid(&unchecked(), 1_000), time_grid(&powf, 1_000));
+        assert!(
+            multiplied < powed,
+            "{:?} multiplying and {:?} with powf",
+            multiplied,
+            powed
+        );
+    }
+
+    /// The quadratic Mandelbrot set with only the interior checks, at an exponent stepped back to 2
+    /// by fractional increments, so slightly off a whole number.
+    fn interior_checked() -> Mandelbrot {
+        Mandelbrot {
+            exp: (0..20).map(|_| 0.1).sum(),
+            check_periodicity: false,
+            ..Mandelbrot::default()
+        }
+    }
+
+    #[test]
+    fn interior_checks_skip_most_iterations_of_full_frames() {
+        let checked = interior_checked();
+        let brute_force = Mandelbrot {
+            exp: checked.exp,
+            ..unchecked()
+        };
+
+        let iterations = |mandelbrot: &Mandelbrot| -> u64 {
+            grid()
+                .map(|c| u64::from(mandelbrot.iterations(c, 2_000, false)))
+                .sum()
+        };
+        let (skipped, iterated) = (iterations(&checked), iterations(&brute_force));
+        assert!(
+            skipped * 3 < iterated,
+            "{} iterations checked and {} unchecked",
+            skipped,
+            iterated
+        );
+    }
+
+    #[test]
+    #[ignore = "depends on wall clock timing; run with --ignored on an idle machine"]
+    fn interior_checks_speed_up_full_frames() {
+        let checked = interior_checked();
+        let brute_force = Mandelbrot {
+            exp: checked.exp,
+            ..unchecked()
+        };
+        let (checked, brute_force) = (time_grid(&checked, 2_000), time_grid(&brute_force, 2_000));
+        assert!(
+            checked < brute_force,
+            "{:?} checked and {:?} unchecked",
+            checked,
+            brute_force
+        );
+    }
+
+    #[test]
+    fn periodicity_checks_stop_cycling_orbits_early() {
+        let cycling = Mandelbrot {
+            check_interior: false,
+            ..Mandelbrot::default()
+        };
+        let brute_force = unchecked();
+        for (re, im) in [(-0.1, 0.), (-1.1, 0.), (-0.12, 0.75)] {
+            let c = Complex64::new(re, im);
+            assert_eq!(cycling.escape(c, 10_000), None);
+            assert!(cycling.iterations(c, 10_000, false) < 1_000, "{}", c);
+            assert_eq!(brute_force.iterations(c, 10_000, false), 10_000);
+        }
+
+        let julia = |check_periodicity| Julia {
+            c_offset: Complex64::new(-1.1, 0.),
+            check_periodicity,
+            ..Julia::default()
+        };
+        let (cycling_julia, brute_force_julia) = (julia(true), julia(false));
+        for (re, im) in [(0., 0.), (0.3, 0.), (0.1, 0.1)] {
+            let c = Complex64::new(re, im);
+            assert_eq!(cycling_julia.escape(c, 10_000), None);
+            assert!(cycling_julia.iterations(c, 10_000, false) < 1_000, "{}", c);
+            assert_eq!(brute_force_julia.iterations(c, 10_000, false), 10_000);
+        }
+
+        for c in grid() {
+            assert_eq!(cycling.escape(c, 500), brute_force.escape(c, 500), "{}", c);
+            assert_eq!(
+                cycling_julia.escape(c, 500),
+                brute_force_julia.escape(c, 500),
+                "{}",
+                c
+            );
+        }
+    }
+
+    #[test]
+    fn multibrot_constructors_store_the_exponent() {
+        let c_offset = Complex64::new(-0.4, 0.6);
+        for exp in [2., 3., 4.5, -2.] {
+            assert_eq!(Mandelbrot::with_exp(exp).exp(), exp);
+
+            let julia = Julia::with_exp(exp, c_offset);
+            assert_eq!(julia.exp(), exp);
+            assert_eq!(julia.c_offset, c_offset);
+
+            let multibrot = PolyComplexFn::multibrot(exp);
+            assert_eq!(multibrot.exp(), exp);
+            assert_eq!(
+                multibrot,
+                PolyComplexFn::Mandelbrot(Mandelbrot::with_exp(exp))
+            );
+
+            let spec = serde_json::to_string(&multibrot).unwrap();
+            let parsed: PolyComplexFn = serde_json::from_str(&spec).unwrap();
+            assert_eq!(parsed, multibrot, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn newton_basins_separate_the_cube_roots_of_unity() {
+        let newton = Newton { exp: 3. };
+        let band = Newton::BASIN_PERIOD / 3.;
+        for k in 0..3 {
+            let root = Complex64::from_polar(&1., &(std::f64::consts::TAU * f64::from(k) / 3.));
+            assert_eq!(newton.root_index(root), k);
+            assert_eq!(newton.escape(root, 100), Some(f64::from(k) * band));
+
+            // Points near a root converge to it, shaded within its band by the iterations taken.
+            for scale in [0.8, 1.2, 1.5] {
+                let escape = newton.escape(root * scale, 100).unwrap();
+                assert!(
+                    escape > f64::from(k) * band && escape < f64::from(k + 1) * band,
+                    "{} from {}",
+                    escape,
+                    root * scale
+                );
+            }
+        }
+
+        // The derivative vanishes at the origin, so Newton's method can't leave it.
+        assert_eq!(newton.escape(Complex64::new(0., 0.), 100), None);
+    }
 }
@@ -0,0 +1,328 @@
+//! Adjustments applied to a colored image after rendering.
+
+use image::RgbImage;
+
+/// The luma of an RGB triple with Rec. 601 weights.
+fn luma(rgb: &[u8; 3]) -> f64 {
+    0.299 * f64::from(rgb[0]) + 0.587 * f64::from(rgb[1]) + 0.114 * f64::from(rgb[2])
+}
+
+fn clamp(v: f64) -> u8 {
+    v.round().clamp(0., 255.) as u8
+}
+
+/// Scale the saturation of an image.
+///
+/// Each channel is pushed away from or pulled toward the pixel luma; a factor of 0 produces a
+/// grayscale image and a factor of 1 leaves the image unchanged.
+pub fn saturate(img: &mut RgbImage, factor: f64) {
+    for pixel in img.pixels_mut() {
+        let y = luma(&pixel.0);
+        for channel in pixel.0.iter_mut() {
+            *channel = clamp(y + (f64::from(*channel) - y) * factor);
+        }
+    }
+}
+
+/// Scale the contrast of an image around the channel midpoint.
+///
+/// A factor of 1 leaves the image unchanged, and a factor of 0 produces a flat gray image.
+pub fn contrast(img: &mut RgbImage, factor: f64) {
+    for pixel in img.pixels_mut() {
+        for channel in pixel.0.iter_mut() {
+            *channel = clamp((f64::from(*channel) - 127.5) * factor + 127.5);
+        }
+    }
+}
+
+/// The color temperature in Kelvin of neutral white light.
+pub const NEUTRAL_TEMPERATURE: f64 = 6500.;
+
+/// Approximate the RGB color of a black body at a temperature in Kelvin.
+///
+/// This uses Tanner Helland's curve fit, which is reasonable from 1000K to 40000K.
+fn blackbody(kelvin: f64) -> [f64; 3] {
+    let t = kelvin.clamp(1000., 40000.) / 100.;
+
+    let red = if t <= 66. {
+        255.
+    } else {
+        329.698_727_446 * (t - 60.).powf(-0.133_204_759_2)
+    };
+    let green = if t <= 66. {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.).powf(-0.075_514_849_2)
+    };
+    let blue = if t >= 66. {
+        255.
+    } else if t <= 19. {
+        0.
+    } else {
+        138.517_731_223_1 * (t - 10.).ln() - 305.044_792_730_7
+    };
+
+    [
+        red.clamp(0., 255.),
+        green.clamp(0., 255.),
+        blue.clamp(0., 255.),
+    ]
+}
+
+/// Tint an image as though it were lit by light of the given color temperature in Kelvin.
+///
+/// Temperatures below `NEUTRAL_TEMPERATURE` warm the image and temperatures above it cool the
+/// image. Each channel is scaled by the ratio of the light color to neutral white, with gains
+/// limited to `[0, 2]`.
+pub fn temperature(img: &mut RgbImage, kelvin: f64) {
+    let light = blackbody(kelvin);
+    let neutral = blackbody(NEUTRAL_TEMPERATURE);
+    let mut gains = [0.; 3];
+    for ((gain, l), n) in gains.iter_mut().zip(light.iter()).zip(neutral.iter()) {
+        *gain = (l / n).clamp(0., 2.);
+    }
+
+    for pixel in img.pixels_mut() {
+        for (channel, gain) in pixel.0.iter_mut().zip(gains.iter()) {
+            *channel = clamp(f64::from(*channel) * gain);
+        }
+    }
+}
+
+/// A symmetry applied to an image by reflecting part of it onto the rest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mirror {
+    /// Reflect the left half onto the right half.
+    Horizontal,
+
+    /// Reflect the top half onto the bottom half.
+    Vertical,
+
+    /// Reflect the top left quadrant onto the other three quadrants.
+    Quad,
+
+    /// Reflect the wedge between the positive real axis and the upper diagonal around the center
+    /// into eight fold symmetry.
+    Kaleidoscope,
+}
+
+/// Make an image symmetric by reflecting part of it onto the rest.
+pub fn mirror(img: &mut RgbImage, mirror: Mirror) {
+    let source = img.clone();
+    let (width, height) = img.dimensions();
+    let (cx, cy) = (i64::from(width - 1) / 2, i64::from(height - 1) / 2);
+
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let (sx, sy) = match mirror {
+            Mirror::Horizontal => (x.min(width - 1 - x), y),
+            Mirror::Vertical => (x, y.min(height - 1 - y)),
+            Mirror::Quad => (x.min(width - 1 - x), y.min(height - 1 - y)),
+            Mirror::Kaleidoscope => {
+                let (mut ax, mut ay) = ((i64::from(x) - cx).abs(), (i64::from(y) - cy).abs());
+                if ay > ax {
+                    std::mem::swap(&mut ax, &mut ay);
+                }
+                (
+                    (cx + ax).clamp(0, i64::from(width - 1)) as u32,
+                    (cy - ay).clamp(0, i64::from(height - 1)) as u32,
+                )
+            }
+        };
+        *pixel = *source.get_pixel(sx, sy);
+    }
+}
+
+#[derive(Debug)]
+pub struct MirrorParseError(String);
+
+impl std::fmt::Display for MirrorParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to mirror", self.0)
+    }
+}
+
+impl std::error::Error for MirrorParseError {}
+
+impl std::str::FromStr for Mirror {
+    type Err = MirrorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(Mirror::Horizontal),
+            "vertical" => Ok(Mirror::Vertical),
+            "quad" => Ok(Mirror::Quad),
+            "kaleidoscope" => Ok(Mirror::Kaleidoscope),
+            _ => Err(MirrorParseError(s.to_string())),
+        }
+    }
+}
+
+/// A pixel rectangle within an image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Crop an image to a pixel rectangle, failing if the rectangle doesn't fit within the image.
+pub fn crop(img: &RgbImage, rect: Crop) -> Result<RgbImage, crate::Error> {
+    let fits =
+        |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+    if rect.width == 0
+        || rect.height == 0
+        || !fits(rect.x, rect.width, img.width())
+        || !fits(rect.y, rect.height, img.height())
+    {
+        return Err(crate::Error::from(format!(
+            "Cannot crop {}x{} at {},{} from a {}x{} image",
+            rect.width,
+            rect.height,
+            rect.x,
+            rect.y,
+            img.width(),
+            img.height()
+        )));
+    }
+
+    Ok(image::imageops::crop_imm(img, rect.x, rect.y, rect.width, rect.height).to_image())
+}
+
+#[derive(Debug)]
+pub struct CropParseError(String);
+
+impl std::fmt::Display for CropParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Cannot parse {} to crop rectangle x,y,w,h", self.0)
+    }
+}
+
+impl std::error::Error for CropParseError {}
+
+impl std::str::FromStr for Crop {
+    type Err = CropParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<u32> = s
+            .split(',')
+            .map(|part| part.trim().parse::<u32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| CropParseError(s.to_string()))?;
+
+        match parts[..] {
+            [x, y, width, height] => Ok(Crop {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err(CropParseError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small image covering a spread of colors.
+    fn sample() -> RgbImage {
+        RgbImage::from_fn(16, 16, |x, y| {
+            image::Rgb([(x * 16) as u8, (y * 16) as u8, (255 - x * y) as u8])
+        })
+    }
+
+    #[test]
+    fn zero_saturation_is_grayscale() {
+        let mut img = sample();
+        saturate(&mut img, 0.);
+
+        assert!(img.pixels().all(|p| p.0[0] == p.0[1] && p.0[1] == p.0[2]));
+    }
+
+    #[test]
+    fn unit_saturation_and_contrast_are_no_ops() {
+        let mut img = sample();
+        saturate(&mut img, 1.);
+        contrast(&mut img, 1.);
+
+        assert_eq!(img, sample());
+    }
+
+    #[test]
+    fn extreme_contrast_clamps_instead_of_overflowing() {
+        let mut img = sample();
+        contrast(&mut img, 10.);
+
+        // Channels more than 13 from the midpoint are pushed past the ends of the range.
+        for (before, after) in sample().pixels().zip(img.pixels()) {
+            for (&b, &a) in before.0.iter().zip(after.0.iter()) {
+                match b {
+                    0..=114 => assert_eq!(a, 0),
+                    141..=255 => assert_eq!(a, 255),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn warm_temperatures_raise_the_red_blue_ratio() {
+        let mut img = RgbImage::from_pixel(4, 4, image::Rgb([128, 128, 128]));
+        temperature(&mut img, 3000.);
+
+        let [red, _, blue] = img.get_pixel(0, 0).0;
+        assert!(red >= 128 && blue < 128, "warmed to {:?}", (red, blue));
+
+        let mut neutral = sample();
+        temperature(&mut neutral, NEUTRAL_TEMPERATURE);
+        assert_eq!(neutral, sample());
+    }
+
+    #[test]
+    fn horizontal_mirroring_reflects_the_left_half_of_odd_widths() {
+        let source = RgbImage::from_fn(15, 8, |x, y| {
+            image::Rgb([(x * 16) as u8, (y * 16) as u8, 0])
+        });
+        let mut img = source.clone();
+        mirror(&mut img, "horizontal".parse().unwrap());
+
+        for y in 0..8 {
+            for x in 0..15 {
+                assert_eq!(img.get_pixel(x, y), img.get_pixel(14 - x, y));
+            }
+            // The left half and the center column are kept.
+            for x in 0..=7 {
+                assert_eq!(img.get_pixel(x, y), source.get_pixel(x, y));
+            }
+        }
+
+        assert_eq!(img.get_pixel(14, 0), source.get_pixel(0, 0));
+        assert_eq!(img.get_pixel(14, 7), source.get_pixel(0, 7));
+    }
+
+    #[test]
+    fn crops_match_the_sub_rectangle_of_the_full_render() {
+        let rctx = crate::rctx::Rctx::default();
+        let bounds = crate::Bounds {
+            width: 48,
+            height: 32,
+        };
+        let full = rctx
+            .bind(bounds)
+            .to_ematrix()
+            .to_img(rctx.palette().as_ref());
+
+        let rect: Crop = "10, 4, 20, 16".parse().unwrap();
+        let cropped = crop(&full, rect).unwrap();
+        assert_eq!(cropped.dimensions(), (20, 16));
+        for (x, y, pixel) in cropped.enumerate_pixels() {
+            assert_eq!(pixel, full.get_pixel(x + 10, y + 4));
+        }
+
+        let overhanging = Crop { x: 40, ..rect };
+        assert!(crop(&full, overhanging).is_err());
+        assert!("10,4,20".parse::<Crop>().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Progress reporting for long running renders.
+//!
+//! Headless renders don't draw a progress bar, so progress is reported on demand: sending
+//! `SIGUSR1` prints the progress and ETA to stderr. Suspending the render with `SIGTSTP` reports
+//! the progress before stopping, and resuming it with `SIGCONT` reports that it continued.
+//!
+//! Renders that don't evaluate cells through `BoundRctx::to_ematrix_with_progress`, such as
+//! streaming, sampled, ordered, or GPU renders, report no cells done until the frame completes.
+
+use signal_hook::consts::{SIGCONT, SIGTSTP, SIGUSR1};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The number of cells evaluated out of the total for a render.
+#[derive(Debug)]
+pub struct Progress {
+    done: AtomicU64,
+    total: u64,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn new(total: u64) -> Self {
+        Self {
+            done: AtomicU64::new(0),
+            total,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record that a cell was evaluated.
+    pub fn inc(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that at least `done` cells have been evaluated.
+    ///
+    /// Updates that arrive out of order never move the progress backward.
+    pub fn update(&self, done: u64) {
+        self.done.fetch_max(done, Ordering::Relaxed);
+    }
+
+    /// Describe the progress so far.
+    pub fn report(&self) -> String {
+        report(
+            self.done.load(Ordering::Relaxed),
+            self.total,
+            self.start.elapsed(),
+        )
+    }
+}
+
+/// Describe the progress of a render from the cells done, the total cells, and the time elapsed.
+///
+/// The ETA extrapolates the rate so far, and is unknown until a cell has been evaluated.
+pub fn report(done: u64, total: u64, elapsed: Duration) -> String {
+    let done = done.min(total);
+    let percent = if total == 0 {
+        100.
+    } else {
+        done as f64 / total as f64 * 100.
+    };
+    let eta = if done == 0 {
+        String::from("unknown")
+    } else {
+        let remaining = elapsed.as_secs_f64() * (total - done) as f64 / done as f64;
+        format!("{:.1}s", remaining)
+    };
+
+    format!(
+        "{}/{} cells ({:.1}%), elapsed {:.1}s, eta {}",
+        done,
+        total,
+        percent,
+        elapsed.as_secs_f64(),
+        eta
+    )
+}
+
+/// Report progress on stderr in response to `SIGUSR1`, `SIGTSTP`, and `SIGCONT`.
+///
+/// Signals are handled on a background thread for the rest of the process lifetime.
+pub fn watch_signals(progress: Arc<Progress>) -> Result<(), crate::Error> {
+    let mut signals = signal_hook::iterator::Signals::new([SIGUSR1, SIGTSTP, SIGCONT])?;
+
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            match signal {
+                SIGUSR1 => eprintln!("progress: {}", progress.report()),
+                SIGTSTP => {
+                    eprintln!("suspended: {}", progress.report());
+                    // Stop the process as the default handler would have.
+                    let _ = signal_hook::low_level::emulate_default_handler(SIGTSTP);
+                }
+                SIGCONT => eprintln!("resumed: {}", progress.report()),
+                _ => {}
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_extrapolate_the_eta_from_the_rate_so_far() {
+        assert_eq!(
+            report(250, 1000, Duration::from_secs(10)),
+            "250/1000 cells (25.0%), elapsed 10.0s, eta 30.0s"
+        );
+        assert_eq!(
+            report(0, 1000, Duration::from_millis(500)),
+            "0/1000 cells (0.0%), elapsed 0.5s, eta unknown"
+        );
+        assert_eq!(
+            report(1200, 1000, Duration::from_secs(4)),
+            "1000/1000 cells (100.0%), elapsed 4.0s, eta 0.0s"
+        );
+        assert_eq!(
+            report(0, 0, Duration::from_secs(0)),
+            "0/0 cells (100.0%), elapsed 0.0s, eta unknown"
+        );
+
+        // Out of order updates never move the progress backward.
+        let progress = Progress::new(10);
+        progress.update(6);
+        progress.update(3);
+        progress.inc();
+        assert!(progress.report().starts_with("7/10 cells (70.0%)"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Commands entered at the interactive `:` prompt.
+//!
+//! Prompt commands give precise control over the rendering context where keystroke transforms
+//! can only nudge it:
+//!
+//! - `goto <re> <im> [scalar]`: move to a location, optionally setting the zoom.
+//! - `iter <n>`: set the escape iteration limit.
+//! - `exp <x>`: set the function exponent, rounded to a whole number when the context constrains
+//!   exponents to integers.
+
+use crate::polycomplex::ComplexFn;
+use crate::rctx::Rctx;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PromptCmd {
+    /// Move the location to the given origin and optionally set the scalar.
+    Goto {
+        re: f64,
+        im: f64,
+        scalar: Option<f64>,
+    },
+
+    /// Set the escape iteration limit.
+    Iter(u32),
+
+    /// Set the function exponent.
+    ///
+    /// Contexts with `integer_exp` set round it to a whole number, as stepping the exponent does.
+    Exp(f64),
+}
+
+impl PromptCmd {
+    /// Apply the command to a rendering context.
+    pub fn apply(&self, rctx: &mut Rctx) {
+        match *self {
+            PromptCmd::Goto { re, im, scalar } => {
+                rctx.loc.re0 = re;
+                rctx.loc.im0 = im;
+                if let Some(scalar) = scalar {
+                    rctx.loc.scalar = scalar;
+                }
+            }
+            PromptCmd::Iter(max_iter) => rctx.loc.max_iter = max_iter,
+            PromptCmd::Exp(exp) => {
+                *rctx.complexfn.exp_mut() = if rctx.integer_exp {
+                    exp.round().max(Rctx::MIN_INTEGER_EXP)
+                } else {
+                    exp
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PromptParseError(String);
+
+impl std::fmt::Display for PromptParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PromptParseError {}
+
+fn parse_arg<T: std::str::FromStr>(arg: &str) -> Result<T, PromptParseError> {
+    arg.parse()
+        .map_err(|_| PromptParseError(format!("Cannot parse argument {}", arg)))
+}
+
+/// Parse a coordinate or exponent, which must be finite.
+fn parse_finite(arg: &str) -> Result<f64, PromptParseError> {
+    let value: f64 = parse_arg(arg)?;
+    if !value.is_finite() {
+        return Err(PromptParseError(format!(
+            "Expected a finite number, got {}",
+            arg
+        )));
+    }
+    Ok(value)
+}
+
+/// Parse a scalar, which must be finite and positive.
+fn parse_scalar(arg: &str) -> Result<f64, PromptParseError> {
+    let scalar = parse_finite(arg)?;
+    if scalar <= 0. {
+        return Err(PromptParseError(format!(
+            "Expected a positive scalar, got {}",
+            arg
+        )));
+    }
+    Ok(scalar)
+}
+
+impl std::str::FromStr for PromptCmd {
+    type Err = PromptParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+
+        match (name, args.len()) {
+            ("goto", 2) | ("goto", 3) => Ok(PromptCmd::Goto {
+                re: parse_finite(args[0])?,
+                im: parse_finite(args[1])?,
+                scalar: args.get(2).map(|arg| parse_scalar(arg)).transpose()?,
+            }),
+            ("goto", n) => Err(PromptParseError(format!(
+                "goto expects 2 or 3 arguments, got {}",
+                n
+            ))),
+            ("iter", 1) => Ok(PromptCmd::Iter(parse_arg(args[0])?)),
+            ("exp", 1) => Ok(PromptCmd::Exp(parse_finite(args[0])?)),
+            ("iter", n) | ("exp", n) => Err(PromptParseError(format!(
+                "{} expects 1 argument, got {}",
+                name, n
+            ))),
+            _ => Err(PromptParseError(format!("Unknown command {}", name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_goto_with_and_without_a_scalar() {
+        assert_eq!(
+            "goto -0.75 0.1".parse::<PromptCmd>().unwrap(),
+            PromptCmd::Goto {
+                re: -0.75,
+                im: 0.1,
+                scalar: None
+            }
+        );
+        assert_eq!(
+            "  goto -0.75   0.1 1e-5 ".parse::<PromptCmd>().unwrap(),
+            PromptCmd::Goto {
+                re: -0.75,
+                im: 0.1,
+                scalar: Some(1e-5)
+            }
+        );
+    }
+
+    #[test]
+    fn parses_iter_and_exp() {
+        assert_eq!(
+            "iter 2000".parse::<PromptCmd>().unwrap(),
+            PromptCmd::Iter(2000)
+        );
+        assert_eq!("exp 3.5".parse::<PromptCmd>().unwrap(), PromptCmd::Exp(3.5));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        for cmd in [
+            "",
+            "warp 1 2",
+            "goto 1",
+            "goto 1 2 3 4",
+            "goto 1 i",
+            "iter",
+            "iter -5",
+            "iter 1 2",
+            "exp two",
+        ] {
+            assert!(cmd.parse::<PromptCmd>().is_err(), "{:?}", cmd);
+        }
+    }
+
+    #[test]
+    fn commands_apply_to_the_rendering_context() {
+        let mut rctx = Rctx::default();
+        for cmd in ["goto 0.25 -0.5 0.001", "iter 321", "exp 3"] {
+            cmd.parse::<PromptCmd>().unwrap().apply(&mut rctx);
+        }
+
+        assert_eq!(
+            (rctx.loc.re0, rctx.loc.im0, rctx.loc.scalar),
+            (0.25, -0.5, 0.001)
+        );
+        assert_eq!(rctx.loc.max_iter, 321);
+        assert_eq!(rctx.complexfn.exp(), 3.);
+    }
+
+    #[test]
+    fn exponents_are_rounded_for_integer_exponent_contexts() {
+        let mut rctx = Rctx {
+            integer_exp: true,
+            ..Rctx::default()
+        };
+        for (cmd, exp) in [
+            ("exp 3.4", 3.),
+            ("exp 2.5", 3.),
+            ("exp -4", Rctx::MIN_INTEGER_EXP),
+        ] {
+            cmd.parse::<PromptCmd>().unwrap().apply(&mut rctx);
+            assert_eq!(rctx.complexfn.exp(), exp, "{}", cmd);
+        }
+
+        let mut fractional = Rctx::default();
+        "exp 3.4"
+            .parse::<PromptCmd>()
+            .unwrap()
+            .apply(&mut fractional);
+        assert_eq!(fractional.complexfn.exp(), 3.4);
+    }
+
+    #[test]
+    fn rejects_non_finite_values_and_non_positive_scalars() {
+        for cmd in [
+            "goto nan 0",
+            "goto 0 inf",
+            "goto -inf 0 0.01",
+            "goto 0 0 0",
+            "goto 0 0 -0.01",
+            "goto 0 0 nan",
+            "goto 0 0 inf",
+            "exp nan",
+            "exp inf",
+            "exp -inf",
+        ] {
+            assert!(cmd.parse::<PromptCmd>().is_err(), "{:?}", cmd);
+        }
+    }
+}
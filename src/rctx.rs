@@ -7,20 +7,38 @@
 //! - Add a related type that binds a rendering context with a specific bounds.
 
 use crate::{
-    ematrix::EMatrix, loc::Loc, Bounds, ComplexFn, Escape, Julia, Mandelbrot, PolyComplexFn, Pos,
+    coloring::ColorMode,
+    doubledouble::{DoubleDouble, DoubleDoubleComplex, Precision},
+    ematrix::EMatrix,
+    loc::{Loc, ScaleMethod},
+    order::PixelOrder,
+    palette::{Colorer, Palette, ScaledPalette},
+    progress::Progress,
+    Bounds, BurningShip, ComplexFn, Escape, Julia, Mandelbrot, Newton, PolyComplexFn, Pos, Tricorn,
 };
+use image::RgbImage;
 use indicatif::ParallelProgressIterator;
 use itertools::Itertools;
 use num::complex::Complex64;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::ops::Index;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// The image bounds that specs are authored for.
+///
+/// This matches the resolution of screenshots and the default `render` resolution.
+pub const SPEC_BOUNDS: Bounds = Bounds {
+    width: 4000,
+    height: 4000,
+};
 
 /// The context for rending a specific point or region within a fractal.
 ///
 /// An `Rctx` gives magnitude, scaling factors, and other properties for the image but does
 /// not bound the precise dimensions.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Rctx {
     /// The current loc.
     pub loc: Loc,
@@ -29,12 +47,58 @@ pub struct Rctx {
     pub complexfn: PolyComplexFn,
 
     /// The colorer for individual escapes.
-    pub colorer: crate::SineRGB,
+    pub colorer: Colorer,
 
     /// Dimensional scaling factors in case the canvas is not square.
     ///
-    /// This compensates for terminal cells having a 2:1 ratio.
+    /// Specs store the compensation for square image pixels. The interactive explorer combines
+    /// it with `TERMINAL_COMP` to compensate for terminal cells having a 2:1 ratio.
     pub comp: (f64, f64),
+
+    /// Bail out early on orbits that appear trapped by an attracting cycle.
+    ///
+    /// This speeds up interior heavy frames but can misclassify points near the set boundary, so
+    /// it's disabled by default.
+    #[serde(default)]
+    pub early_bailout: bool,
+
+    /// The value derived for each point to be colored.
+    #[serde(default)]
+    pub color_mode: ColorMode,
+
+    /// The number of escape iterations per palette cycle.
+    #[serde(default = "default_color_period")]
+    pub color_period: f64,
+
+    /// The factor the scalar is multiplied or divided by for each zoom step.
+    #[serde(default = "default_zoom_factor")]
+    pub zoom_factor: f64,
+
+    /// The phase in radians the palette is rotated by for each phase shift step.
+    #[serde(default = "default_phase_step")]
+    pub phase_step: f64,
+
+    /// Constrain the exponent to whole numbers, stepping it by one instead of `EXP_SCALAR`.
+    ///
+    /// Integer exponents are evaluated by exact repeated multiplication rather than `powf`.
+    #[serde(default)]
+    pub integer_exp: bool,
+
+    /// The precision orbits are iterated with.
+    #[serde(default)]
+    pub precision: Precision,
+}
+
+fn default_zoom_factor() -> f64 {
+    Rctx::SCALE_SCALAR
+}
+
+fn default_phase_step() -> f64 {
+    Rctx::PHASE_SCALAR
+}
+
+fn default_color_period() -> f64 {
+    Rctx::COLOR_PERIOD
 }
 
 impl Rctx {
@@ -43,31 +107,332 @@ impl Rctx {
     const ITERATIONS_SCALAR: u32 = 25;
     const EXP_SCALAR: f64 = 0.001;
 
+    /// The lowest exponent reachable by stepping or setting it in integer exponent mode.
+    pub const MIN_INTEGER_EXP: f64 = 2.;
+    const COLOR_PERIOD_SCALAR: f64 = 1.25;
+    const PHASE_SCALAR: f64 = std::f64::consts::PI / 16.;
+
+    /// The number of cells along the longest side of the grid probed by `suggest_max_iter`.
+    const PROBE_CELLS: u16 = 96;
+
+    /// The lowest iteration limit suggested by `suggest_max_iter`, matching the default loc.
+    const MIN_SUGGESTED_ITER: u32 = 100;
+
+    /// The number of escape iterations per cycle that built in palettes are designed around.
+    pub const COLOR_PERIOD: f64 = 64.;
+
+    /// The compensation for the aspect ratio of terminal cells.
+    pub const TERMINAL_COMP: (f64, f64) = (2.3, 1.);
+
     pub fn bind<'a>(&'a self, bounds: Bounds) -> BoundRctx<'a> {
-        BoundRctx {
-            rctx: &self,
-            bounds,
-        }
+        BoundRctx { rctx: self, bounds }
+    }
+
+    /// Serialize the rctx as a pretty printed JSON spec.
+    pub fn to_spec_string(&self) -> Result<String, crate::Error> {
+        serde_json::to_string_pretty(self).map_err(crate::Error::from)
+    }
+
+    /// Deserialize an rctx from a JSON spec.
+    pub fn from_spec_string(spec: &str) -> Result<Self, crate::Error> {
+        serde_json::from_str(spec).map_err(crate::Error::from)
     }
 
     /// Create a new application context with a pre-defined location.
     pub fn with_loc(loc: Loc) -> Self {
-        let mut rctx = Rctx::default();
-        rctx.loc = loc;
-        rctx
+        Rctx {
+            loc,
+            ..Rctx::default()
+        }
     }
 
     /// Determine the complex value at a given offset of the origin with respect to the provided
     /// bounds.
     pub fn complex_at(&self, bounds: Bounds, pos: Pos) -> Complex64 {
+        self.complex_at_subpixel(bounds, pos, (0., 0.))
+    }
+
+    /// Determine the complex value under the cell at column `x` and row `y` of the bounds.
+    ///
+    /// Cells outside of the bounds have no complex value.
+    pub fn coord_at(&self, bounds: Bounds, x: u16, y: u16) -> Option<Complex64> {
+        if x < bounds.width && y < bounds.height {
+            Some(self.complex_at(bounds, Pos { x, y }))
+        } else {
+            None
+        }
+    }
+
+    /// Determine the width of the complex plane spanned by a run of horizontal cells.
+    pub fn span(&self, cells: u16) -> f64 {
+        f64::from(cells) * self.comp.1 * self.loc.scalar
+    }
+
+    /// Determine the complex value at a fractional offset `(dx, dy)` from a given position.
+    pub fn complex_at_subpixel(&self, bounds: Bounds, pos: Pos, subpixel: (f64, f64)) -> Complex64 {
         let offset = pos - bounds.center();
 
         Complex64 {
-            im: self.comp.0 * f64::from(offset.y) * self.loc.scalar + self.loc.im0,
-            re: self.comp.1 * f64::from(offset.x) * self.loc.scalar + self.loc.re0,
+            im: self.comp.0 * (f64::from(offset.y) + subpixel.1) * self.loc.scalar + self.loc.im0,
+            re: self.comp.1 * (f64::from(offset.x) + subpixel.0) * self.loc.scalar + self.loc.re0,
         }
     }
 
+    /// Rescale the location from the bounds of a rendered image to the bounds of a new frame, such
+    /// that the frame shows the same region as the image.
+    pub fn fit_to_image(&mut self, image: &Bounds, frame: &Bounds) {
+        self.loc = self.loc.scale(image, frame, ScaleMethod::Min);
+    }
+
+    /// Create a rendering context for an image that shows the same region as a terminal frame.
+    ///
+    /// The location is rescaled from the frame bounds to the image bounds, the terminal aspect
+    /// compensation is removed, and accuracy shortcuts are disabled.
+    pub fn for_image(&self, frame: &Bounds, image: &Bounds) -> Self {
+        Self {
+            comp: self.image_comp(),
+            early_bailout: false,
+            ..self.rescaled(frame, image)
+        }
+    }
+
+    /// Create a rendering context that shows the same region within new bounds.
+    pub fn rescaled(&self, frame: &Bounds, target: &Bounds) -> Self {
+        Self {
+            loc: self.loc.scale(frame, target, ScaleMethod::Min),
+            ..self.clone()
+        }
+    }
+
+    /// Advance the location a fraction `t` of the way toward a target, as a step of a camera move.
+    ///
+    /// See `Loc::toward` for how each part of the location is interpolated.
+    pub fn step_toward(&mut self, target: &Loc, t: f64) {
+        self.loc = self.loc.toward(target, t);
+    }
+
+    /// Zoom to the rectangle of cells spanned by two corners, inclusive.
+    ///
+    /// The rectangle is centered and scaled to fit within the bounds, so the visible region
+    /// covers at least the rectangle's complex extents.
+    pub fn zoom_to_box(&mut self, bounds: Bounds, a: Pos, b: Pos) {
+        let (x0, x1) = (a.x.min(b.x), a.x.max(b.x));
+        let (y0, y1) = (a.y.min(b.y), a.y.max(b.y));
+
+        let center = self.complex_at_subpixel(
+            bounds,
+            Pos { x: x0, y: y0 },
+            (f64::from(x1 - x0) / 2., f64::from(y1 - y0) / 2.),
+        );
+        let width = f64::from(x1 - x0 + 1) / f64::from(bounds.width);
+        let height = f64::from(y1 - y0 + 1) / f64::from(bounds.height);
+
+        self.loc.move_to(center);
+        self.loc.scalar *= width.max(height);
+    }
+
+    /// Move the origin to the centroid of the largest interior region visible within the bounds.
+    ///
+    /// The location is left unchanged if no interior points are visible.
+    pub fn center_on_interior(&mut self, bounds: Bounds) {
+        let centroid = self.bind(bounds).to_ematrix().largest_interior_centroid();
+        if let Some(c) =
+            centroid.and_then(|(row, col)| self.coord_at(bounds, col as u16, row as u16))
+        {
+            self.loc.move_to(c);
+        }
+    }
+
+    /// Move the origin to the cell with the largest escape visible within the bounds, and zoom in
+    /// one step.
+    ///
+    /// The slowest escaping points lie closest to the set, where the boundary detail is most
+    /// intricate. Escapes are evaluated by iteration count whatever the color mode, and the
+    /// location is left unchanged if no points escape.
+    pub fn center_on_max_escape(&mut self, bounds: Bounds) {
+        let probe = Self {
+            color_mode: ColorMode::Escape,
+            ..self.clone()
+        };
+        let position = probe.bind(bounds).to_ematrix().max_escape_position();
+        if let Some(c) =
+            position.and_then(|(row, col)| self.coord_at(bounds, col as u16, row as u16))
+        {
+            self.loc.move_to(c);
+            self.loc.scalar /= self.zoom_factor;
+        }
+    }
+
+    /// Evaluate the escape of a complex value with the active function and iteration limit.
+    ///
+    /// The escape is derived according to the active color mode.
+    pub fn escape(&self, c: Complex64) -> Escape {
+        match self.color_mode {
+            ColorMode::Distance => self
+                .complexfn
+                .distance(c, self.loc.max_iter)
+                .map(|distance| ColorMode::distance_value(distance, self.loc.scalar)),
+            ColorMode::Velocity => self
+                .complexfn
+                .velocity(c, self.loc.max_iter)
+                .map(ColorMode::velocity_value),
+            ColorMode::Escape if self.early_bailout => {
+                self.complexfn.escape_bailout(c, self.loc.max_iter)
+            }
+            ColorMode::Escape => self.complexfn.escape(c, self.loc.max_iter),
+        }
+    }
+
+    /// Evaluate the escape of the cell at a given position.
+    pub fn escape_at(&self, bounds: Bounds, pos: Pos) -> Escape {
+        self.escape_at_subpixel(bounds, pos, (0., 0.))
+    }
+
+    /// Evaluate the escape at a fractional offset `(dx, dy)` from a given position.
+    ///
+    /// Past the zoom depth where `f64` renders blocks, the cell's complex value and orbit are
+    /// computed in double-double precision as selected by `precision`. Double-double orbits only
+    /// color by escape and skip the trapped orbit bailout; distance and velocity are always
+    /// evaluated in `f64`.
+    pub fn escape_at_subpixel(&self, bounds: Bounds, pos: Pos, subpixel: (f64, f64)) -> Escape {
+        if self.uses_double_double() {
+            let c = self.double_double_at_subpixel(bounds, pos, subpixel);
+            self.complexfn.escape_double_double(c, self.loc.max_iter)
+        } else {
+            self.escape(self.complex_at_subpixel(bounds, pos, subpixel))
+        }
+    }
+
+    /// Determine if escapes are evaluated in double-double precision at the current zoom.
+    pub fn uses_double_double(&self) -> bool {
+        self.color_mode == ColorMode::Escape && self.precision.uses_double_double(self.loc.scalar)
+    }
+
+    /// Determine the double-double complex value at a fractional offset `(dx, dy)` from a given
+    /// position.
+    pub fn double_double_at_subpixel(
+        &self,
+        bounds: Bounds,
+        pos: Pos,
+        subpixel: (f64, f64),
+    ) -> DoubleDoubleComplex {
+        // The offset from the origin only needs the relative precision of `f64`; it's the sum
+        // with the origin that loses the low bits of the offset.
+        let offset = pos - bounds.center();
+        DoubleDoubleComplex::new(
+            DoubleDouble::from(self.loc.re0)
+                + DoubleDouble::from(
+                    self.comp.1 * (f64::from(offset.x) + subpixel.0) * self.loc.scalar,
+                ),
+            DoubleDouble::from(self.loc.im0)
+                + DoubleDouble::from(
+                    self.comp.0 * (f64::from(offset.y) + subpixel.1) * self.loc.scalar,
+                ),
+        )
+    }
+
+    /// Determine if the zoom has reached the limit of the precision escapes are evaluated with.
+    ///
+    /// At the limit the center cell can't be told apart from a neighboring cell, so zooming
+    /// further only enlarges blocks of identical cells.
+    pub fn at_precision_limit(&self, bounds: Bounds) -> bool {
+        let center = bounds.center();
+
+        if self.uses_double_double() {
+            // Double-double coordinates stay distinct however small the offset, but orbits
+            // round them to the relative precision of double-double arithmetic.
+            let c = self.double_double_at_subpixel(bounds, center, (0., 0.));
+            let step = |comp: f64| (comp * self.loc.scalar).abs();
+            return step(self.comp.1) < c.re.hi.abs() * DoubleDouble::EPSILON
+                || step(self.comp.0) < c.im.hi.abs() * DoubleDouble::EPSILON;
+        }
+
+        let c = self.complex_at(bounds, center);
+        let left = Pos {
+            x: center.x.saturating_sub(1),
+            ..center
+        };
+        let up = Pos {
+            y: center.y.saturating_sub(1),
+            ..center
+        };
+        (center.x > 0 && self.complex_at(bounds, left).re == c.re)
+            || (center.y > 0 && self.complex_at(bounds, up).im == c.im)
+    }
+
+    /// Count the iterations evaluated to derive the escape of a complex value.
+    ///
+    /// The trapped orbit bailout only applies when coloring by escape, matching `escape`.
+    pub fn iterations(&self, c: Complex64) -> u32 {
+        let bailout = self.early_bailout && self.color_mode == ColorMode::Escape;
+        self.complexfn.iterations(c, self.loc.max_iter, bailout)
+    }
+
+    /// Determine if this rctx only differs from `previous` by a higher iteration limit.
+    ///
+    /// When this holds, every point that escaped under `previous` escapes with the same value
+    /// under this rctx, so only interior points need to be evaluated again.
+    pub fn extends_iterations(&self, previous: &Rctx) -> bool {
+        if self.loc.max_iter < previous.loc.max_iter || self.color_mode == ColorMode::Velocity {
+            return false;
+        }
+
+        let mut rebased = previous.clone();
+        rebased.loc.max_iter = self.loc.max_iter;
+        rebased == *self
+    }
+
+    /// Suggest an iteration limit that resolves the detail visible within the bounds.
+    ///
+    /// The frame is probed on a coarse grid with a generous iteration limit, and the suggestion
+    /// leaves headroom above the escapes of nearly all escaping points. Frames where no probed
+    /// point escapes keep the current limit.
+    pub fn suggest_max_iter(&self, bounds: Bounds) -> u32 {
+        let longest = bounds.width.max(bounds.height).max(1);
+        let factor = (f64::from(Self::PROBE_CELLS) / f64::from(longest)).min(1.);
+        let probe = Bounds {
+            width: ((f64::from(bounds.width) * factor) as u16).max(1),
+            height: ((f64::from(bounds.height) * factor) as u16).max(1),
+        };
+        let rctx = self.rescaled(&bounds, &probe);
+        let limit = (self.loc.max_iter.saturating_mul(16)).clamp(1_000, 100_000);
+
+        let mut escapes: Vec<f64> = (0..probe.width)
+            .cartesian_product(0..probe.height)
+            .map(Pos::from)
+            .collect::<Vec<Pos>>()
+            .par_iter()
+            .filter_map(|pos| {
+                let c = rctx.complex_at(probe, *pos);
+                rctx.complexfn.escape_bailout(c, limit)
+            })
+            .collect();
+
+        if escapes.is_empty() {
+            return self.loc.max_iter;
+        }
+
+        escapes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let percentile = escapes[(escapes.len() - 1) * 99 / 100];
+        let suggested = (percentile.max(0.) * 1.25).ceil() as u32;
+        suggested.clamp(Self::MIN_SUGGESTED_ITER, limit)
+    }
+
+    /// Determine if this rctx evaluates the same escapes as `previous`, only coloring them
+    /// differently.
+    ///
+    /// When this holds, a frame of escapes evaluated for `previous` can be recolored instead of
+    /// evaluated again.
+    pub fn same_escapes(&self, previous: &Rctx) -> bool {
+        let mut rebased = previous.clone();
+        rebased.colorer = self.colorer.clone();
+        // Velocities are normalized to the color period when evaluated.
+        if self.color_mode != ColorMode::Velocity {
+            rebased.color_period = self.color_period;
+        }
+        rebased == *self
+    }
+
     /// Apply a transform to the rctx.
     pub fn transform(&mut self, transform: &RctxTransform) {
         match *transform {
@@ -85,50 +450,135 @@ impl Rctx {
             RctxTransform::IncIterations => self.loc.max_iter += Self::ITERATIONS_SCALAR,
             RctxTransform::DecIterations => self.loc.max_iter -= Self::ITERATIONS_SCALAR,
 
-            RctxTransform::ScaleIn => self.loc.scalar /= Self::SCALE_SCALAR,
-            RctxTransform::ScaleOut => self.loc.scalar *= Self::SCALE_SCALAR,
+            RctxTransform::ScaleIn => self.loc.scalar /= self.zoom_factor,
+            RctxTransform::ScaleOut => self.loc.scalar *= self.zoom_factor,
 
             RctxTransform::Reset => {
                 // TODO: use `Loc::for_bounds()` for appropriate zoom selection
-                std::mem::replace(&mut self.loc, Loc::default());
+                self.loc = Loc::default();
             }
 
             RctxTransform::IncExp => {
-                *self.complexfn.exp_mut() += Self::EXP_SCALAR;
+                let exp = self.complexfn.exp_mut();
+                if self.integer_exp {
+                    *exp = exp.round() + 1.;
+                } else {
+                    *exp += Self::EXP_SCALAR;
+                }
             }
             RctxTransform::DecExp => {
-                *self.complexfn.exp_mut() -= Self::EXP_SCALAR;
+                let exp = self.complexfn.exp_mut();
+                if self.integer_exp {
+                    *exp = (exp.round() - 1.).max(Self::MIN_INTEGER_EXP);
+                } else {
+                    *exp -= Self::EXP_SCALAR;
+                }
             }
 
+            RctxTransform::IncColorPeriod => self.color_period *= Self::COLOR_PERIOD_SCALAR,
+            RctxTransform::DecColorPeriod => self.color_period /= Self::COLOR_PERIOD_SCALAR,
+
+            RctxTransform::ShiftPhaseForward => self.colorer.shift_phase(self.phase_step),
+            RctxTransform::ShiftPhaseBackward => self.colorer.shift_phase(-self.phase_step),
+
             RctxTransform::SwitchFn => {
-                let new_fn: PolyComplexFn;
-                match self.complexfn {
+                self.complexfn = match self.complexfn {
                     PolyComplexFn::Julia(ref j) => {
-                        new_fn = PolyComplexFn::Mandelbrot(Mandelbrot::from(j));
                         // When switching from a Julia fractal to the mandelbrot fractal, we need
                         // to change the location specified in the Julia offset. This allows the
                         // user to switch back and forth between the two fractals to observe how
                         // Julia fractals change as the position in the mandelbrot set changes.
                         self.loc.move_to(j.c_offset);
+                        PolyComplexFn::Mandelbrot(Mandelbrot::from(j))
                     }
                     PolyComplexFn::Mandelbrot(ref m) => {
                         // When switching from the mandelbrot fractal to a Julia fractal, the
                         // current position generally maps to a similar looking position. The
                         // location can be preserved.
-                        new_fn = PolyComplexFn::Julia(Julia::from_c(m, self.loc.origin()))
+                        PolyComplexFn::Julia(Julia {
+                            c_offset: self.loc.origin(),
+                            ..Julia::from(m)
+                        })
                     }
-                }
-                self.complexfn = new_fn;
+                    // The other functions have no Julia sets, so switch back to the multibrot.
+                    ref f => PolyComplexFn::multibrot(f.exp()),
+                };
+            }
+
+            RctxTransform::SwitchFamily => {
+                self.complexfn = match self.complexfn {
+                    // The Julia offset has no counterpart in the Burning Ship, so the location is
+                    // kept as it is rather than moved back to the offset.
+                    PolyComplexFn::Mandelbrot(Mandelbrot { exp, .. })
+                    | PolyComplexFn::Julia(Julia { exp, .. }) => {
+                        PolyComplexFn::BurningShip(BurningShip {
+                            exp,
+                            ..BurningShip::default()
+                        })
+                    }
+                    PolyComplexFn::BurningShip(ref b) => PolyComplexFn::Tricorn(Tricorn {
+                        exp: b.exp,
+                        ..Tricorn::default()
+                    }),
+                    PolyComplexFn::Tricorn(ref t) => PolyComplexFn::Newton(Newton { exp: t.exp }),
+                    PolyComplexFn::Newton(ref n) => PolyComplexFn::multibrot(n.exp),
+                };
             }
         }
     }
 
+    /// Adapt a palette to the color period of this context.
+    pub fn with_color_period(&self, palette: Box<dyn Palette>) -> Box<dyn Palette> {
+        if self.color_period == Self::COLOR_PERIOD {
+            palette
+        } else {
+            Box::new(ScaledPalette::new(
+                palette,
+                Self::COLOR_PERIOD / self.color_period,
+            ))
+        }
+    }
+
+    /// The spec colorer, adapted to the color period of this context.
+    pub fn palette(&self) -> Box<dyn Palette> {
+        self.with_color_period(Box::new(self.colorer.clone()))
+    }
+
+    /// Convert the compensation of a spec to the compensation for terminal cells.
+    pub fn terminal_comp(&self) -> (f64, f64) {
+        (
+            self.comp.0 * Self::TERMINAL_COMP.0,
+            self.comp.1 * Self::TERMINAL_COMP.1,
+        )
+    }
+
+    /// Convert the compensation for terminal cells back to the compensation of a spec.
+    pub fn image_comp(&self) -> (f64, f64) {
+        (
+            self.comp.0 / Self::TERMINAL_COMP.0,
+            self.comp.1 / Self::TERMINAL_COMP.1,
+        )
+    }
+
+    /// Create a context that samples each cell `factor` times along the imaginary axis.
+    ///
+    /// The vertical compensation is divided by the factor, so rendering to bounds that are
+    /// `factor` times taller covers the same region with finer rows. Two samples per terminal
+    /// cell bring the sampling close to square for 2:1 cells.
+    pub fn with_vertical_oversample(&self, factor: u16) -> Self {
+        Self {
+            comp: (self.comp.0 / f64::from(factor), self.comp.1),
+            ..self.clone()
+        }
+    }
+
     /// Create a cell rendering context with compensations for terminal cell sizes
     pub fn for_terminal(loc: Option<Loc>) -> Self {
         Self {
-            loc: loc.unwrap_or(Loc::default()),
-            comp: (2.3, 1.),
-            .. Self::default()
+            loc: loc.unwrap_or_default(),
+            comp: Self::TERMINAL_COMP,
+            early_bailout: true,
+            ..Self::default()
         }
     }
 }
@@ -138,12 +588,74 @@ impl Default for Rctx {
         Self {
             loc: Loc::default(),
             complexfn: PolyComplexFn::default(),
-            colorer: crate::SineRGB::default(),
+            colorer: Colorer::default(),
             comp: (1., 1.),
+            early_bailout: false,
+            color_mode: ColorMode::default(),
+            color_period: Self::COLOR_PERIOD,
+            zoom_factor: Self::SCALE_SCALAR,
+            phase_step: Self::PHASE_SCALAR,
+            integer_exp: false,
+            precision: Precision::default(),
         }
     }
 }
 
+/// Derive a deterministic subpixel offset within `[-0.5, 0.5)` on each axis for a sample.
+///
+/// Offsets are hashed from the position and sample index with splitmix64, so repeated renders are
+/// identical without depending on a random number generator.
+fn jitter(pos: Pos, sample: u32) -> (f64, f64) {
+    let seed = u64::from(pos.x) << 48 | u64::from(pos.y) << 32 | u64::from(sample);
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+
+    let unit = |bits: u64| bits as f64 / (f64::from(u32::MAX) + 1.) - 0.5;
+    (unit(z >> 32), unit(z & 0xffff_ffff))
+}
+
+/// The number of cells evaluated between updates of a render's `Progress`.
+pub const PROGRESS_GRANULARITY: usize = 1024;
+
+/// The number of samples taken for every pixel by adaptive anti-aliasing.
+pub const ADAPTIVE_MIN_SAMPLES: u32 = 4;
+
+/// The cells of a frame evaluated so far by a render with a deadline.
+#[derive(Debug, Clone)]
+pub struct PartialEMatrix {
+    bounds: Bounds,
+
+    /// The escape of each cell in column major order, or `None` until it has been evaluated.
+    cells: Vec<Option<Escape>>,
+}
+
+impl PartialEMatrix {
+    /// Start a render of the given bounds with no cells evaluated.
+    pub fn new(bounds: Bounds) -> Self {
+        Self {
+            bounds,
+            cells: vec![None; usize::from(bounds.width) * usize::from(bounds.height)],
+        }
+    }
+
+    /// The number of cells that haven't been evaluated yet.
+    pub fn remaining(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_none()).count()
+    }
+}
+
+/// A rectangle of a frame evaluated by `BoundRctx::tiles`.
+#[derive(Debug, Clone)]
+pub struct Tile {
+    /// The position of the top left cell of the tile within the frame.
+    pub origin: Pos,
+
+    /// The escapes of the cells of the tile, starting from `origin`.
+    pub ematrix: EMatrix,
+}
+
 /// A rendering context with the given bounds.
 pub struct BoundRctx<'a> {
     pub rctx: &'a Rctx,
@@ -157,18 +669,410 @@ impl<'a> BoundRctx<'a> {
 
         let escapes: Vec<Escape> = x_iter
             .cartesian_product(y_iter)
-            .map(|pt| Pos::from(pt))
+            .map(Pos::from)
+            .collect::<Vec<Pos>>()
+            .par_iter()
+            .map(|pos| self.rctx.escape_at(self.bounds, *pos))
+            .collect();
+
+        self.normalize(self.ematrix(escapes))
+    }
+
+    /// Evaluate the number of iterations spent on each cell instead of its escape.
+    ///
+    /// Every cell of the profile has a value, so interior cells can be told apart by how much
+    /// work ruling them out took.
+    pub fn to_iteration_ematrix(&self) -> EMatrix {
+        let y_iter = 0..self.bounds.height;
+        let x_iter = 0..self.bounds.width;
+
+        let iterations: Vec<Escape> = x_iter
+            .cartesian_product(y_iter)
+            .map(Pos::from)
             .collect::<Vec<Pos>>()
             .par_iter()
             .map(|pos| self.rctx.complex_at(self.bounds, *pos))
-            .map(|c| self.rctx.complexfn.escape(c, self.rctx.loc.max_iter))
+            .map(|c| Some(f64::from(self.rctx.iterations(c))))
+            .collect();
+
+        self.ematrix(iterations)
+    }
+
+    /// Evaluate the escape matrix, counting evaluated cells in `progress`.
+    ///
+    /// Progress is updated every `PROGRESS_GRANULARITY` cells.
+    pub fn to_ematrix_with_progress(&self, progress: &Progress) -> EMatrix {
+        self.to_ematrix_with_callback(PROGRESS_GRANULARITY, |done, _| progress.update(done))
+    }
+
+    /// Evaluate the escape matrix, reporting progress as chunks of `granularity` cells complete.
+    ///
+    /// The callback receives the number of cells evaluated so far and the total, and is called
+    /// once per chunk, so it's called `ceil(total / granularity)` times. Chunks are evaluated in
+    /// parallel and may complete out of order, so consecutive calls can report the same or
+    /// slightly lower counts. Larger chunks reduce the overhead of reporting at the cost of
+    /// smoothness.
+    pub fn to_ematrix_with_callback<F>(&self, granularity: usize, callback: F) -> EMatrix
+    where
+        F: Fn(u64, u64) + Sync,
+    {
+        let y_iter = 0..self.bounds.height;
+        let x_iter = 0..self.bounds.width;
+        let total = u64::from(self.bounds.width) * u64::from(self.bounds.height);
+        let done = AtomicU64::new(0);
+
+        let chunks: Vec<Vec<Escape>> = x_iter
+            .cartesian_product(y_iter)
+            .map(Pos::from)
+            .collect::<Vec<Pos>>()
+            .par_chunks(granularity.max(1))
+            .map(|chunk| {
+                let escapes: Vec<Escape> = chunk
+                    .iter()
+                    .map(|pos| self.rctx.escape_at(self.bounds, *pos))
+                    .collect();
+                let cells = chunk.len() as u64;
+                callback(done.fetch_add(cells, Ordering::Relaxed) + cells, total);
+                escapes
+            })
+            .collect();
+
+        self.normalize(self.ematrix(chunks.concat()))
+    }
+
+    /// Evaluate escapes into a caller owned buffer, allowing the buffer to be reused across
+    /// frames.
+    ///
+    /// The buffer uses the column major layout of an `EMatrix` and must hold exactly one escape
+    /// per cell. The escapes are identical to those of `to_ematrix`, including the frame
+    /// normalization of the color mode.
+    pub fn render_into(&self, buf: &mut [Escape]) -> Result<(), crate::Error> {
+        let height = usize::from(self.bounds.height);
+        let expected = height * usize::from(self.bounds.width);
+        if buf.len() != expected {
+            return Err(crate::Error::from(format!(
+                "Cannot render {} cells into a buffer of length {}",
+                expected,
+                buf.len()
+            )));
+        }
+
+        buf.par_iter_mut().enumerate().for_each(|(index, escape)| {
+            let pos = Pos {
+                x: (index / height) as u16,
+                y: (index % height) as u16,
+            };
+            *escape = self.rctx.escape_at(self.bounds, pos);
+        });
+        self.rctx
+            .color_mode
+            .normalize_escapes(buf, self.rctx.color_period);
+
+        Ok(())
+    }
+
+    /// Evaluate the escape matrix by extending a matrix rendered with a lower iteration limit.
+    ///
+    /// Escaped cells of `previous` are kept as is and only interior cells are evaluated again,
+    /// which is much faster than `to_ematrix` on interior heavy frames. The result is identical
+    /// to `to_ematrix` as long as `previous` was rendered over the same bounds by an rctx that
+    /// this rctx `extends_iterations` of.
+    pub fn to_ematrix_extended(&self, previous: EMatrix) -> EMatrix {
+        let height = usize::from(self.bounds.height);
+        if previous.nrows() != height || previous.ncols() != usize::from(self.bounds.width) {
+            return self.to_ematrix();
+        }
+
+        let mut ematrix = previous;
+        ematrix
+            .as_mut_slice()
+            .par_iter_mut()
+            .enumerate()
+            .filter(|(_, escape)| escape.is_none())
+            .for_each(|(index, escape)| {
+                let pos = Pos {
+                    x: (index / height) as u16,
+                    y: (index % height) as u16,
+                };
+                *escape = self.rctx.escape_at(self.bounds, pos);
+            });
+
+        ematrix
+    }
+
+    /// Evaluate the escape matrix, stopping once the deadline passes.
+    ///
+    /// Returns the complete matrix, or the cells evaluated before the deadline so the render can
+    /// be picked up again with `resume_until`.
+    pub fn to_ematrix_until(&self, deadline: Instant) -> Result<EMatrix, PartialEMatrix> {
+        self.resume_until(PartialEMatrix::new(self.bounds), deadline)
+    }
+
+    /// Continue a render stopped by `to_ematrix_until`, evaluating only the cells it didn't get to.
+    ///
+    /// Once every cell has been evaluated the matrix is identical to `to_ematrix`. A partial
+    /// render of different bounds is discarded and started over.
+    pub fn resume_until(
+        &self,
+        partial: PartialEMatrix,
+        deadline: Instant,
+    ) -> Result<EMatrix, PartialEMatrix> {
+        let mut partial = if partial.bounds == self.bounds {
+            partial
+        } else {
+            PartialEMatrix::new(self.bounds)
+        };
+
+        let height = usize::from(self.bounds.height);
+        partial
+            .cells
+            .par_iter_mut()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_none())
+            .for_each(|(index, cell)| {
+                if Instant::now() <= deadline {
+                    let pos = Pos {
+                        x: (index / height) as u16,
+                        y: (index % height) as u16,
+                    };
+                    *cell = Some(self.rctx.escape_at(self.bounds, pos));
+                }
+            });
+
+        let escapes: Option<Vec<Escape>> = partial.cells.iter().copied().collect();
+        match escapes {
+            Some(escapes) => Ok(self.normalize(self.ematrix(escapes))),
+            None => Err(partial),
+        }
+    }
+
+    /// Build an escape matrix from an incomplete render, leaving cells that haven't been
+    /// evaluated yet as interior.
+    pub fn partial_ematrix(&self, partial: &PartialEMatrix) -> EMatrix {
+        if partial.bounds != self.bounds {
+            return self.ematrix(vec![
+                None;
+                usize::from(self.bounds.width)
+                    * usize::from(self.bounds.height)
+            ]);
+        }
+        let escapes = partial.cells.iter().map(|cell| cell.flatten()).collect();
+        self.normalize(self.ematrix(escapes))
+    }
+
+    /// Evaluate the escape matrix, visiting pixels in the given order.
+    ///
+    /// The resulting matrix is identical to `to_ematrix` regardless of order.
+    pub fn to_ematrix_ordered(&self, order: PixelOrder) -> EMatrix {
+        let positions = order.positions(self.bounds);
+
+        let escapes: Vec<Escape> = positions
+            .par_iter()
+            .map(|pos| self.rctx.escape_at(self.bounds, *pos))
+            .collect();
+
+        // Scatter the escapes into the column major layout of the matrix.
+        let height = usize::from(self.bounds.height);
+        let mut cells: Vec<Escape> = vec![None; escapes.len()];
+        for (pos, escape) in positions.iter().zip(escapes) {
+            cells[usize::from(pos.x) * height + usize::from(pos.y)] = escape;
+        }
+
+        self.normalize(self.ematrix(cells))
+    }
+
+    /// Evaluate the frame one `tile` sized rectangle at a time.
+    ///
+    /// Tiles are yielded a row of tiles at a time from the top left, and each tile is evaluated
+    /// in parallel only once it's reached, so a consumer that doesn't keep tiles around holds a
+    /// single tile of escapes at a time. Tiles along the right and bottom edges are clipped to
+    /// the frame. Frame normalization by the color mode needs the whole frame and isn't applied.
+    pub fn tiles(&self, tile: Bounds) -> impl Iterator<Item = Tile> + '_ {
+        let Bounds { width, height } = self.bounds;
+        let tile_width = tile.width.max(1);
+        let tile_height = tile.height.max(1);
+
+        (0..height)
+            .step_by(usize::from(tile_height))
+            .flat_map(move |y0| {
+                (0..width)
+                    .step_by(usize::from(tile_width))
+                    .map(move |x0| self.tile_at(Pos { x: x0, y: y0 }, tile_width, tile_height))
+            })
+    }
+
+    /// Evaluate the tile with its top left cell at `origin`, clipped to the frame.
+    fn tile_at(&self, origin: Pos, width: u16, height: u16) -> Tile {
+        let x_iter = origin.x..origin.x.saturating_add(width).min(self.bounds.width);
+        let y_iter = origin.y..origin.y.saturating_add(height).min(self.bounds.height);
+        let (ncols, nrows) = (x_iter.len(), y_iter.len());
+
+        let escapes: Vec<Escape> = x_iter
+            .cartesian_product(y_iter)
+            .map(Pos::from)
+            .collect::<Vec<Pos>>()
+            .par_iter()
+            .map(|pos| self.rctx.escape_at(self.bounds, *pos))
             .collect();
 
+        Tile {
+            origin,
+            ematrix: EMatrix::from_vec(nrows, ncols, escapes)
+                .expect("one escape is evaluated per cell of the tile"),
+        }
+    }
+
+    /// Evaluate the escape matrix one `tile` sized rectangle at a time with `tiles`, scattering
+    /// each tile into the matrix as it completes.
+    ///
+    /// The resulting matrix is identical to `to_ematrix`.
+    pub fn to_ematrix_tiled(&self, tile: Bounds) -> EMatrix {
+        let height = usize::from(self.bounds.height);
+        let mut cells: Vec<Escape> = vec![None; usize::from(self.bounds.width) * height];
+        for Tile { origin, ematrix } in self.tiles(tile) {
+            for (pos, escape) in ematrix.enumerate_positions() {
+                let x = usize::from(origin.x + pos.x);
+                let y = usize::from(origin.y + pos.y);
+                cells[x * height + y] = *escape;
+            }
+        }
+
+        self.normalize(self.ematrix(cells))
+    }
+
+    /// Assemble escapes evaluated for every cell of the bounds, in column major order.
+    fn ematrix(&self, escapes: Vec<Escape>) -> EMatrix {
         EMatrix::from_vec(
             usize::from(self.bounds.height),
             usize::from(self.bounds.width),
             escapes,
         )
+        .expect("one escape is evaluated per cell")
+    }
+
+    /// Apply the frame normalization of the active color mode.
+    fn normalize(&self, ematrix: EMatrix) -> EMatrix {
+        self.rctx
+            .color_mode
+            .normalize(ematrix, self.rctx.color_period)
+    }
+
+    /// Render an image by averaging the colors of `samples` jittered samples within each pixel.
+    ///
+    /// Higher sample densities reduce aliasing noise along the set boundary, at a proportional
+    /// cost in render time.
+    pub fn to_img_jittered<P: Palette + ?Sized>(&self, palette: &P, samples: u32) -> RgbImage {
+        let samples = samples.max(1);
+        let positions = PixelOrder::Scanline.positions(self.bounds);
+
+        let pixels: Vec<[u8; 3]> = positions
+            .par_iter()
+            .map(|pos| {
+                let mut acc = [0u32; 3];
+                for sample in 0..samples {
+                    let escape =
+                        self.rctx
+                            .escape_at_subpixel(self.bounds, *pos, jitter(*pos, sample));
+                    let rgb = palette.rgb_at(*pos, escape);
+                    acc[0] += u32::from(rgb.0);
+                    acc[1] += u32::from(rgb.1);
+                    acc[2] += u32::from(rgb.2);
+                }
+                [
+                    ((acc[0] + samples / 2) / samples) as u8,
+                    ((acc[1] + samples / 2) / samples) as u8,
+                    ((acc[2] + samples / 2) / samples) as u8,
+                ]
+            })
+            .collect();
+
+        RgbImage::from_raw(
+            u32::from(self.bounds.width),
+            u32::from(self.bounds.height),
+            pixels.concat(),
+        )
+        .expect("pixel buffer matches the bounds")
+    }
+
+    /// Render an image by averaging jittered samples within each pixel, taking samples until the
+    /// variance of their escapes drops below `threshold` or `max_samples` have been taken.
+    ///
+    /// Smooth regions settle after `ADAPTIVE_MIN_SAMPLES` samples, while pixels straddling the set
+    /// boundary take up to `max_samples`. Pixels with both interior and escaped samples always
+    /// take the maximum.
+    pub fn to_img_adaptive<P: Palette + ?Sized>(
+        &self,
+        palette: &P,
+        threshold: f64,
+        max_samples: u32,
+    ) -> RgbImage {
+        let positions = PixelOrder::Scanline.positions(self.bounds);
+
+        let pixels: Vec<[u8; 3]> = positions
+            .par_iter()
+            .map(|pos| self.adaptive_pixel(palette, *pos, threshold, max_samples).0)
+            .collect();
+
+        RgbImage::from_raw(
+            u32::from(self.bounds.width),
+            u32::from(self.bounds.height),
+            pixels.concat(),
+        )
+        .expect("pixel buffer matches the bounds")
+    }
+
+    /// Sample a pixel adaptively, returning its color and the number of samples taken.
+    pub fn adaptive_pixel<P: Palette + ?Sized>(
+        &self,
+        palette: &P,
+        pos: Pos,
+        threshold: f64,
+        max_samples: u32,
+    ) -> ([u8; 3], u32) {
+        let max_samples = max_samples.max(ADAPTIVE_MIN_SAMPLES);
+        let mut acc = [0u32; 3];
+        let (mut interior, mut escaped) = (0u32, 0u32);
+        // Welford's running mean and sum of squared deviations of the escaped samples.
+        let (mut mean, mut m2) = (0f64, 0f64);
+
+        let mut samples = 0;
+        while samples < max_samples {
+            let escape = self
+                .rctx
+                .escape_at_subpixel(self.bounds, pos, jitter(pos, samples));
+            let rgb = palette.rgb_at(pos, escape);
+            acc[0] += u32::from(rgb.0);
+            acc[1] += u32::from(rgb.1);
+            acc[2] += u32::from(rgb.2);
+            samples += 1;
+
+            match escape {
+                None => interior += 1,
+                Some(value) => {
+                    escaped += 1;
+                    let delta = value - mean;
+                    mean += delta / f64::from(escaped);
+                    m2 += delta * (value - mean);
+                }
+            }
+
+            let mixed = interior > 0 && escaped > 0;
+            let variance = if escaped > 1 {
+                m2 / f64::from(escaped - 1)
+            } else {
+                0.
+            };
+            if samples >= ADAPTIVE_MIN_SAMPLES && !mixed && variance < threshold {
+                break;
+            }
+        }
+
+        let rgb = [
+            ((acc[0] + samples / 2) / samples) as u8,
+            ((acc[1] + samples / 2) / samples) as u8,
+            ((acc[2] + samples / 2) / samples) as u8,
+        ];
+        (rgb, samples)
     }
 
     pub fn to_ematrix_with_bar(&self, bar: indicatif::ProgressBar) -> EMatrix {
@@ -179,19 +1083,14 @@ impl<'a> BoundRctx<'a> {
 
         let escapes: Vec<Escape> = x_iter
             .cartesian_product(y_iter)
-            .map(|pt| Pos::from(pt))
+            .map(Pos::from)
             .collect::<Vec<Pos>>()
             .par_iter()
             .progress_with(bar)
-            .map(|pos| self.rctx.complex_at(self.bounds, *pos))
-            .map(|c| self.rctx.complexfn.escape(c, self.rctx.loc.max_iter))
+            .map(|pos| self.rctx.escape_at(self.bounds, *pos))
             .collect();
 
-        EMatrix::from_vec(
-            usize::from(self.bounds.height),
-            usize::from(self.bounds.width),
-            escapes,
-        )
+        self.normalize(self.ematrix(escapes))
     }
 }
 
@@ -213,12 +1112,22 @@ pub enum RctxTransform {
     IncIterations,
     /// Decrement the escape iteration limit
     DecIterations,
-    /// Switch to the next function
+    /// Toggle between the mandelbrot fractal and its Julia sets
     SwitchFn,
+    /// Switch to the next family of functions
+    SwitchFamily,
     /// Increment the function exponent
     IncExp,
     /// Decrement the function exponent
     DecExp,
+    /// Increase the number of escape iterations per palette cycle
+    IncColorPeriod,
+    /// Decrease the number of escape iterations per palette cycle
+    DecColorPeriod,
+    /// Cycle the palette colors forward
+    ShiftPhaseForward,
+    /// Cycle the palette colors backward
+    ShiftPhaseBackward,
     /// Reset the context to defaults
     Reset,
 }
@@ -231,11 +1140,12 @@ impl tui::widgets::Widget for Rctx {
         };
 
         let ematrix = self.bind(bounds).to_ematrix();
+        let palette = self.palette();
 
         for yi in 0..bounds.height {
             for xi in 0..bounds.width {
                 let escape = ematrix.index((yi as usize, xi as usize));
-                let rgb = self.colorer.rgb(*escape);
+                let rgb = palette.rgb(*escape);
                 let color = tui::style::Color::Rgb(rgb.0, rgb.1, rgb.2);
                 buf.get_mut(xi + rect.x, yi + rect.y).set_bg(color);
             }
@@ -258,3 +1168,791 @@ impl tui::widgets::Widget for Rctx {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mandelbrot;
+    use std::time::Duration;
+
+    const BOUNDS: Bounds = Bounds {
+        width: 48,
+        height: 32,
+    };
+
+    /// The mean squared difference between the channels of two images.
+    fn mean_squared_error(a: &RgbImage, b: &RgbImage) -> f64 {
+        let total: f64 = a
+            .as_raw()
+            .iter()
+            .zip(b.as_raw())
+            .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+            .sum();
+        total / a.as_raw().len() as f64
+    }
+
+    #[test]
+    fn doubling_the_sample_density_halves_the_variance() {
+        // Pixels several times wider than the filaments of seahorse valley, so each sample lands
+        // on an effectively random escape.
+        let rctx = Rctx {
+            loc: Loc {
+                re0: -0.7436,
+                im0: 0.1318,
+                scalar: 2e-3,
+                max_iter: 500,
+            },
+            ..Rctx::default()
+        };
+        let bound = rctx.bind(Bounds {
+            width: 16,
+            height: 16,
+        });
+        let palette = rctx.palette();
+
+        // Jitter offsets are hashed from the pixel and sample index, so every render here draws
+        // from the same fixed sequence of samples.
+        let reference = bound.to_img_jittered(palette.as_ref(), 256);
+        let variance = |samples| {
+            mean_squared_error(
+                &bound.to_img_jittered(palette.as_ref(), samples),
+                &reference,
+            )
+        };
+
+        let ratio = variance(16) / variance(8);
+        assert!((0.3..0.7).contains(&ratio), "variance ratio {}", ratio);
+    }
+
+    #[test]
+    fn adaptive_sampling_concentrates_on_the_boundary() {
+        let rctx = Rctx::default();
+        let palette = rctx.palette();
+        let bound = rctx.bind(BOUNDS);
+
+        // The center pixel lies well within the main cardioid.
+        let (_, samples) = bound.adaptive_pixel(palette.as_ref(), BOUNDS.center(), 1., 16);
+        assert_eq!(samples, ADAPTIVE_MIN_SAMPLES);
+
+        // Pixels taking every sample share their jitter with fixed sampling, and match it.
+        let fixed = bound.to_img_jittered(palette.as_ref(), 16);
+        let mut boundary = 0;
+        for pos in PixelOrder::Scanline.positions(BOUNDS) {
+            let (rgb, samples) = bound.adaptive_pixel(palette.as_ref(), pos, 1., 16);
+            assert!((ADAPTIVE_MIN_SAMPLES..=16).contains(&samples));
+            if samples == 16 {
+                boundary += 1;
+                assert_eq!(
+                    rgb,
+                    fixed.get_pixel(u32::from(pos.x), u32::from(pos.y)).0,
+                    "{:?}",
+                    pos
+                );
+            }
+        }
+        assert!(boundary > 0);
+    }
+
+    #[test]
+    fn render_into_matches_to_ematrix() {
+        for color_mode in [ColorMode::Escape, ColorMode::Distance, ColorMode::Velocity] {
+            let rctx = Rctx {
+                color_mode,
+                ..Rctx::default()
+            };
+            let bound = rctx.bind(BOUNDS);
+            let mut buf = vec![None; usize::from(BOUNDS.width) * usize::from(BOUNDS.height)];
+            bound.render_into(&mut buf).unwrap();
+
+            let ematrix =
+                EMatrix::from_vec(usize::from(BOUNDS.height), usize::from(BOUNDS.width), buf)
+                    .unwrap();
+            assert_eq!(
+                ematrix.inner(),
+                bound.to_ematrix().inner(),
+                "{:?}",
+                color_mode
+            );
+        }
+    }
+
+    #[test]
+    fn render_into_rejects_buffers_of_the_wrong_length() {
+        let rctx = Rctx::default();
+        let cells = usize::from(BOUNDS.width) * usize::from(BOUNDS.height);
+
+        for len in [0, cells - 1, cells + 1] {
+            let mut buf = vec![None; len];
+            assert!(rctx.bind(BOUNDS).render_into(&mut buf).is_err());
+        }
+    }
+
+    #[test]
+    fn tiled_renders_match_to_ematrix() {
+        let tiles = [(1, 1), (7, 5), (16, 16), (48, 1), (1, 32), (100, 100)];
+        for color_mode in [ColorMode::Escape, ColorMode::Velocity] {
+            let rctx = Rctx {
+                color_mode,
+                ..Rctx::default()
+            };
+            let bound = rctx.bind(BOUNDS);
+            let untiled = bound.to_ematrix();
+
+            for (width, height) in tiles {
+                let tiled = bound.to_ematrix_tiled(Bounds { width, height });
+                assert_eq!(tiled.inner(), untiled.inner(), "{}x{} tiles", width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn tiles_are_clipped_to_the_frame() {
+        let rctx = Rctx::default();
+        let tiles: Vec<Tile> = rctx
+            .bind(BOUNDS)
+            .tiles(Bounds {
+                width: 20,
+                height: 20,
+            })
+            .collect();
+
+        let extents: Vec<(u16, u16, usize, usize)> = tiles
+            .iter()
+            .map(|t| (t.origin.x, t.origin.y, t.ematrix.ncols(), t.ematrix.nrows()))
+            .collect();
+        assert_eq!(
+            extents,
+            vec![
+                (0, 0, 20, 20),
+                (20, 0, 20, 20),
+                (40, 0, 8, 20),
+                (0, 20, 20, 12),
+                (20, 20, 20, 12),
+                (40, 20, 8, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn to_ematrix_until_returns_promptly_once_over_budget() {
+        // Without the interior and periodicity checks every interior cell runs the full
+        // iteration limit, which would take minutes over the whole frame.
+        let mandelbrot = Mandelbrot {
+            check_interior: false,
+            check_periodicity: false,
+            ..Mandelbrot::default()
+        };
+        let rctx = Rctx {
+            complexfn: PolyComplexFn::Mandelbrot(mandelbrot),
+            loc: Loc {
+                max_iter: 1_000_000,
+                ..Loc::default()
+            },
+            ..Rctx::default()
+        };
+
+        let start = Instant::now();
+        let partial = rctx
+            .bind(BOUNDS)
+            .to_ematrix_until(start + Duration::from_millis(20))
+            .expect_err("the frame can't be evaluated within the budget");
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(partial.remaining() > 0);
+    }
+
+    #[test]
+    fn resumed_renders_match_to_ematrix() {
+        let rctx = Rctx::default();
+        let bound = rctx.bind(BOUNDS);
+
+        let partial = bound
+            .to_ematrix_until(Instant::now())
+            .expect_err("no time was given to evaluate the frame");
+        let ematrix = bound
+            .resume_until(partial, Instant::now() + Duration::from_secs(60))
+            .expect("a minute is enough to evaluate the frame");
+
+        assert_eq!(ematrix.inner(), bound.to_ematrix().inner());
+    }
+
+    #[test]
+    fn the_center_cell_is_at_the_origin() {
+        let rctx = Rctx {
+            loc: Loc {
+                im0: 0.25,
+                re0: -0.75,
+                scalar: 0.01,
+                max_iter: 100,
+            },
+            comp: (2., 1.),
+            ..Rctx::default()
+        };
+        assert_eq!(rctx.coord_at(BOUNDS, 24, 16), Some(rctx.loc.origin()));
+        assert_eq!(
+            rctx.coord_at(BOUNDS, 25, 16),
+            Some(rctx.complex_at(BOUNDS, Pos { x: 25, y: 16 }))
+        );
+
+        assert!(rctx.coord_at(BOUNDS, 47, 31).is_some());
+        assert_eq!(rctx.coord_at(BOUNDS, 48, 0), None);
+        assert_eq!(rctx.coord_at(BOUNDS, 0, 32), None);
+    }
+
+    #[test]
+    fn progress_callbacks_fire_once_per_granularity_cells() {
+        let rctx = Rctx::default();
+        let bound = rctx.bind(BOUNDS);
+        let total = 48 * 32;
+
+        for (granularity, calls) in [(100, 16), (total, 1), (0, total)] {
+            let updates = std::sync::Mutex::new(vec![]);
+            let ematrix = bound.to_ematrix_with_callback(granularity, |done, all| {
+                updates.lock().unwrap().push((done, all));
+            });
+            assert_eq!(ematrix.inner(), bound.to_ematrix().inner());
+
+            let mut updates = updates.into_inner().unwrap();
+            assert_eq!(updates.len(), calls, "granularity {}", granularity);
+            assert!(updates.iter().all(|&(_, all)| all == total as u64));
+            updates.sort_unstable();
+            assert_eq!(updates.last(), Some(&(total as u64, total as u64)));
+        }
+    }
+
+    #[test]
+    fn stepping_toward_a_target_interpolates_the_location() {
+        let start = Rctx {
+            loc: Loc {
+                im0: 0.,
+                re0: -0.5,
+                scalar: 1e-2,
+                max_iter: 100,
+            },
+            ..Rctx::default()
+        };
+        let target = Loc {
+            im0: 0.5,
+            re0: 0.25,
+            scalar: 1e-6,
+            max_iter: 300,
+        };
+
+        let mut halfway = start.clone();
+        halfway.step_toward(&target, 0.5);
+        assert!((halfway.loc.re0 - -0.125).abs() < 1e-15);
+        assert!((halfway.loc.im0 - 0.25).abs() < 1e-15);
+        assert!((halfway.loc.scalar / 1e-4 - 1.).abs() < 1e-12);
+        assert_eq!(halfway.loc.max_iter, 200);
+
+        let mut stayed = start.clone();
+        stayed.step_toward(&target, 0.);
+        assert_eq!(stayed, start);
+
+        for t in [1., 2.] {
+            let mut arrived = start.clone();
+            arrived.step_toward(&target, t);
+            assert_eq!(arrived.loc, target);
+            assert_eq!(arrived.complexfn, start.complexfn);
+        }
+    }
+
+    #[test]
+    fn only_palette_transforms_keep_the_cached_escapes() {
+        let rctx = Rctx::default();
+        let escapes = rctx.bind(BOUNDS).to_ematrix();
+
+        for transform in [
+            RctxTransform::IncColorPeriod,
+            RctxTransform::DecColorPeriod,
+            RctxTransform::ShiftPhaseForward,
+            RctxTransform::ShiftPhaseBackward,
+        ] {
+            let mut recolored = rctx.clone();
+            recolored.transform(&transform);
+            assert!(recolored.same_escapes(&rctx), "{:?}", transform);
+            assert_eq!(
+                recolored.bind(BOUNDS).to_ematrix().inner(),
+                escapes.inner(),
+                "{:?}",
+                transform
+            );
+        }
+
+        for transform in [
+            RctxTransform::TranslateLeft,
+            RctxTransform::ScaleIn,
+            RctxTransform::IncIterations,
+            RctxTransform::IncExp,
+            RctxTransform::SwitchFn,
+            RctxTransform::SwitchFamily,
+        ] {
+            let mut moved = rctx.clone();
+            moved.transform(&transform);
+            assert!(!moved.same_escapes(&rctx), "{:?}", transform);
+        }
+
+        // Velocities are normalized to the color period, so changing it evaluates them again.
+        let velocity = Rctx {
+            color_mode: ColorMode::Velocity,
+            ..Rctx::default()
+        };
+        let mut recolored = velocity.clone();
+        recolored.transform(&RctxTransform::IncColorPeriod);
+        assert!(!recolored.same_escapes(&velocity));
+    }
+
+    #[test]
+    fn profiled_iterations_count_up_to_the_escape() {
+        let rctx = Rctx::default();
+        let bound = rctx.bind(BOUNDS);
+        let escapes = bound.to_ematrix();
+        let profile = bound.to_iteration_ematrix();
+
+        let mut escaped = 0;
+        for (escape, iterations) in escapes.iter().zip(profile.iter()) {
+            let iterations = iterations.expect("every cell is profiled");
+            assert!(iterations <= f64::from(rctx.loc.max_iter));
+            // Escapes count the iterations before the one the orbit escaped on.
+            if let Some(escape) = escape {
+                escaped += 1;
+                assert_eq!(iterations, escape.ceil() + 1., "escape {}", escape);
+            }
+        }
+        assert!(escaped > 0);
+    }
+
+    #[test]
+    fn fit_to_image_keeps_the_center_and_extent() {
+        let spec = Rctx {
+            loc: Loc {
+                re0: -0.75,
+                im0: 0.1,
+                scalar: 5e-4,
+                max_iter: 500,
+            },
+            ..Rctx::default()
+        };
+        let frame = Bounds {
+            width: 80,
+            height: 40,
+        };
+        let mut preview = spec.clone();
+        preview.fit_to_image(&SPEC_BOUNDS, &frame);
+
+        assert_eq!(
+            (preview.loc.re0, preview.loc.im0),
+            (spec.loc.re0, spec.loc.im0)
+        );
+        assert_eq!(preview.loc.max_iter, spec.loc.max_iter);
+
+        // The shorter side of the frame spans the same complex extent as the image.
+        let image_extent = spec.loc.scalar * f64::from(SPEC_BOUNDS.height);
+        let frame_extent = preview.loc.scalar * f64::from(frame.height);
+        assert!((image_extent - frame_extent).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zooming_scales_by_the_zoom_factor() {
+        let mut rctx = Rctx {
+            zoom_factor: 1.5,
+            ..Rctx::default()
+        };
+        let scalar = rctx.loc.scalar;
+
+        rctx.transform(&RctxTransform::ScaleOut);
+        assert_eq!(rctx.loc.scalar, scalar * 1.5);
+        rctx.transform(&RctxTransform::ScaleOut);
+        assert_eq!(rctx.loc.scalar, scalar * 1.5 * 1.5);
+        rctx.transform(&RctxTransform::ScaleIn);
+        rctx.transform(&RctxTransform::ScaleIn);
+        assert!((rctx.loc.scalar - scalar).abs() < 1e-15);
+    }
+
+    #[test]
+    fn specs_without_a_zoom_factor_zoom_by_2() {
+        let mut spec = serde_json::to_value(Rctx::default()).unwrap();
+        spec.as_object_mut().unwrap().remove("zoom_factor");
+
+        let rctx = Rctx::from_spec_string(&spec.to_string()).unwrap();
+        assert_eq!(rctx.zoom_factor, 2.);
+    }
+
+    #[test]
+    fn specs_round_trip_through_strings() {
+        let rctx = Rctx {
+            loc: Loc {
+                re0: -0.7436,
+                im0: 0.1318,
+                scalar: 1e-12,
+                max_iter: 2000,
+            },
+            zoom_factor: 1.5,
+            ..Rctx::default()
+        };
+
+        let spec = rctx.to_spec_string().unwrap();
+        assert_eq!(Rctx::from_spec_string(&spec).unwrap(), rctx);
+    }
+
+    #[test]
+    fn spec_colorers_round_trip_and_load_bare_sine_colorers() {
+        let rctx = Rctx {
+            colorer: Colorer::Grayscale(crate::palette::GrayscalePalette::new(2.2)),
+            ..Rctx::default()
+        };
+        let spec = rctx.to_spec_string().unwrap();
+        assert!(spec.contains("\"Grayscale\""));
+        assert_eq!(Rctx::from_spec_string(&spec).unwrap(), rctx);
+
+        // Specs from before colorers were tagged give the SineRGB itself, or its name.
+        for (colorer, expected) in [
+            (
+                serde_json::to_value(crate::SineRGB::rgb_true()).unwrap(),
+                crate::SineRGB::rgb_true(),
+            ),
+            (serde_json::json!("sunset"), crate::SineRGB::default()),
+        ] {
+            let mut spec = serde_json::to_value(Rctx::default()).unwrap();
+            spec["colorer"] = colorer;
+            let rctx = Rctx::from_spec_string(&spec.to_string()).unwrap();
+            assert_eq!(rctx.colorer, Colorer::SineRGB(expected));
+        }
+    }
+
+    #[test]
+    fn specs_are_pretty_printed() {
+        let spec = Rctx::default().to_spec_string().unwrap();
+        assert!(spec.lines().count() > 1);
+        assert!(spec.contains("\n  \"loc\": {"));
+    }
+
+    #[test]
+    fn specs_render_with_their_stored_comp() {
+        let mut spec = serde_json::to_value(Rctx::default()).unwrap();
+        spec["comp"] = serde_json::json!([1.5, 1.0]);
+
+        let rctx = Rctx::from_spec_string(&spec.to_string()).unwrap();
+        assert_eq!(rctx.comp, (1.5, 1.));
+
+        // The top row of the frame is stretched vertically by the stored comp.
+        let top = rctx.complex_at(BOUNDS, Pos { x: 24, y: 0 });
+        let expected = rctx.loc.im0 - 1.5 * 16. * rctx.loc.scalar;
+        assert!((top.im - expected).abs() < 1e-12);
+
+        let unstretched = Rctx::default();
+        assert_ne!(
+            rctx.bind(BOUNDS).to_ematrix().inner(),
+            unstretched.bind(BOUNDS).to_ematrix().inner()
+        );
+
+        // Converting the comp for terminal cells and back keeps the stored comp.
+        let terminal = Rctx {
+            comp: rctx.terminal_comp(),
+            ..rctx.clone()
+        };
+        let image = terminal.image_comp();
+        assert!((image.0 - 1.5).abs() < 1e-12 && (image.1 - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn distance_coloring_differs_from_escape_coloring() {
+        let escape = Rctx::default();
+        let distance = Rctx {
+            color_mode: "distance".parse().unwrap(),
+            ..Rctx::default()
+        };
+        let (escapes, distances) = (
+            escape.bind(BOUNDS).to_ematrix(),
+            distance.bind(BOUNDS).to_ematrix(),
+        );
+        assert_ne!(escapes.inner(), distances.inner());
+
+        // Both modes agree on which points are interior.
+        assert_eq!(
+            escapes.inner().map(|e| e.is_none()),
+            distances.inner().map(|e| e.is_none())
+        );
+
+        // Specs from before color modes were added keep coloring by escape.
+        let mut spec = serde_json::to_value(Rctx::default()).unwrap();
+        spec.as_object_mut().unwrap().remove("color_mode");
+        let rctx = Rctx::from_spec_string(&spec.to_string()).unwrap();
+        assert_eq!(rctx.color_mode, ColorMode::Escape);
+    }
+
+    #[test]
+    fn stored_color_periods_round_trip_and_scale_the_palette() {
+        let rctx = Rctx {
+            color_period: 32.,
+            ..Rctx::default()
+        };
+        let loaded = Rctx::from_spec_string(&rctx.to_spec_string().unwrap()).unwrap();
+        assert_eq!(loaded.color_period, 32.);
+
+        // Halving the period cycles through the palette twice as often.
+        let (halved, default) = (loaded.palette(), Rctx::default().palette());
+        for i in 0..50 {
+            let escape = f64::from(i) * 0.7;
+            assert_eq!(halved.rgb(Some(escape)), default.rgb(Some(escape * 2.)));
+        }
+        assert_eq!(halved.rgb(None), default.rgb(None));
+    }
+
+    #[test]
+    fn vertical_oversampling_squares_terminal_samples() {
+        let cells = Bounds {
+            width: 40,
+            height: 20,
+        };
+        let samples = Bounds {
+            width: 40,
+            height: 40,
+        };
+        let terminal = Rctx::for_terminal(None);
+        let oversampled = terminal.with_vertical_oversample(2);
+
+        // Adjacent samples are about as far apart vertically as horizontally.
+        let at = |x, y| oversampled.complex_at(samples, Pos { x, y });
+        let (dx, dy) = ((at(11, 10) - at(10, 10)).re, (at(10, 11) - at(10, 10)).im);
+        let aspect = dy / dx;
+        assert!((0.8..1.2).contains(&aspect), "sample aspect {}", aspect);
+
+        // The oversampled rows cover the same region as the terminal cells.
+        let top = terminal.complex_at(cells, Pos { x: 10, y: 0 });
+        assert!((at(10, 0) - top).norm() < 1e-12);
+    }
+
+    #[test]
+    fn zooming_to_a_box_shows_its_complex_extents() {
+        let bounds = Bounds {
+            width: 80,
+            height: 40,
+        };
+        let rctx = Rctx::default();
+        let (a, b) = (Pos { x: 39, y: 10 }, Pos { x: 20, y: 19 });
+
+        // The outer edges of the corner cells of the box, and of the whole frame once zoomed.
+        let edges = |rctx: &Rctx, top_left: Pos, bottom_right: Pos| {
+            (
+                rctx.complex_at_subpixel(bounds, top_left, (-0.5, -0.5)),
+                rctx.complex_at_subpixel(bounds, bottom_right, (0.5, 0.5)),
+            )
+        };
+        let (box_min, box_max) = edges(&rctx, Pos { x: 20, y: 10 }, Pos { x: 39, y: 19 });
+
+        let mut zoomed = rctx.clone();
+        zoomed.zoom_to_box(bounds, a, b);
+        let (min, max) = edges(&zoomed, Pos { x: 0, y: 0 }, Pos { x: 79, y: 39 });
+
+        // The box spans a quarter of the frame on both axes, so it fills the zoomed frame to
+        // within the half cell that the frame center is offset by.
+        assert_eq!(zoomed.loc.scalar, rctx.loc.scalar / 4.);
+        let cell = zoomed.loc.scalar;
+        assert!((min - box_min).norm() <= cell, "{} and {}", min, box_min);
+        assert!((max - box_max).norm() <= cell, "{} and {}", max, box_max);
+    }
+
+    #[test]
+    fn extended_renders_match_a_full_render_at_the_new_limit() {
+        for color_mode in [ColorMode::Escape, ColorMode::Distance] {
+            let previous = Rctx {
+                loc: Loc {
+                    re0: -0.75,
+                    im0: 0.1,
+                    scalar: 1e-3,
+                    max_iter: 50,
+                },
+                color_mode,
+                ..Rctx::default()
+            };
+            let mut rctx = previous.clone();
+            rctx.transform(&RctxTransform::IncIterations);
+            rctx.transform(&RctxTransform::IncIterations);
+            assert!(rctx.extends_iterations(&previous));
+
+            let bound = rctx.bind(BOUNDS);
+            let extended = bound.to_ematrix_extended(previous.bind(BOUNDS).to_ematrix());
+            assert_eq!(
+                extended.inner(),
+                bound.to_ematrix().inner(),
+                "{:?}",
+                color_mode
+            );
+        }
+
+        // Moving the location invalidates every cell.
+        let previous = Rctx::default();
+        let mut moved = previous.clone();
+        moved.loc.max_iter += 100;
+        moved.loc.re0 += 0.1;
+        assert!(!moved.extends_iterations(&previous));
+        assert!(!previous.extends_iterations(&Rctx {
+            loc: Loc {
+                max_iter: 1000,
+                ..Loc::default()
+            },
+            ..Rctx::default()
+        }));
+    }
+
+    #[test]
+    fn switching_to_a_julia_set_and_back_round_trips() {
+        let mandelbrot = Mandelbrot {
+            exp: 3.,
+            check_periodicity: false,
+            ..Mandelbrot::default()
+        };
+        let mut rctx = Rctx {
+            complexfn: PolyComplexFn::Mandelbrot(mandelbrot.clone()),
+            ..Rctx::default()
+        };
+        rctx.loc.move_to(Complex64::new(-0.75, 0.1));
+        let start = rctx.clone();
+
+        // The exponent and orbit settings carry over in both directions.
+        rctx.transform(&RctxTransform::SwitchFn);
+        assert_eq!(
+            rctx.complexfn,
+            PolyComplexFn::Julia(Julia::from_c(&mandelbrot, start.loc.origin()))
+        );
+        assert_eq!(rctx.loc, start.loc);
+
+        // Moving around the Julia set is undone by switching back to the offset.
+        rctx.transform(&RctxTransform::TranslateLeft);
+        rctx.transform(&RctxTransform::SwitchFn);
+        assert_eq!(rctx.complexfn, start.complexfn);
+        assert_eq!(rctx.loc.origin(), start.loc.origin());
+    }
+
+    #[test]
+    fn switching_families_cycles_back_to_the_multibrot() {
+        let mut rctx = Rctx::default();
+        *rctx.complexfn.exp_mut() = 3.;
+        let start = rctx.clone();
+
+        let mut kinds = vec![];
+        for _ in 0..4 {
+            rctx.transform(&RctxTransform::SwitchFamily);
+            assert_eq!(rctx.complexfn.exp(), 3.);
+            assert_eq!(rctx.loc, start.loc);
+            kinds.push(std::mem::discriminant(&rctx.complexfn));
+        }
+        assert_eq!(rctx.complexfn, start.complexfn);
+        kinds.dedup();
+        assert_eq!(kinds.len(), 4);
+
+        // Functions without Julia sets switch back to the multibrot.
+        rctx.transform(&RctxTransform::SwitchFamily);
+        rctx.transform(&RctxTransform::SwitchFn);
+        assert_eq!(rctx.complexfn, start.complexfn);
+    }
+
+    #[test]
+    fn integer_exponents_step_by_whole_numbers() {
+        let mut rctx = Rctx {
+            integer_exp: true,
+            ..Rctx::default()
+        };
+        rctx.transform(&RctxTransform::IncExp);
+        assert_eq!(rctx.complexfn.exp(), 3.);
+        assert_eq!(
+            crate::polycomplex::integer_exp(rctx.complexfn.exp()),
+            Some(3)
+        );
+
+        // Cubic escapes match an orbit iterated by exact multiplication.
+        let escape_value = match &rctx.complexfn {
+            PolyComplexFn::Mandelbrot(m) => m.escape_value(),
+            f => panic!("{:?} isn't a multibrot", f),
+        };
+        for (re, im) in [(0.8, 0.3), (-0.3, 0.9), (-0.7, 0.6), (-0.1, 0.2)] {
+            let c = Complex64::new(re, im);
+            let mut z = Complex64::new(0., 0.);
+            let iters = (0..100).find(|_| {
+                z = z * z * z + c;
+                z.norm_sqr() > escape_value
+            });
+            let escape = rctx.complexfn.escape(c, 100);
+            assert_eq!(escape.map(|e| e.ceil() as u32), iters, "{}", c);
+        }
+
+        rctx.transform(&RctxTransform::DecExp);
+        rctx.transform(&RctxTransform::DecExp);
+        assert_eq!(rctx.complexfn.exp(), 2.);
+
+        let mut fractional = Rctx::default();
+        fractional.transform(&RctxTransform::IncExp);
+        assert_eq!(fractional.complexfn.exp(), 2. + Rctx::EXP_SCALAR);
+    }
+
+    #[test]
+    fn double_double_renders_resolve_detail_below_f64_precision() {
+        // Just right of the cusp of the main cardioid, where escapes slow down smoothly enough to
+        // differ between cells far narrower than an f64 ulp.
+        let row = Bounds {
+            width: 16,
+            height: 1,
+        };
+        let deep = Rctx {
+            loc: Loc {
+                im0: 0.,
+                re0: 0.25 + 1e-8,
+                scalar: 1e-17,
+                max_iter: 50_000,
+            },
+            ..Rctx::default()
+        };
+        assert!(deep.uses_double_double());
+        let double = Rctx {
+            precision: Precision::Double,
+            ..deep.clone()
+        };
+
+        let escapes = |rctx: &Rctx| -> Vec<f64> {
+            rctx.bind(row)
+                .to_ematrix()
+                .iter()
+                .map(|escape| escape.expect("every cell escapes"))
+                .collect()
+        };
+
+        // f64 rounds the cells onto a few distinct values, repeating escapes in blocks.
+        let mut blocks = escapes(&double);
+        blocks.dedup();
+        assert!(blocks.len() <= 4, "{:?}", blocks);
+
+        // Every double-double cell escapes a little sooner than the cell to its left.
+        let detailed = escapes(&deep);
+        for pair in detailed.windows(2) {
+            assert!(pair[1] < pair[0], "{:?}", detailed);
+        }
+    }
+
+    #[test]
+    fn precision_limits_are_reached_once_neighbors_round_together() {
+        let bounds = Bounds {
+            width: 80,
+            height: 40,
+        };
+        let at = |scalar: f64, precision: Precision| Rctx {
+            loc: Loc {
+                re0: -0.75,
+                scalar,
+                ..Loc::default()
+            },
+            precision,
+            ..Rctx::default()
+        };
+
+        assert!(!Rctx::default().at_precision_limit(bounds));
+        assert!(!at(1e-12, Precision::Double).at_precision_limit(bounds));
+        assert!(at(1e-17, Precision::Double).at_precision_limit(bounds));
+
+        // Double-double pushes the limit back by another 16 or so digits.
+        let deep = at(1e-17, Precision::Auto);
+        assert!(deep.uses_double_double());
+        assert!(!deep.at_precision_limit(bounds));
+        assert!(at(1e-33, Precision::Auto).at_precision_limit(bounds));
+    }
+}